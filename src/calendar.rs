@@ -0,0 +1,261 @@
+//! Counting down to the next event of an iCalendar (`.ics`) file.
+//!
+//! Only what a countdown needs is read: each `VEVENT`'s `SUMMARY` and
+//! `DTSTART`. Start times may be in UTC (`...Z`), in a `TZID` zone from the
+//! system database, or dates without a time. Floating times without a zone are
+//! taken as UTC. Recurrence rules (`RRULE`) aren't expanded, so only the first
+//! occurrence of a recurring event is seen.
+
+use crate::clock::{Zone, days_from_civil};
+use crate::engine::format_dhms;
+use crate::interval::CancelHandle;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// A calendar event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The event title (`SUMMARY`), empty if the event has none.
+    pub title: String,
+    /// When the event starts.
+    pub start: SystemTime,
+}
+
+/// The events of an iCalendar file, ordered by start time.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::calendar::Calendar;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let calendar = Calendar::from_ics(
+///     "BEGIN:VCALENDAR\r\n\
+///      BEGIN:VEVENT\r\nSUMMARY:Stand-up\r\nDTSTART:19700101T090000Z\r\nEND:VEVENT\r\n\
+///      BEGIN:VEVENT\r\nSUMMARY:Retro\r\nDTSTART:19700101T150000Z\r\nEND:VEVENT\r\n\
+///      END:VCALENDAR\r\n",
+/// )
+/// .unwrap();
+///
+/// // At 10:00 the stand-up has passed, so the countdown rolls over to the retro.
+/// let ten = UNIX_EPOCH + Duration::from_secs(10 * 3600);
+/// let (event, remaining) = calendar.countdown(ten).unwrap();
+/// assert_eq!(event.title, "Retro");
+/// assert_eq!(remaining, Duration::from_secs(5 * 3600));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Calendar {
+    events: Vec<Event>,
+}
+
+impl Calendar {
+    /// Parses the events of an iCalendar document.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Calendar)` with every event that has a start time.
+    /// * `Err("Invalid calendar data.")` if the document isn't a `VCALENDAR` or an event's
+    ///   start time can't be understood.
+    pub fn from_ics(ics: &str) -> Result<Calendar, &'static str> {
+        const INVALID: &str = "Invalid calendar data.";
+        let lines = unfold(ics);
+        if !lines
+            .iter()
+            .any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+        {
+            return Err(INVALID);
+        }
+
+        let mut events = Vec::new();
+        let mut current: Option<(String, Option<SystemTime>)> = None;
+        for line in &lines {
+            let Some((name, parameters, value)) = split_property(line) else {
+                continue;
+            };
+            match (name.to_ascii_uppercase().as_str(), &mut current) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                    current = Some((String::new(), None));
+                }
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                    if let Some((title, Some(start))) = current.take() {
+                        events.push(Event { title, start });
+                    }
+                }
+                ("SUMMARY", Some((title, _))) => *title = unescape(value),
+                ("DTSTART", Some((_, start))) => {
+                    *start = Some(parse_start(parameters, value).ok_or(INVALID)?);
+                }
+                _ => {}
+            }
+        }
+
+        events.sort_by_key(|event| event.start);
+        Ok(Calendar { events })
+    }
+
+    /// Reads and parses an iCalendar file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Calendar)` if the file was read and parsed.
+    /// * `Err(io::Error)` if it couldn't be read, or with `InvalidData` if it isn't valid.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Calendar> {
+        let ics = fs::read_to_string(path)?;
+        Calendar::from_ics(&ics).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// All events, earliest first.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The first event starting after `now`.
+    pub fn next_event(&self, now: SystemTime) -> Option<&Event> {
+        self.events.iter().find(|event| event.start > now)
+    }
+
+    /// The next event after `now` and the time remaining until it starts.
+    pub fn countdown(&self, now: SystemTime) -> Option<(&Event, Duration)> {
+        let event = self.next_event(now)?;
+        Some((event, event.start.duration_since(now).unwrap_or_default()))
+    }
+
+    /// Renders `title in h:m:s` for the next event once a second, rolling over to the
+    /// following event whenever one starts, until `handle` is cancelled or no events remain.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use clock_timer::{calendar::Calendar, interval::CancelHandle};
+    /// use std::io::stdout;
+    ///
+    /// let calendar = Calendar::load("work.ics").unwrap();
+    /// calendar.run(&mut stdout(), &CancelHandle::new()).unwrap();
+    /// ```
+    pub fn run<W: Write>(&self, writer: &mut W, handle: &CancelHandle) -> io::Result<()> {
+        loop {
+            let Some((event, remaining)) = self.countdown(SystemTime::now()) else {
+                writeln!(writer)?;
+                return Ok(());
+            };
+            // Pad so a shorter line fully overwrites the previous one.
+            write!(
+                writer,
+                "{} in {}\x1b[K\r",
+                event.title,
+                format_dhms(remaining.as_secs())
+            )?;
+            writer.flush()?;
+
+            // Tick on the event's second boundaries so the display hits 0:0:0 on time.
+            let step = Duration::from_nanos(remaining.subsec_nanos().into());
+            let step = if step.is_zero() {
+                Duration::from_secs(1)
+            } else {
+                step
+            };
+            if handle.wait_until(Instant::now() + step) {
+                writeln!(writer)?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Splits a document into logical lines, joining folded continuation lines.
+pub(crate) fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits `NAME;PARAM=x;PARAM=y:value` into its name, parameters and value.
+pub(crate) fn split_property(line: &str) -> Option<(&str, &str, &str)> {
+    let colon = line.find(':')?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    match head.find(';') {
+        Some(semicolon) => Some((&head[..semicolon], &head[semicolon + 1..], value)),
+        None => Some((head, "", value)),
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Parses a `DTSTART` value, honouring its `TZID` parameter.
+fn parse_start(parameters: &str, value: &str) -> Option<SystemTime> {
+    let zone = parameters
+        .split(';')
+        .find_map(|parameter| parameter.strip_prefix("TZID="))
+        .map(|tzid| Zone::load(tzid.trim_matches('"')).ok())
+        .unwrap_or_else(|| Some(Zone::utc()))?;
+
+    let (date, time, utc) = match value.split_once('T') {
+        Some((date, time)) => match time.strip_suffix('Z') {
+            Some(time) => (date, time, true),
+            None => (date, time, false),
+        },
+        None => (value, "000000", false),
+    };
+    if date.len() != 8
+        || time.len() != 6
+        || !(date.chars().chain(time.chars())).all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let number = |text: &str| text.parse::<u8>().ok();
+    let year: i64 = date[..4].parse().ok()?;
+    let (month, day) = (number(&date[4..6])?, number(&date[6..])?);
+    let (hour, minute, second) = (
+        number(&time[..2])?,
+        number(&time[2..4])?,
+        number(&time[4..])?,
+    );
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let local = days_from_civil(year, month, day) * 86_400
+        + i64::from(hour) * 3600
+        + i64::from(minute) * 60
+        + i64::from(second);
+    let unix = if utc {
+        local
+    } else {
+        zone.local_to_unix(local)
+    };
+    if unix >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(unix.unsigned_abs()))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(unix.unsigned_abs()))
+    }
+}
@@ -0,0 +1,927 @@
+//! Wall-clock time in one or more time zones.
+//!
+//! IANA zones such as `Europe/Berlin` are read from the system time zone
+//! database (`$TZDIR`, or `/usr/share/zoneinfo`), which is kept up to date by
+//! the OS. Times past the last recorded transition follow the POSIX TZ rule
+//! stored at the end of each zone file. Fixed offsets and POSIX TZ strings can
+//! be used directly where no database is available.
+
+use crate::engine::format_dhms;
+use crate::interval::CancelHandle;
+use crate::output::MultiLineRenderer;
+use crate::terminal::{self, Direction};
+use crate::time_source::unix_seconds;
+use std::{
+    env, fmt, fs,
+    io::{self, Write},
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// A time zone: a name plus the rules mapping UTC to local time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zone {
+    name: String,
+    rules: Rules,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Rules {
+    Fixed(LocalType),
+    Posix(PosixRule),
+    Tzif(Tzif),
+}
+
+/// One kind of local time: its UTC offset and abbreviation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LocalType {
+    offset: i32,
+    is_dst: bool,
+    abbreviation: String,
+}
+
+impl Zone {
+    /// Coordinated Universal Time.
+    pub fn utc() -> Zone {
+        Zone::fixed("UTC", 0)
+    }
+
+    /// A zone `offset` seconds east of UTC that never changes, e.g. `Zone::fixed("IST", 19_800)`.
+    pub fn fixed(name: impl Into<String>, offset: i32) -> Zone {
+        let name = name.into();
+        Zone {
+            rules: Rules::Fixed(LocalType {
+                offset,
+                is_dst: false,
+                abbreviation: name.clone(),
+            }),
+            name,
+        }
+    }
+
+    /// A zone following a POSIX TZ string such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Zone)` named after the string.
+    /// * `Err("Invalid TZ string.")` if the string can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::clock::Zone;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let zone = Zone::from_posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    /// let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    /// let winter = at(1_705_320_000); // 2024-01-15 12:00 UTC
+    /// let summer = at(1_721_044_800); // 2024-07-15 12:00 UTC
+    /// assert_eq!(zone.wall_time(winter).to_string(), "13:00:00 CET");
+    /// assert_eq!(zone.wall_time(summer).to_string(), "14:00:00 CEST");
+    ///
+    /// // In the southern hemisphere daylight saving time spans the new year.
+    /// let sydney = Zone::from_posix("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+    /// assert_eq!(sydney.wall_time(winter).to_string(), "23:00:00 AEDT");
+    /// assert_eq!(sydney.wall_time(summer).to_string(), "22:00:00 AEST");
+    /// assert_eq!(sydney.wall_time(at(1_712_419_199)).to_string(), "02:59:59 AEDT");
+    /// assert_eq!(sydney.wall_time(at(1_712_419_200)).to_string(), "02:00:00 AEST");
+    /// assert_eq!(sydney.wall_time(at(1_728_143_999)).to_string(), "01:59:59 AEST");
+    /// assert_eq!(sydney.wall_time(at(1_728_144_000)).to_string(), "03:00:00 AEDT");
+    /// let new_year = sydney.wall_time(at(1_735_651_800)); // 2024-12-31 13:30 UTC
+    /// assert_eq!((new_year.year, new_year.month, new_year.day), (2025, 1, 1));
+    /// assert_eq!(new_year.to_string(), "00:30:00 AEDT");
+    ///
+    /// // Lord Howe Island moves its clocks by only 30 minutes.
+    /// let lord_howe = Zone::from_posix("<+1030>-10:30<+11>-11,M10.1.0,M4.1.0").unwrap();
+    /// assert_eq!(lord_howe.wall_time(winter).offset, 39_600);
+    /// assert_eq!(lord_howe.wall_time(summer).offset, 37_800);
+    /// assert_eq!(lord_howe.wall_time(at(1_712_415_599)).to_string(), "01:59:59 +11");
+    /// assert_eq!(lord_howe.wall_time(at(1_712_415_600)).to_string(), "01:30:00 +1030");
+    /// assert_eq!(lord_howe.wall_time(at(1_728_142_200)).to_string(), "02:30:00 +11");
+    /// ```
+    pub fn from_posix(tz: &str) -> Result<Zone, &'static str> {
+        Ok(Zone {
+            name: tz.to_string(),
+            rules: Rules::Posix(PosixRule::parse(tz)?),
+        })
+    }
+
+    /// Loads the IANA zone `name` (e.g. `America/New_York`) from the system database.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Zone)` if the zone file exists and is valid.
+    /// * `Err(io::Error)` if it couldn't be read, or with `InvalidData` if it isn't a
+    ///   valid zone file or `name` isn't a plain zone name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::clock::Zone;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// // Not every system ships a time zone database.
+    /// if let Ok(zone) = Zone::load("America/New_York") {
+    ///     let summer = UNIX_EPOCH + Duration::from_secs(1_721_044_800); // 2024-07-15 12:00 UTC
+    ///     assert_eq!(zone.wall_time(summer).to_string(), "08:00:00 EDT");
+    /// }
+    /// ```
+    pub fn load(name: &str) -> io::Result<Zone> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        if name.is_empty()
+            || name.starts_with('/')
+            || name.split('/').any(|part| part == ".." || part == ".")
+        {
+            return Err(invalid("invalid time zone name"));
+        }
+
+        let directory = env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/usr/share/zoneinfo"));
+        let data = fs::read(directory.join(name))?;
+        Zone::from_tzif(name, &data).map_err(invalid)
+    }
+
+    /// A zone read from the contents of a TZif zone file, e.g. one bundled with an
+    /// application for systems without a time zone database.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Zone)` if `data` is a valid zone file of any version.
+    /// * `Err("invalid time zone file")` if it is truncated or corrupt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::clock::Zone;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// # fn tzif(version: u8, transitions: &[(i64, u8)], types: &[(i32, bool, &str)], footer: &str) -> Vec<u8> {
+    /// #     let mut abbreviations = Vec::new();
+    /// #     let mut indexes = Vec::new();
+    /// #     for (_, _, name) in types {
+    /// #         indexes.push(abbreviations.len() as u8);
+    /// #         abbreviations.extend(name.bytes().chain([0]));
+    /// #     }
+    /// #     let block = |time_size: usize| {
+    /// #         let mut data = b"TZif".to_vec();
+    /// #         data.push(version);
+    /// #         data.extend([0; 15 + 12]);
+    /// #         for count in [transitions.len(), types.len(), abbreviations.len()] {
+    /// #             data.extend((count as u32).to_be_bytes());
+    /// #         }
+    /// #         for (time, _) in transitions {
+    /// #             data.extend(&time.to_be_bytes()[8 - time_size..]);
+    /// #         }
+    /// #         data.extend(transitions.iter().map(|(_, index)| index));
+    /// #         for ((offset, is_dst, _), index) in types.iter().zip(&indexes) {
+    /// #             data.extend(offset.to_be_bytes());
+    /// #             data.extend([u8::from(*is_dst), *index]);
+    /// #         }
+    /// #         data.extend(&abbreviations);
+    /// #         data
+    /// #     };
+    /// #     let mut data = block(4);
+    /// #     if version >= b'2' {
+    /// #         data.extend(block(8));
+    /// #         data.extend(format!("\n{}\n", footer).bytes());
+    /// #     }
+    /// #     data
+    /// # }
+    /// let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    /// // A daylight type listed first, a standard one, and a switch between them in 2001.
+    /// let types = [(7200, true, "XDT"), (3600, false, "XST")];
+    /// let transitions = [(1_000_000_000, 1), (1_000_003_600, 0)];
+    ///
+    /// // Version 1 files have 32-bit times and no footer rule.
+    /// let v1 = Zone::from_tzif("v1", &tzif(0, &transitions, &types, "")).unwrap();
+    /// // Before the first transition, the first standard type applies.
+    /// assert_eq!(v1.wall_time(at(0)).to_string(), "01:00:00 XST");
+    /// assert_eq!(v1.wall_time(at(1_000_000_000)).to_string(), "02:46:40 XST");
+    /// assert_eq!(v1.wall_time(at(1_721_044_800)).to_string(), "14:00:00 XDT");
+    ///
+    /// // Version 2 files follow their footer rule after the last transition.
+    /// let data = tzif(b'2', &transitions, &types, "CET-1CEST,M3.5.0,M10.5.0/3");
+    /// let v2 = Zone::from_tzif("v2", &data).unwrap();
+    /// assert_eq!(v2.wall_time(at(0)).to_string(), "01:00:00 XST");
+    /// assert_eq!(v2.wall_time(at(1_000_003_599)).to_string(), "03:46:39 XST");
+    /// assert_eq!(v2.wall_time(at(1_705_320_000)).to_string(), "13:00:00 CET");
+    /// assert_eq!(v2.wall_time(at(1_721_044_800)).to_string(), "14:00:00 CEST");
+    ///
+    /// // Truncated or corrupt files are rejected.
+    /// assert!(Zone::from_tzif("empty", b"").is_err());
+    /// assert!(Zone::from_tzif("truncated", &data[..data.len() / 2]).is_err());
+    /// assert!(Zone::from_tzif("magic", &[b"TZjf", &data[4..]].concat()).is_err());
+    /// assert!(Zone::from_tzif("no types", &tzif(b'2', &[], &[], "UTC0")).is_err());
+    /// let bad_index = tzif(0, &[(0, 2)], &types, "");
+    /// assert_eq!(Zone::from_tzif("index", &bad_index), Err("invalid time zone file"));
+    /// ```
+    pub fn from_tzif(name: impl Into<String>, data: &[u8]) -> Result<Zone, &'static str> {
+        Ok(Zone {
+            name: name.into(),
+            rules: Rules::Tzif(Tzif::parse(data)?),
+        })
+    }
+
+    /// The name the zone was created with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Converts a local date and time in this zone to seconds since the epoch.
+    ///
+    /// Times skipped by a daylight saving change resolve using the offset from before
+    /// the change; repeated times resolve to their first occurrence.
+    pub(crate) fn local_to_unix(&self, local: i64) -> i64 {
+        let guess = local - i64::from(self.local_type(local).offset);
+        let earlier = local - i64::from(self.local_type(guess - 3600).offset);
+        let later = local - i64::from(self.local_type(guess).offset);
+        // Prefer the first occurrence when the local time maps back onto itself.
+        [earlier, later]
+            .into_iter()
+            .find(|&utc| utc + i64::from(self.local_type(utc).offset) == local)
+            .unwrap_or(earlier)
+    }
+
+    fn local_type(&self, utc: i64) -> &LocalType {
+        match &self.rules {
+            Rules::Fixed(local_type) => local_type,
+            Rules::Posix(rule) => rule.local_type(utc),
+            Rules::Tzif(tzif) => tzif.local_type(utc),
+        }
+    }
+
+    /// The local time in this zone at `time`.
+    pub fn wall_time(&self, time: SystemTime) -> WallTime {
+        let utc = unix_seconds(time);
+        let local_type = self.local_type(utc);
+
+        let local = utc + i64::from(local_type.offset);
+        let (year, month, day) = civil_from_days(local.div_euclid(SECS_PER_DAY));
+        let seconds_of_day = local.rem_euclid(SECS_PER_DAY);
+        WallTime {
+            year,
+            month,
+            day,
+            hour: (seconds_of_day / 3600) as u8,
+            minute: (seconds_of_day % 3600 / 60) as u8,
+            second: (seconds_of_day % 60) as u8,
+            offset: local_type.offset,
+            is_dst: local_type.is_dst,
+            abbreviation: local_type.abbreviation.clone(),
+        }
+    }
+}
+
+/// A local date and time in some zone.
+///
+/// Displays as `HH:MM:SS ABBR`; the date is available through the fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WallTime {
+    /// The year.
+    pub year: i64,
+    /// The month, 1-12.
+    pub month: u8,
+    /// The day of the month, 1-31.
+    pub day: u8,
+    /// The hour, 0-23.
+    pub hour: u8,
+    /// The minute, 0-59.
+    pub minute: u8,
+    /// The second, 0-59.
+    pub second: u8,
+    /// The offset from UTC in seconds.
+    pub offset: i32,
+    /// Whether daylight saving time is in effect.
+    pub is_dst: bool,
+    /// The zone abbreviation in effect, such as `CEST`.
+    pub abbreviation: String,
+}
+
+impl fmt::Display for WallTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02} {}",
+            self.hour, self.minute, self.second, self.abbreviation
+        )
+    }
+}
+
+/// Displays the current time in several zones, one line each, updating every second.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::clock::{WorldClock, Zone};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let clock = WorldClock::new()
+///     .zone("London", Zone::fixed("GMT", 0))
+///     .zone("Mumbai", Zone::fixed("IST", 19_800));
+///
+/// let lines = clock.lines(UNIX_EPOCH + Duration::from_secs(12 * 3600));
+/// assert_eq!(lines, ["London  12:00:00 GMT", "Mumbai  17:30:00 IST"]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldClock {
+    zones: Vec<(String, Zone)>,
+    direction: Direction,
+}
+
+impl WorldClock {
+    /// Creates a world clock without any zones.
+    pub fn new() -> WorldClock {
+        WorldClock::default()
+    }
+
+    /// Adds a line showing the time in `zone`, labelled `label`.
+    pub fn zone(mut self, label: impl Into<String>, zone: Zone) -> WorldClock {
+        self.zones.push((label.into(), zone));
+        self
+    }
+
+    /// Lays every zone out in `direction`, e.g. `Direction::Rtl` for Hebrew or Arabic labels.
+    pub fn direction(mut self, direction: Direction) -> WorldClock {
+        self.direction = direction;
+        self
+    }
+
+    /// The `label  HH:MM:SS ABBR` line of every zone at `time`, labels padded to the same width,
+    /// or `HH:MM:SS ABBR  label` when laid out right to left.
+    pub fn lines(&self, time: SystemTime) -> Vec<String> {
+        let width = self
+            .zones
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.zones
+            .iter()
+            .map(|(label, zone)| {
+                terminal::label_line(
+                    label,
+                    width,
+                    &zone.wall_time(time).to_string(),
+                    self.direction,
+                )
+            })
+            .collect()
+    }
+
+    /// Redraws every zone on `writer` at the start of each second until `handle` is cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use clock_timer::{clock::{WorldClock, Zone}, interval::CancelHandle};
+    /// use std::io::stdout;
+    ///
+    /// let clock = WorldClock::new()
+    ///     .zone("Berlin", Zone::load("Europe/Berlin").unwrap())
+    ///     .zone("Tokyo", Zone::load("Asia/Tokyo").unwrap());
+    /// clock.run(&mut stdout(), &CancelHandle::new()).unwrap();
+    /// ```
+    pub fn run<W: Write>(&self, writer: &mut W, handle: &CancelHandle) -> io::Result<()> {
+        let mut renderer = MultiLineRenderer::new();
+        loop {
+            let now = SystemTime::now();
+            renderer.draw(writer, &self.lines(now))?;
+
+            // Wake up just after the next whole second so the display ticks with the clock.
+            let into_second = now
+                .duration_since(UNIX_EPOCH)
+                .map_or(Duration::ZERO, |since| {
+                    Duration::from_nanos(since.subsec_nanos().into())
+                });
+            let deadline = Instant::now() + (Duration::from_secs(1) - into_second);
+            if handle.wait_until(deadline) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Counts down to a meeting, showing its start time in each of a world clock's zones.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::clock::{MeetingCountdown, WorldClock, Zone};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let zones = WorldClock::new()
+///     .zone("London", Zone::fixed("GMT", 0))
+///     .zone("Mumbai", Zone::fixed("IST", 19_800));
+/// let start = UNIX_EPOCH + Duration::from_secs(12 * 3600);
+/// let meeting = MeetingCountdown::new("Planning", start, zones);
+///
+/// let lines = meeting.lines(start - Duration::from_secs(90));
+/// assert_eq!(
+///     lines,
+///     ["Planning in 0:1:30", "London  12:00:00 GMT", "Mumbai  17:30:00 IST"]
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeetingCountdown {
+    title: String,
+    start: SystemTime,
+    zones: WorldClock,
+}
+
+impl MeetingCountdown {
+    /// Creates a countdown to `title` starting at `start`, shown in every zone of `zones`.
+    pub fn new(title: impl Into<String>, start: SystemTime, zones: WorldClock) -> MeetingCountdown {
+        MeetingCountdown {
+            title: title.into(),
+            start,
+            zones,
+        }
+    }
+
+    /// The time left until the meeting starts at `now`, zero once it has started.
+    pub fn remaining(&self, now: SystemTime) -> Duration {
+        self.start.duration_since(now).unwrap_or_default()
+    }
+
+    /// A `title in h:m:s` line followed by the start time in every zone.
+    pub fn lines(&self, now: SystemTime) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{} in {}",
+            self.title,
+            format_dhms(self.remaining(now).as_secs())
+        )];
+        lines.extend(self.zones.lines(self.start));
+        lines
+    }
+
+    /// Redraws the countdown on `writer` every second until the meeting starts or `handle`
+    /// is cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use clock_timer::{
+    ///     clock::{MeetingCountdown, WorldClock, Zone},
+    ///     interval::CancelHandle,
+    /// };
+    /// use std::{io::stdout, time::{Duration, SystemTime}};
+    ///
+    /// let zones = WorldClock::new()
+    ///     .zone("Berlin", Zone::load("Europe/Berlin").unwrap())
+    ///     .zone("New York", Zone::load("America/New_York").unwrap());
+    /// let start = SystemTime::now() + Duration::from_secs(15 * 60);
+    /// MeetingCountdown::new("Sync", start, zones)
+    ///     .run(&mut stdout(), &CancelHandle::new())
+    ///     .unwrap();
+    /// ```
+    pub fn run<W: Write>(&self, writer: &mut W, handle: &CancelHandle) -> io::Result<()> {
+        let mut renderer = MultiLineRenderer::new();
+        loop {
+            let now = SystemTime::now();
+            renderer.draw(writer, &self.lines(now))?;
+
+            let remaining = self.remaining(now);
+            if remaining.is_zero() {
+                return Ok(());
+            }
+            // Tick on the start time's second boundaries so the last redraw lands on 0:0:0.
+            let step = Duration::from_nanos(remaining.subsec_nanos().into());
+            let step = if step.is_zero() {
+                Duration::from_secs(1)
+            } else {
+                step
+            };
+            if handle.wait_until(Instant::now() + step) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// A POSIX TZ rule: standard time plus optional daylight saving time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PosixRule {
+    standard: LocalType,
+    daylight: Option<(LocalType, Transition, Transition)>,
+}
+
+/// When a daylight saving period starts or ends: a date rule plus local time of day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Transition {
+    date: DateRule,
+    time: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateRule {
+    /// `Jn`: day 1-365, February 29 never counted.
+    Julian(u16),
+    /// `n`: day 0-365, February 29 counted in leap years.
+    ZeroBased(u16),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (5 = last) of month `m`.
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+}
+
+impl PosixRule {
+    fn parse(tz: &str) -> Result<PosixRule, &'static str> {
+        const INVALID: &str = "Invalid TZ string.";
+        let mut rest = tz;
+
+        let standard_name = parse_name(&mut rest).ok_or(INVALID)?;
+        // POSIX offsets count west of UTC, the opposite of what we store.
+        let standard_offset = -parse_time(&mut rest).ok_or(INVALID)?;
+        let standard = LocalType {
+            offset: i32::try_from(standard_offset).map_err(|_| INVALID)?,
+            is_dst: false,
+            abbreviation: standard_name,
+        };
+        if rest.is_empty() {
+            return Ok(PosixRule {
+                standard,
+                daylight: None,
+            });
+        }
+
+        let daylight_name = parse_name(&mut rest).ok_or(INVALID)?;
+        let daylight_offset = if rest.is_empty() || rest.starts_with(',') {
+            standard_offset + 3600
+        } else {
+            -parse_time(&mut rest).ok_or(INVALID)?
+        };
+        let daylight = LocalType {
+            offset: i32::try_from(daylight_offset).map_err(|_| INVALID)?,
+            is_dst: true,
+            abbreviation: daylight_name,
+        };
+
+        // Without explicit rules, fall back to the current US rules as POSIX suggests.
+        let rules = if rest.is_empty() {
+            ",M3.2.0,M11.1.0"
+        } else {
+            rest
+        };
+        let mut rules = rules.strip_prefix(',').ok_or(INVALID)?;
+        let start = parse_transition(&mut rules).ok_or(INVALID)?;
+        rules = rules.strip_prefix(',').ok_or(INVALID)?;
+        let end = parse_transition(&mut rules).ok_or(INVALID)?;
+        if !rules.is_empty() {
+            return Err(INVALID);
+        }
+
+        Ok(PosixRule {
+            standard,
+            daylight: Some((daylight, start, end)),
+        })
+    }
+
+    fn local_type(&self, utc: i64) -> &LocalType {
+        let Some((daylight, start, end)) = &self.daylight else {
+            return &self.standard;
+        };
+
+        let standard_offset = i64::from(self.standard.offset);
+        let (year, _, _) = civil_from_days((utc + standard_offset).div_euclid(SECS_PER_DAY));
+        // Transitions are given in the local time in effect just before them.
+        let start_utc = start.local_seconds(year) - standard_offset;
+        let end_utc = end.local_seconds(year) - i64::from(daylight.offset);
+
+        let in_daylight = if start_utc < end_utc {
+            start_utc <= utc && utc < end_utc
+        } else {
+            // Southern hemisphere: daylight saving time spans the new year.
+            !(end_utc <= utc && utc < start_utc)
+        };
+        if in_daylight {
+            daylight
+        } else {
+            &self.standard
+        }
+    }
+}
+
+impl Transition {
+    /// Seconds since the epoch, in local time, at which this transition happens in `year`.
+    fn local_seconds(&self, year: i64) -> i64 {
+        let jan_1 = days_from_civil(year, 1, 1);
+        let day = match self.date {
+            DateRule::Julian(n) => {
+                let n = i64::from(n);
+                jan_1 + n - 1 + i64::from(is_leap(year) && n >= 60)
+            }
+            DateRule::ZeroBased(n) => jan_1 + i64::from(n),
+            DateRule::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                // 1970-01-01 was a Thursday, weekday 4 counting from Sunday.
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day =
+                    (i64::from(weekday) - first_weekday).rem_euclid(7) + 7 * (i64::from(week) - 1);
+                while day >= i64::from(days_in_month(year, month)) {
+                    day -= 7;
+                }
+                first + day
+            }
+        };
+        day * SECS_PER_DAY + self.time
+    }
+}
+
+/// Parses a zone abbreviation: three or more letters, or anything inside `<...>`.
+fn parse_name(rest: &mut &str) -> Option<String> {
+    let (name, remaining) = if let Some(quoted) = rest.strip_prefix('<') {
+        let end = quoted.find('>')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        (&rest[..end], &rest[end..])
+    };
+    if name.len() < 3 {
+        return None;
+    }
+    *rest = remaining;
+    Some(name.to_string())
+}
+
+/// Parses `[+-]hh[:mm[:ss]]` into seconds.
+fn parse_time(rest: &mut &str) -> Option<i64> {
+    let (sign, unsigned) = match rest.as_bytes().first() {
+        Some(b'-') => (-1, &rest[1..]),
+        Some(b'+') => (1, &rest[1..]),
+        _ => (1, *rest),
+    };
+    let end = unsigned
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(unsigned.len());
+    let mut seconds = 0;
+    let mut parts = 0;
+    for (index, part) in unsigned[..end].split(':').enumerate() {
+        let value: i64 = part.parse().ok()?;
+        if index > 2 || (index > 0 && value > 59) {
+            return None;
+        }
+        seconds += value * [3600, 60, 1][index];
+        parts += 1;
+    }
+    if parts == 0 {
+        return None;
+    }
+    *rest = &unsigned[end..];
+    Some(sign * seconds)
+}
+
+/// Parses a `date[/time]` transition; the time defaults to 02:00.
+fn parse_transition(rest: &mut &str) -> Option<Transition> {
+    let end = rest.find([',', '/']).unwrap_or(rest.len());
+    let date = &rest[..end];
+    let date = if let Some(month_week_day) = date.strip_prefix('M') {
+        let mut fields = month_week_day.split('.').map(|field| field.parse::<u8>());
+        let (Some(Ok(month)), Some(Ok(week)), Some(Ok(weekday)), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return None;
+        };
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+            return None;
+        }
+        DateRule::MonthWeekDay {
+            month,
+            week,
+            weekday,
+        }
+    } else if let Some(day) = date.strip_prefix('J') {
+        let day = day.parse().ok().filter(|day| (1..=365).contains(day))?;
+        DateRule::Julian(day)
+    } else {
+        DateRule::ZeroBased(date.parse().ok().filter(|day| *day <= 365)?)
+    };
+    *rest = &rest[end..];
+
+    let time = match rest.strip_prefix('/') {
+        Some(mut time) => {
+            let seconds = parse_time(&mut time)?;
+            *rest = time;
+            seconds
+        }
+        None => 2 * 3600,
+    };
+    Some(Transition { date, time })
+}
+
+/// The contents of a TZif zone file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Tzif {
+    transitions: Vec<i64>,
+    /// Index into `types` for each transition.
+    transition_types: Vec<u8>,
+    types: Vec<LocalType>,
+    /// The rule for times after the last transition, from the file's footer.
+    footer: Option<PosixRule>,
+}
+
+impl Tzif {
+    fn parse(data: &[u8]) -> Result<Tzif, &'static str> {
+        const INVALID: &str = "invalid time zone file";
+        let mut reader = Reader { data };
+
+        let header = Header::parse(&mut reader).ok_or(INVALID)?;
+        if header.version < b'2' {
+            return header.body(&mut reader, 4).ok_or(INVALID);
+        }
+
+        // Skip the 32-bit body; version 2+ files repeat everything with 64-bit times.
+        reader.take(header.body_len(4)).ok_or(INVALID)?;
+        let header = Header::parse(&mut reader).ok_or(INVALID)?;
+        let mut tzif = header.body(&mut reader, 8).ok_or(INVALID)?;
+
+        let footer = reader
+            .data
+            .strip_prefix(b"\n")
+            .and_then(|footer| footer.split(|&byte| byte == b'\n').next())
+            .and_then(|footer| std::str::from_utf8(footer).ok())
+            .filter(|footer| !footer.is_empty());
+        tzif.footer = footer.and_then(|footer| PosixRule::parse(footer).ok());
+        Ok(tzif)
+    }
+
+    fn local_type(&self, utc: i64) -> &LocalType {
+        let after = self
+            .transitions
+            .partition_point(|&transition| transition <= utc);
+        if let (true, Some(footer)) = (after == self.transitions.len(), &self.footer) {
+            return footer.local_type(utc);
+        }
+        match after.checked_sub(1) {
+            Some(index) => &self.types[usize::from(self.transition_types[index])],
+            // Before the first transition, the first standard time type applies.
+            None => self
+                .types
+                .iter()
+                .find(|local_type| !local_type.is_dst)
+                .unwrap_or(&self.types[0]),
+        }
+    }
+}
+
+struct Header {
+    version: u8,
+    utc_indicators: usize,
+    standard_indicators: usize,
+    leap_seconds: usize,
+    transitions: usize,
+    types: usize,
+    abbreviation_bytes: usize,
+}
+
+impl Header {
+    fn parse(reader: &mut Reader<'_>) -> Option<Header> {
+        if reader.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = reader.take(1)?[0];
+        reader.take(15)?;
+        let mut count = || reader.u32().map(|count| count as usize);
+        Some(Header {
+            version,
+            utc_indicators: count()?,
+            standard_indicators: count()?,
+            leap_seconds: count()?,
+            transitions: count()?,
+            types: count()?,
+            abbreviation_bytes: count()?,
+        })
+    }
+
+    fn body_len(&self, time_size: usize) -> usize {
+        self.transitions * (time_size + 1)
+            + self.types * 6
+            + self.abbreviation_bytes
+            + self.leap_seconds * (time_size + 4)
+            + self.standard_indicators
+            + self.utc_indicators
+    }
+
+    fn body(&self, reader: &mut Reader<'_>, time_size: usize) -> Option<Tzif> {
+        if self.types == 0 {
+            return None;
+        }
+        let transitions = (0..self.transitions)
+            .map(|_| {
+                if time_size == 8 {
+                    reader.i64()
+                } else {
+                    reader.u32().map(|time| i64::from(time as i32))
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let transition_types = reader.take(self.transitions)?.to_vec();
+        let raw_types = (0..self.types)
+            .map(|_| Some((reader.u32()? as i32, reader.take(1)?[0], reader.take(1)?[0])))
+            .collect::<Option<Vec<_>>>()?;
+        let abbreviations = reader.take(self.abbreviation_bytes)?;
+        reader.take(
+            self.leap_seconds * (time_size + 4) + self.standard_indicators + self.utc_indicators,
+        )?;
+
+        if transition_types
+            .iter()
+            .any(|&index| usize::from(index) >= self.types)
+        {
+            return None;
+        }
+        let types = raw_types
+            .into_iter()
+            .map(|(offset, is_dst, abbreviation_index)| {
+                let start = abbreviations.get(usize::from(abbreviation_index)..)?;
+                let end = start.iter().position(|&byte| byte == 0)?;
+                Some(LocalType {
+                    offset,
+                    is_dst: is_dst != 0,
+                    abbreviation: String::from_utf8_lossy(&start[..end]).into_owned(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Tzif {
+            transitions,
+            transition_types,
+            types,
+            footer: None,
+        })
+    }
+}
+
+/// A cursor over big-endian binary data.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub(crate) fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date of a count of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
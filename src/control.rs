@@ -0,0 +1,274 @@
+//! Control of a running timer from other processes over a Unix domain socket.
+//!
+//! A `ControlServer` listens next to the tick loop and applies one command per
+//! line to a `TimerController`, answering each with one line:
+//!
+//! | Command          | Effect                          | Reply                         |
+//! |------------------|---------------------------------|-------------------------------|
+//! | `pause`          | pauses the countdown            | `ok`                          |
+//! | `resume`         | resumes it                      | `ok`                          |
+//! | `cancel`         | cancels the run                 | `ok`                          |
+//! | `status`         | nothing                         | `remaining=83 paused=false`   |
+//! | `usage`          | nothing                         | `wakeups=12 busy_us=840`      |
+//! | `add <secs>`     | adds time                       | `ok`                          |
+//! | `subtract <secs>`| subtracts time                  | `ok`                          |
+//!
+//! Anything else is answered with `error: <message>`. `send` is the client side,
+//! e.g. for a `ctl pause` subcommand run from another terminal.
+//!
+//! An `OwnerWatch` ties a timer to another process instead, cancelling it once
+//! that process has exited.
+
+use crate::timer::TimerController;
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How often an `OwnerWatch` checks whether its process is still running.
+pub const OWNER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Listens on a Unix domain socket and applies the commands it receives to a timer.
+///
+/// The socket file is removed when the server is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::{
+///     control::{self, ControlServer},
+///     timer::{TimerStruct, TimerTrait},
+/// };
+/// use std::io::sink;
+///
+/// let path = std::env::temp_dir().join("clock-timer-control-doctest.sock");
+/// let (handle, controller) = TimerStruct::new(0, 5, 0).unwrap().spawn(sink());
+/// let server = ControlServer::bind(&path, controller).unwrap();
+///
+/// assert_eq!(control::send(&path, "pause").unwrap(), "ok");
+/// assert!(control::send(&path, "status").unwrap().ends_with("paused=true"));
+/// assert_eq!(control::send(&path, "cancel").unwrap(), "ok");
+///
+/// handle.join().unwrap().unwrap();
+/// drop(server);
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct ControlServer {
+    path: PathBuf,
+    stopped: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Binds a socket at `path` and starts serving commands for `controller` on a
+    /// background thread.
+    ///
+    /// A socket file left behind by a process that has exited is replaced.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ControlServer)` once the socket is listening.
+    /// * `Err(io::Error)` with `AddrInUse` if another timer is already listening at `path`,
+    ///   or if the socket couldn't be created.
+    pub fn bind(path: impl AsRef<Path>, controller: TimerController) -> io::Result<ControlServer> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("a timer is already listening at {}", path.display()),
+                ));
+            }
+            fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                // A client that fails mid-connection only loses its own reply.
+                let Ok(stream) = stream else { continue };
+                let controller = controller.clone();
+                thread::spawn(move || {
+                    let _ = serve(stream, &controller);
+                });
+            }
+        });
+
+        Ok(ControlServer {
+            path,
+            stopped,
+            listener: Some(handle),
+        })
+    }
+
+    /// The path of the socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake the listener blocked in `accept` so it sees the flag.
+        let _ = UnixStream::connect(&self.path);
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Cancels a timer once the process that owns it has exited, so a timer started on
+/// behalf of a script that crashed doesn't linger.
+///
+/// The process is checked every `OWNER_POLL_INTERVAL` until it exits or the watch is
+/// dropped. A child process counts as running until it has been waited for.
+pub struct OwnerWatch {
+    stopped: Arc<AtomicBool>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl OwnerWatch {
+    /// Starts watching process `pid` on behalf of the timer run behind `controller`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OwnerWatch)` watching the process.
+    /// * `Err` with `InvalidInput` if `pid` is 0 or out of range for a process ID.
+    pub fn start(pid: u32, controller: TimerController) -> io::Result<OwnerWatch> {
+        let pid = libc::pid_t::try_from(pid)
+            .ok()
+            .filter(|&pid| pid > 0)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid owner process ID")
+            })?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let handle = thread::spawn(move || {
+            while !thread_stopped.load(Ordering::SeqCst) {
+                if !process_running(pid) {
+                    controller.cancel();
+                    return;
+                }
+                thread::park_timeout(OWNER_POLL_INTERVAL);
+            }
+        });
+        Ok(OwnerWatch {
+            stopped,
+            watcher: Some(handle),
+        })
+    }
+}
+
+impl Drop for OwnerWatch {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Some(watcher) = self.watcher.take() {
+            watcher.thread().unpark();
+            let _ = watcher.join();
+        }
+    }
+}
+
+/// Whether process `pid` exists, including one owned by another user.
+fn process_running(pid: libc::pid_t) -> bool {
+    // SAFETY: `kill` has no memory-safety preconditions; signal 0 only checks the process.
+    let signalled = unsafe { libc::kill(pid, 0) } == 0;
+    signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Answers every line received on `stream` until the client hangs up.
+fn serve(stream: UnixStream, controller: &TimerController) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        writeln!(writer, "{}", respond(controller, &line?))?;
+    }
+    Ok(())
+}
+
+/// Applies one protocol command to `controller` and returns the reply line, without
+/// its newline.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::{
+///     control::respond,
+///     timer::{TimerStruct, TimerTrait},
+/// };
+/// use std::io::sink;
+///
+/// let (handle, controller) = TimerStruct::new(0, 1, 0).unwrap().spawn(sink());
+/// assert_eq!(respond(&controller, "pause"), "ok");
+/// assert_eq!(respond(&controller, "add 60"), "ok");
+/// assert_eq!(respond(&controller, "add soon"), "error: expected a number of seconds");
+/// assert!(respond(&controller, "usage").starts_with("wakeups="));
+/// assert_eq!(respond(&controller, "snooze"), "error: unknown command");
+/// respond(&controller, "cancel");
+/// handle.join().unwrap().unwrap();
+/// ```
+pub fn respond(controller: &TimerController, line: &str) -> String {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default();
+    let seconds = words.next().map(str::parse::<u64>);
+    if words.next().is_some() {
+        return String::from("error: too many arguments");
+    }
+    match (command, seconds) {
+        ("pause", None) => controller.pause(),
+        ("resume", None) => controller.resume(),
+        ("cancel", None) => controller.cancel(),
+        ("status", None) => {
+            return format!(
+                "remaining={} paused={}",
+                controller.remaining().as_secs(),
+                controller.is_paused()
+            );
+        }
+        ("usage", None) => {
+            let usage = controller.usage();
+            return format!(
+                "wakeups={} busy_us={}",
+                usage.wakeups,
+                usage.busy.as_micros()
+            );
+        }
+        ("add", Some(Ok(seconds))) => controller.add_time(Duration::from_secs(seconds)),
+        ("subtract", Some(Ok(seconds))) => controller.subtract_time(Duration::from_secs(seconds)),
+        ("add" | "subtract", _) => return String::from("error: expected a number of seconds"),
+        ("pause" | "resume" | "cancel" | "status" | "usage", Some(_)) => {
+            return String::from("error: too many arguments");
+        }
+        _ => return String::from("error: unknown command"),
+    }
+    String::from("ok")
+}
+
+/// Sends `command` to the timer listening at `path` and returns its reply line.
+///
+/// # Returns
+///
+/// * `Ok(String)` with the reply, which starts with `error: ` if the command was rejected.
+/// * `Err(io::Error)` if no timer is listening at `path` or the connection failed.
+pub fn send(path: impl AsRef<Path>, command: &str) -> io::Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", command)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
@@ -0,0 +1,207 @@
+//! A self-test for bug reports: how accurately this machine counts down, and
+//! which of the terminal, signal, notification and sound support the crate can
+//! use on it.
+//!
+//! `diagnose` gathers a `Report`, which displays as a plain-text block to
+//! paste into an issue.
+
+use crate::output::ColorChoice;
+use crate::terminal;
+use crate::time_source::{CalibratedClock, Calibration, SleepStrategy, calibration};
+use crate::timer::{TimerBuilder, TimerTrait};
+use std::{
+    env, fmt,
+    io::{self, IsTerminal},
+    time::{Duration, Instant},
+};
+
+/// The length of each test countdown.
+pub const COUNTDOWN: Duration = Duration::from_secs(1);
+
+/// One test countdown and how long it really took.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CountdownCheck {
+    /// The clock the countdown ran on: `system` or `calibrated`.
+    pub clock: &'static str,
+    /// How long the countdown was set for.
+    pub expected: Duration,
+    /// How long it took, or `None` if it failed to run.
+    pub measured: Option<Duration>,
+}
+
+impl CountdownCheck {
+    /// How far the real duration was from the expected one.
+    pub fn drift(&self) -> Option<Duration> {
+        self.measured
+            .map(|measured| measured.abs_diff(self.expected))
+    }
+}
+
+/// Whether an external tool the crate relies on is usable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// The crate was built without the feature that uses it.
+    Disabled(&'static str),
+    /// The tool wasn't found on `PATH`.
+    Missing(&'static str),
+    /// The tool was found on `PATH`.
+    Found(&'static str),
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Disabled(feature) => write!(f, "disabled (built without `{}`)", feature),
+            Backend::Missing(tool) => write!(f, "unavailable ({} not found)", tool),
+            Backend::Found(tool) => write!(f, "{}", tool),
+        }
+    }
+}
+
+/// What `diagnose` found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// How accurately the platform sleeps.
+    pub calibration: Calibration,
+    /// A countdown on the system clock, then one on a `CalibratedClock`.
+    pub countdowns: Vec<CountdownCheck>,
+    /// Whether standard output is a terminal.
+    pub stdout_terminal: bool,
+    /// Whether ANSI escape sequences can be written to it.
+    pub ansi: bool,
+    /// Whether colour is on by default, i.e. `NO_COLOR` isn't set.
+    pub color: bool,
+    /// Whether `SIGTERM` and `SIGHUP` stop a stopwatch as well as Ctrl+C.
+    pub termination_signals: bool,
+    /// The desktop notification tool.
+    pub notifications: Backend,
+    /// The audio file player. The terminal bell needs no tool.
+    pub sound: Backend,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "clock-timer {} on {}/{}",
+            env!("CARGO_PKG_VERSION"),
+            env::consts::OS,
+            env::consts::ARCH
+        )?;
+        let strategy = match self.calibration.strategy {
+            SleepStrategy::Plain => "plain sleeps".to_string(),
+            SleepStrategy::Hybrid { spin } => format!("hybrid sleeps spinning {:?}", spin),
+        };
+        writeln!(
+            f,
+            "sleep: mean oversleep {:?}, max {:?}, {}",
+            self.calibration.mean_oversleep, self.calibration.max_oversleep, strategy
+        )?;
+        for check in &self.countdowns {
+            match (check.measured, check.drift()) {
+                (Some(measured), Some(drift)) => writeln!(
+                    f,
+                    "countdown ({} clock): {:?} took {:?} (drift {:?})",
+                    check.clock, check.expected, measured, drift
+                )?,
+                _ => writeln!(f, "countdown ({} clock): failed to run", check.clock)?,
+            }
+        }
+        writeln!(
+            f,
+            "terminal: stdout {} a terminal, ANSI {}, colour {}",
+            if self.stdout_terminal { "is" } else { "is not" },
+            if self.ansi {
+                "supported"
+            } else {
+                "unsupported"
+            },
+            if self.color { "on" } else { "off (NO_COLOR)" }
+        )?;
+        writeln!(
+            f,
+            "signals: {}",
+            if self.termination_signals {
+                "Ctrl+C, SIGTERM and SIGHUP"
+            } else {
+                "Ctrl+C only"
+            }
+        )?;
+        writeln!(f, "notifications: {}", self.notifications)?;
+        writeln!(f, "sound: {}", self.sound)
+    }
+}
+
+/// Runs the self-test, blocking for two `COUNTDOWN`s.
+///
+/// Nothing is shown or played: the notification and sound tools are only looked up.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::doctor::diagnose;
+///
+/// let report = diagnose();
+/// assert_eq!(report.countdowns.len(), 2);
+/// assert!(report.countdowns.iter().all(|check| check.measured.is_some()));
+/// eprint!("{}", report);
+/// ```
+pub fn diagnose() -> Report {
+    let countdowns = vec![
+        CountdownCheck {
+            clock: "system",
+            expected: COUNTDOWN,
+            measured: time_countdown(TimerBuilder::new(0, 0, COUNTDOWN.as_secs() as u32)),
+        },
+        CountdownCheck {
+            clock: "calibrated",
+            expected: COUNTDOWN,
+            measured: time_countdown(
+                TimerBuilder::new(0, 0, COUNTDOWN.as_secs() as u32).clock(CalibratedClock::new()),
+            ),
+        },
+    ];
+
+    #[cfg(feature = "notify")]
+    let notifications = find_tool(&[crate::notify::TOOL]);
+    #[cfg(not(feature = "notify"))]
+    let notifications = Backend::Disabled("notify");
+    #[cfg(feature = "sound")]
+    let sound = find_tool(crate::sound::PLAYERS);
+    #[cfg(not(feature = "sound"))]
+    let sound = Backend::Disabled("sound");
+
+    Report {
+        calibration: calibration(),
+        countdowns,
+        stdout_terminal: io::stdout().is_terminal(),
+        ansi: terminal::ansi_supported(),
+        color: ColorChoice::Auto.enabled(),
+        termination_signals: cfg!(all(unix, feature = "signals")),
+        notifications,
+        sound,
+    }
+}
+
+/// Runs the countdown `builder` describes without output and returns how long it took.
+fn time_countdown(builder: TimerBuilder) -> Option<Duration> {
+    let timer = builder.build().ok()?;
+    let started = Instant::now();
+    timer.start_timer(&mut io::sink()).ok()?;
+    Some(started.elapsed())
+}
+
+/// The first of `tools` found on `PATH`.
+#[cfg(any(feature = "notify", feature = "sound"))]
+fn find_tool(tools: &'static [&'static str]) -> Backend {
+    let paths = env::var_os("PATH").unwrap_or_default();
+    tools
+        .iter()
+        .find(|tool| {
+            env::split_paths(&paths).any(|dir| {
+                let path = dir.join(tool);
+                path.is_file() || (cfg!(windows) && path.with_extension("exe").is_file())
+            })
+        })
+        .map_or(Backend::Missing(tools[0]), |tool| Backend::Found(tool))
+}
@@ -0,0 +1,376 @@
+//! Platform-agnostic timekeeping shared by the native and wasm front-ends.
+//!
+//! Nothing in here sleeps, spawns threads or touches the terminal. Front-ends
+//! read their own monotonic clock (`Instant` natively, `performance.now()` in
+//! the browser) and pass the current reading in as a `Duration` measured from
+//! any fixed origin they like.
+//!
+//! This module, `format`, `parse`, `segment`, `share`, `splits` and `led`
+//! build without the default `std` feature, in `no_std` environments with an
+//! allocator. Embedded front-ends pass in readings from a hardware timer the
+//! same way.
+
+use crate::format::{self as time_format, UnitLabels};
+use alloc::{format, string::String};
+use core::time::Duration;
+
+/// The stopwatch state machine: accumulated time plus the start of the current run.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::StopwatchEngine;
+/// use std::time::Duration;
+///
+/// let mut engine = StopwatchEngine::new();
+/// engine.start(Duration::from_secs(10));
+/// assert_eq!(engine.elapsed(Duration::from_secs(15)), Duration::from_secs(5));
+///
+/// engine.stop(Duration::from_secs(15));
+/// engine.start(Duration::from_secs(100));
+/// assert_eq!(engine.elapsed(Duration::from_secs(101)), Duration::from_secs(6));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StopwatchEngine {
+    accumulated: Duration,
+    started_at: Option<Duration>,
+}
+
+impl StopwatchEngine {
+    /// Creates a stopped engine with no elapsed time.
+    pub const fn new() -> StopwatchEngine {
+        StopwatchEngine {
+            accumulated: Duration::ZERO,
+            started_at: None,
+        }
+    }
+
+    /// Creates a stopped engine that has already accumulated `elapsed`.
+    pub const fn from_elapsed(elapsed: Duration) -> StopwatchEngine {
+        StopwatchEngine {
+            accumulated: elapsed,
+            started_at: None,
+        }
+    }
+
+    /// Starts a run at clock reading `now`. Returns `false` if it was already running.
+    pub fn start(&mut self, now: Duration) -> bool {
+        if self.started_at.is_some() {
+            return false;
+        }
+        self.started_at = Some(now);
+        true
+    }
+
+    /// Ends the current run at clock reading `now`. Returns `false` if it wasn't running.
+    pub fn stop(&mut self, now: Duration) -> bool {
+        match self.started_at.take() {
+            Some(started_at) => {
+                self.accumulated += now.saturating_sub(started_at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clears the elapsed time. A running engine keeps running from zero at `now`.
+    pub fn reset(&mut self, now: Duration) {
+        self.accumulated = Duration::ZERO;
+        if self.started_at.is_some() {
+            self.started_at = Some(now);
+        }
+    }
+
+    /// Returns `true` while a run is in progress.
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// The total elapsed time at clock reading `now`.
+    pub fn elapsed(&self, now: Duration) -> Duration {
+        let running = self
+            .started_at
+            .map_or(Duration::ZERO, |started_at| now.saturating_sub(started_at));
+        self.accumulated + running
+    }
+
+    /// The elapsed time of all completed runs, excluding the current one.
+    pub fn accumulated(&self) -> Duration {
+        self.accumulated
+    }
+
+    /// The clock reading at which the current run started, if running.
+    pub fn started_at(&self) -> Option<Duration> {
+        self.started_at
+    }
+}
+
+/// The countdown state machine: a target duration and the time spent running towards it.
+///
+/// Pausing is stopping the underlying stopwatch; the remaining time is whatever of the
+/// target the running time hasn't used up.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::CountdownEngine;
+/// use std::time::Duration;
+///
+/// let secs = Duration::from_secs;
+/// let mut countdown = CountdownEngine::from_hms(0, 1, 0).unwrap();
+/// countdown.start(secs(0));
+/// assert_eq!(countdown.remaining(secs(20)), secs(40));
+///
+/// // Paused from 20s to 50s, so only 10 more seconds count.
+/// countdown.pause(secs(20));
+/// countdown.start(secs(50));
+/// assert_eq!(countdown.remaining(secs(60)), secs(30));
+///
+/// // The 30 second mark was crossed between 55s and 60s.
+/// assert!(countdown.crossed(secs(30), secs(55), secs(60)));
+/// assert!(countdown.is_finished(secs(90)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CountdownEngine {
+    total: Duration,
+    running: StopwatchEngine,
+}
+
+impl CountdownEngine {
+    /// Creates a paused countdown from `total`.
+    pub const fn new(total: Duration) -> CountdownEngine {
+        CountdownEngine {
+            total,
+            running: StopwatchEngine::new(),
+        }
+    }
+
+    /// Creates a paused countdown from hours, minutes and seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CountdownEngine)` if the duration is greater than 0.
+    /// * `Err("Duration need to be 1 or more seconds.")` if the total duration is 0.
+    pub fn from_hms(
+        hours: u32,
+        minutes: u32,
+        seconds: u32,
+    ) -> Result<CountdownEngine, &'static str> {
+        let total = hms_secs(hours, minutes, seconds);
+        if total == 0 {
+            return Err("Duration need to be 1 or more seconds.");
+        }
+        Ok(CountdownEngine::new(Duration::from_secs(total)))
+    }
+
+    /// Starts or resumes counting down at clock reading `now`. Returns `false` if it was
+    /// already running.
+    pub fn start(&mut self, now: Duration) -> bool {
+        self.running.start(now)
+    }
+
+    /// Pauses at clock reading `now`. Returns `false` if it wasn't running.
+    pub fn pause(&mut self, now: Duration) -> bool {
+        self.running.stop(now)
+    }
+
+    /// Returns `true` while counting down.
+    pub fn is_running(&self) -> bool {
+        self.running.is_running()
+    }
+
+    /// The target duration, including time added or subtracted since.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Adds `time` to the target.
+    pub fn add_time(&mut self, time: Duration) {
+        self.total = self.total.saturating_add(time);
+    }
+
+    /// Subtracts `time` from the target, clamping at zero.
+    pub fn subtract_time(&mut self, time: Duration) {
+        self.total = self.total.saturating_sub(time);
+    }
+
+    /// The time left at clock reading `now`, zero once the countdown is over.
+    pub fn remaining(&self, now: Duration) -> Duration {
+        self.total.saturating_sub(self.running.elapsed(now))
+    }
+
+    /// Returns `true` once no time is left at clock reading `now`.
+    pub fn is_finished(&self, now: Duration) -> bool {
+        self.remaining(now).is_zero()
+    }
+
+    /// Returns `true` if the remaining time reached `mark` between clock readings `since`
+    /// and `now`, for firing threshold callbacks exactly once.
+    pub fn crossed(&self, mark: Duration, since: Duration, now: Duration) -> bool {
+        self.remaining(since) > mark && self.remaining(now) <= mark
+    }
+}
+
+/// How remaining time maps onto a progress display, from full at the start to empty at
+/// zero.
+///
+/// `Linear` drains evenly. The others drain slowly at first and quickly towards the
+/// end, so the last stretch of a countdown takes up more of the bar.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::Easing;
+/// use std::time::Duration;
+///
+/// let (half, total) = (Duration::from_secs(30), Duration::from_secs(60));
+/// assert_eq!(Easing::Linear.apply(half, total), half);
+/// assert_eq!(Easing::Quadratic.apply(half, total), Duration::from_secs(45));
+/// assert_eq!(Easing::Cubic.apply(half, total), Duration::from_millis(52_500));
+/// assert_eq!(Easing::Exponential.apply(half, total).as_secs(), 58);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// The display follows the remaining time.
+    #[default]
+    Linear,
+    /// `1 - (1 - x)²` of the display stays lit with `x` of the time left.
+    Quadratic,
+    /// `1 - (1 - x)³` of the display stays lit with `x` of the time left.
+    Cubic,
+    /// `(1 - 2^(-10x)) / (1 - 2^(-10))` of the display stays lit with `x` of the time
+    /// left, so the final tenth covers half of it.
+    Exponential,
+}
+
+impl Easing {
+    /// The remaining time to display with `remaining` out of `total` left, on the same
+    /// scale as `total`.
+    ///
+    /// Any time left is shown as more than zero.
+    pub fn apply(self, remaining: Duration, total: Duration) -> Duration {
+        let remaining = remaining.min(total);
+        if remaining.is_zero() {
+            return Duration::ZERO;
+        }
+        let x = remaining.as_secs_f64() / total.as_secs_f64();
+        let eased = match self {
+            Easing::Linear => return remaining,
+            Easing::Quadratic => 1.0 - (1.0 - x) * (1.0 - x),
+            Easing::Cubic => 1.0 - (1.0 - x) * (1.0 - x) * (1.0 - x),
+            Easing::Exponential => (1.0 - exp2_neg(10.0 * x)) / (1.0 - exp2_neg(10.0)),
+        };
+        total.mul_f64(eased.min(1.0)).max(Duration::from_nanos(1))
+    }
+}
+
+/// `2^-y` for `y` from 0 to 10, without the float functions only `std` provides.
+fn exp2_neg(y: f64) -> f64 {
+    let whole = y as u32;
+    let z = -(y - whole as f64) * core::f64::consts::LN_2;
+    // The Taylor series of e^z converges to full precision for |z| < 0.7.
+    let (mut term, mut sum) = (1.0, 1.0);
+    for k in 1..16 {
+        term *= z / k as f64;
+        sum += term;
+    }
+    sum / (1u32 << whole) as f64
+}
+
+/// The total seconds in `hours`, `minutes` and `seconds`. Three `u32` components can't
+/// overflow a `u64`.
+pub(crate) const fn hms_secs(hours: u32, minutes: u32, seconds: u32) -> u64 {
+    hours as u64 * 3600 + minutes as u64 * 60 + seconds as u64
+}
+
+/// Builds a `Duration` from hours, minutes and seconds in a const context; `duration_hms!`
+/// calls it in a `const` block so mistakes fail the build.
+///
+/// # Panics
+///
+/// If the duration is 0.
+pub const fn duration_hms(hours: u32, minutes: u32, seconds: u32) -> Duration {
+    match hms_secs(hours, minutes, seconds) {
+        0 => panic!("Duration need to be 1 or more seconds."),
+        total => Duration::from_secs(total),
+    }
+}
+
+/// Formats a number of seconds as `h:m:s`, the way the timer and stopwatch display it.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::format_hms;
+///
+/// assert_eq!(format_hms(3723), "1:2:3");
+/// ```
+pub fn format_hms(total_seconds: u64) -> String {
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    format!("{}:{}:{}", hours, minutes, seconds)
+}
+
+/// Formats a number of seconds like `format_hms` below a day, and with a days component
+/// and a zero-padded clock from one day up, so multi-day countdowns stay readable.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::format_dhms;
+///
+/// assert_eq!(format_dhms(3723), "1:2:3");
+/// assert_eq!(format_dhms(3 * 86_400 + 4 * 3600 + 12 * 60 + 9), "3d 04:12:09");
+/// ```
+pub fn format_dhms(total_seconds: u64) -> String {
+    let days = total_seconds / 86_400;
+    if days == 0 {
+        return format_hms(total_seconds);
+    }
+    let rest = total_seconds % 86_400;
+    format!(
+        "{}d {:02}:{:02}:{:02}",
+        days,
+        rest / 3600,
+        (rest % 3600) / 60,
+        rest % 60
+    )
+}
+
+/// Formats `duration` as an ISO 8601 duration such as `PT1H30M`. See
+/// `format::iso8601_into_fmt`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::engine::format_iso8601;
+/// use std::time::Duration;
+///
+/// assert_eq!(format_iso8601(Duration::from_secs(5400)), "PT1H30M");
+/// assert_eq!(format_iso8601(Duration::ZERO), "PT0S");
+/// ```
+pub fn format_iso8601(duration: Duration) -> String {
+    let mut text = String::new();
+    // Writing to a `String` can't fail.
+    let _ = time_format::iso8601_into_fmt(duration, &mut text);
+    text
+}
+
+/// Spells out a number of seconds with `labels`, such as `1h 5m 3s` with
+/// `format::Labels::COMPACT`. See `format::humanize_into_fmt`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::{engine::humanize, format::Labels};
+///
+/// assert_eq!(humanize(3903, &Labels::COMPACT), "1h 5m 3s");
+/// assert_eq!(humanize(120, &Labels::COMPACT), "2m");
+/// ```
+pub fn humanize<L: UnitLabels + ?Sized>(total_seconds: u64, labels: &L) -> String {
+    let mut text = String::new();
+    // Writing to a `String` can't fail.
+    let _ = time_format::humanize_into_fmt(total_seconds, labels, &mut text);
+    text
+}
@@ -0,0 +1,436 @@
+//! Allocation-free time formatting.
+//!
+//! These functions render a number of seconds as zero-padded `HH:MM:SS`
+//! (hours grow past two digits when needed) straight into a caller-provided
+//! byte buffer or `fmt::Write` sink, for hot paths and environments without a
+//! heap. `humanize_into_fmt` spells durations out as `1h 5m 3s` instead, with
+//! unit labels supplied through `UnitLabels` for other styles and languages, and
+//! `iso8601_into_fmt` writes ISO 8601 durations for exchange with other programs.
+
+use core::{fmt, time::Duration};
+
+/// Writes `total_seconds` as `HH:MM:SS` into `writer`, returning the number of bytes written.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::format_into_fmt;
+///
+/// let mut line = String::from("Remaining: ");
+/// let written = format_into_fmt(3723, &mut line).unwrap();
+/// assert_eq!(line, "Remaining: 01:02:03");
+/// assert_eq!(written, 8);
+/// ```
+pub fn format_into_fmt<W: fmt::Write + ?Sized>(
+    total_seconds: u64,
+    writer: &mut W,
+) -> Result<usize, fmt::Error> {
+    format_into_fmt_width(total_seconds, 2, writer)
+}
+
+/// Writes `total_seconds` as `HH:MM:SS` into `buffer`, returning the number of bytes written.
+///
+/// # Returns
+///
+/// * `Ok(len)` with the formatted time in `buffer[..len]`.
+/// * `Err(fmt::Error)` if `buffer` is too small; its contents are then unspecified.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::format_into;
+///
+/// let mut buffer = [0u8; 16];
+/// let len = format_into(59, &mut buffer).unwrap();
+/// assert_eq!(&buffer[..len], b"00:00:59");
+///
+/// assert!(format_into(59, &mut [0u8; 4]).is_err());
+/// ```
+pub fn format_into(total_seconds: u64, buffer: &mut [u8]) -> Result<usize, fmt::Error> {
+    let mut writer = SliceWriter {
+        buffer,
+        position: 0,
+    };
+    format_into_fmt(total_seconds, &mut writer)
+}
+
+/// A `fmt::Write` adapter over a fixed byte slice that fails instead of growing.
+struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = self.position + text.len();
+        let target = self.buffer.get_mut(self.position..end).ok_or(fmt::Error)?;
+        target.copy_from_slice(text.as_bytes());
+        self.position = end;
+        Ok(())
+    }
+}
+
+/// The number of decimal digits in `value`.
+fn digits(mut value: u64) -> usize {
+    let mut count = 1;
+    while value >= 10 {
+        value /= 10;
+        count += 1;
+    }
+    count
+}
+
+/// `HH:MM:SS` formatting with the hours field fixed at `HOUR_DIGITS` characters.
+///
+/// Every successful call writes exactly `FixedWidth::<HOUR_DIGITS>::LEN` bytes,
+/// which is what fixed-character displays (LCDs, segment modules) need. Times
+/// whose hours don't fit in `HOUR_DIGITS` digits are rejected rather than
+/// widening the output.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::FixedWidth;
+///
+/// type Lcd = FixedWidth<1>;
+///
+/// let mut buffer = [0u8; Lcd::LEN];
+/// assert_eq!(Lcd::format_into(3723, &mut buffer), Ok(7));
+/// assert_eq!(&buffer, b"1:02:03");
+///
+/// // Ten hours doesn't fit a one-digit hours field.
+/// assert!(Lcd::format_into(36_000, &mut buffer).is_err());
+///
+/// let mut wide = [0u8; FixedWidth::<3>::LEN];
+/// FixedWidth::<3>::format_into(59, &mut wide).unwrap();
+/// assert_eq!(&wide, b"000:00:59");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedWidth<const HOUR_DIGITS: usize>;
+
+impl<const HOUR_DIGITS: usize> FixedWidth<HOUR_DIGITS> {
+    /// The number of bytes every formatted time occupies.
+    pub const LEN: usize = HOUR_DIGITS + 6;
+
+    /// Writes `total_seconds` into `buffer`, returning `Self::LEN`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self::LEN)` with the formatted time in `buffer[..Self::LEN]`.
+    /// * `Err(fmt::Error)` if `buffer` is shorter than `Self::LEN` or the hours
+    ///   need more than `HOUR_DIGITS` digits.
+    pub fn format_into(total_seconds: u64, buffer: &mut [u8]) -> Result<usize, fmt::Error> {
+        let mut writer = SliceWriter {
+            buffer,
+            position: 0,
+        };
+        Self::format_into_fmt(total_seconds, &mut writer)
+    }
+
+    /// Writes `total_seconds` into `writer`, returning `Self::LEN`.
+    ///
+    /// Fails under the same conditions as `format_into`, except that the
+    /// writer decides whether it has room.
+    pub fn format_into_fmt<W: fmt::Write + ?Sized>(
+        total_seconds: u64,
+        writer: &mut W,
+    ) -> Result<usize, fmt::Error> {
+        if HOUR_DIGITS == 0 || digits(total_seconds / 3600) > HOUR_DIGITS {
+            return Err(fmt::Error);
+        }
+        format_into_fmt_width(total_seconds, HOUR_DIGITS, writer)
+    }
+}
+
+/// A unit a human-readable duration is broken into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
+    /// Hours, the largest unit; they aren't carried into days.
+    Hour,
+    /// Minutes.
+    Minute,
+    /// Seconds.
+    Second,
+}
+
+/// The labels `humanize_into_fmt` puts after each amount.
+///
+/// Implement it to supply plural rules: `label` sees the amount, so a language can pick
+/// between as many forms as it needs.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::{TimeUnit, UnitLabels, humanize_into_fmt};
+///
+/// struct Polish;
+///
+/// impl UnitLabels for Polish {
+///     fn label(&self, unit: TimeUnit, amount: u64) -> &str {
+///         let few = (2..=4).contains(&(amount % 10)) && !(12..=14).contains(&(amount % 100));
+///         match (unit, amount, few) {
+///             (TimeUnit::Minute, 1, _) => "minuta",
+///             (TimeUnit::Minute, _, true) => "minuty",
+///             (TimeUnit::Minute, _, false) => "minut",
+///             (TimeUnit::Hour, 1, _) => "godzina",
+///             (TimeUnit::Hour, _, true) => "godziny",
+///             (TimeUnit::Hour, _, false) => "godzin",
+///             (TimeUnit::Second, 1, _) => "sekunda",
+///             (TimeUnit::Second, _, true) => "sekundy",
+///             (TimeUnit::Second, _, false) => "sekund",
+///         }
+///     }
+///
+///     fn gap(&self) -> &str {
+///         " "
+///     }
+/// }
+///
+/// let mut text = String::new();
+/// humanize_into_fmt(22 * 60, &Polish, &mut text).unwrap();
+/// assert_eq!(text, "22 minuty");
+/// ```
+pub trait UnitLabels {
+    /// The label for `amount` of `unit`.
+    fn label(&self, unit: TimeUnit, amount: u64) -> &str;
+
+    /// Written between an amount and its label. Empty by default, as in `5m`.
+    fn gap(&self) -> &str {
+        ""
+    }
+
+    /// Written between units. A space by default, as in `1h 5m`.
+    fn separator(&self) -> &str {
+        " "
+    }
+}
+
+/// Fixed unit labels that don't change with the amount, such as abbreviations.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::{Labels, humanize_into_fmt};
+///
+/// let german = Labels { hour: "Std", minute: "Min", second: "Sek", gap: " ", separator: " " };
+/// let japanese = Labels { hour: "時間", minute: "分", second: "秒", gap: "", separator: "" };
+///
+/// let mut text = String::new();
+/// humanize_into_fmt(3900, &german, &mut text).unwrap();
+/// assert_eq!(text, "1 Std 5 Min");
+///
+/// text.clear();
+/// humanize_into_fmt(3900, &japanese, &mut text).unwrap();
+/// assert_eq!(text, "1時間5分");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Labels<'a> {
+    /// The label for hours.
+    pub hour: &'a str,
+    /// The label for minutes.
+    pub minute: &'a str,
+    /// The label for seconds.
+    pub second: &'a str,
+    /// Written between an amount and its label.
+    pub gap: &'a str,
+    /// Written between units.
+    pub separator: &'a str,
+}
+
+impl Labels<'static> {
+    /// `1h 5m 3s`.
+    pub const COMPACT: Labels<'static> = Labels {
+        hour: "h",
+        minute: "m",
+        second: "s",
+        gap: "",
+        separator: " ",
+    };
+}
+
+impl Default for Labels<'static> {
+    fn default() -> Labels<'static> {
+        Labels::COMPACT
+    }
+}
+
+/// Full English unit words, singular or plural with the amount: `1 hour 5 minutes`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::{English, humanize_into_fmt};
+///
+/// let mut text = String::new();
+/// humanize_into_fmt(3660, &English, &mut text).unwrap();
+/// assert_eq!(text, "1 hour 1 minute");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct English;
+
+impl UnitLabels for English {
+    fn label(&self, unit: TimeUnit, amount: u64) -> &str {
+        match (unit, amount) {
+            (TimeUnit::Hour, 1) => "hour",
+            (TimeUnit::Hour, _) => "hours",
+            (TimeUnit::Minute, 1) => "minute",
+            (TimeUnit::Minute, _) => "minutes",
+            (TimeUnit::Second, 1) => "second",
+            (TimeUnit::Second, _) => "seconds",
+        }
+    }
+
+    fn gap(&self) -> &str {
+        " "
+    }
+}
+
+impl UnitLabels for Labels<'_> {
+    fn label(&self, unit: TimeUnit, _amount: u64) -> &str {
+        match unit {
+            TimeUnit::Hour => self.hour,
+            TimeUnit::Minute => self.minute,
+            TimeUnit::Second => self.second,
+        }
+    }
+
+    fn gap(&self) -> &str {
+        self.gap
+    }
+
+    fn separator(&self) -> &str {
+        self.separator
+    }
+}
+
+/// Writes `total_seconds` broken into hours, minutes and seconds, such as `1h 5m 3s`,
+/// into `writer` with the labels from `labels`. Units that are zero are left out; zero
+/// itself is written in seconds.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::{Labels, humanize_into_fmt};
+///
+/// let mut text = String::new();
+/// humanize_into_fmt(3903, &Labels::COMPACT, &mut text).unwrap();
+/// assert_eq!(text, "1h 5m 3s");
+///
+/// text.clear();
+/// humanize_into_fmt(0, &Labels::COMPACT, &mut text).unwrap();
+/// assert_eq!(text, "0s");
+/// ```
+pub fn humanize_into_fmt<W, L>(total_seconds: u64, labels: &L, writer: &mut W) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+    L: UnitLabels + ?Sized,
+{
+    let parts = [
+        (TimeUnit::Hour, total_seconds / 3600),
+        (TimeUnit::Minute, (total_seconds % 3600) / 60),
+        (TimeUnit::Second, total_seconds % 60),
+    ];
+    let mut first = true;
+    for (unit, amount) in parts {
+        // Zero is still written once, as seconds, so the result is never empty.
+        if amount == 0 && !(unit == TimeUnit::Second && first) {
+            continue;
+        }
+        if !first {
+            writer.write_str(labels.separator())?;
+        }
+        write!(
+            writer,
+            "{}{}{}",
+            amount,
+            labels.gap(),
+            labels.label(unit, amount)
+        )?;
+        first = false;
+    }
+    Ok(())
+}
+
+/// Writes `duration` as an ISO 8601 duration such as `PT1H30M` or `P2DT3H` into `writer`.
+///
+/// Days are the largest component, so the output never depends on calendar lengths.
+/// Fractions of a second are written to millisecond precision, and a zero duration is
+/// `PT0S`. `parse::parse_duration` reads the output back.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::format::iso8601_into_fmt;
+/// use std::time::Duration;
+///
+/// let mut text = String::new();
+/// iso8601_into_fmt(Duration::from_secs(2 * 86_400 + 3 * 3600), &mut text).unwrap();
+/// assert_eq!(text, "P2DT3H");
+///
+/// text.clear();
+/// iso8601_into_fmt(Duration::from_millis(90_500), &mut text).unwrap();
+/// assert_eq!(text, "PT1M30.5S");
+/// ```
+pub fn iso8601_into_fmt<W: fmt::Write + ?Sized>(duration: Duration, writer: &mut W) -> fmt::Result {
+    let total_seconds = duration.as_secs();
+    let days = total_seconds / 86_400;
+    let hours = (total_seconds % 86_400) / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    let millis = duration.subsec_millis();
+
+    writer.write_char('P')?;
+    if days > 0 {
+        write!(writer, "{}D", days)?;
+    }
+    let has_time = hours > 0 || minutes > 0 || seconds > 0 || millis > 0;
+    if !has_time {
+        return if days > 0 {
+            Ok(())
+        } else {
+            writer.write_str("T0S")
+        };
+    }
+    writer.write_char('T')?;
+    if hours > 0 {
+        write!(writer, "{}H", hours)?;
+    }
+    if minutes > 0 {
+        write!(writer, "{}M", minutes)?;
+    }
+    match (seconds, millis) {
+        (0, 0) => {}
+        (seconds, 0) => write!(writer, "{}S", seconds)?,
+        (seconds, millis) => {
+            // Trailing zeros of the fraction are dropped, as in `30.5S`.
+            let mut fraction = millis;
+            let mut width = 3;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+            write!(writer, "{}.{:0width$}S", seconds, fraction, width = width)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `H:MM:SS` with hours zero-padded to at least `hour_width` characters.
+fn format_into_fmt_width<W: fmt::Write + ?Sized>(
+    total_seconds: u64,
+    hour_width: usize,
+    writer: &mut W,
+) -> Result<usize, fmt::Error> {
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    write!(
+        writer,
+        "{:0width$}:{:02}:{:02}",
+        hours,
+        minutes,
+        seconds,
+        width = hour_width
+    )?;
+    Ok(digits(hours).max(hour_width) + 6)
+}
@@ -0,0 +1,201 @@
+//! Several labelled stopwatches running side by side, e.g. for timing parallel tasks.
+
+use crate::engine::{StopwatchEngine, format_hms};
+use crate::output::MultiLineRenderer;
+use crate::terminal::{self, Direction};
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct LaneState {
+    label: String,
+    engine: StopwatchEngine,
+}
+
+/// A set of labelled stopwatches rendered together, one line each.
+///
+/// The group is cheap to clone and every clone refers to the same lanes, so
+/// worker threads can stop their own lane while the main thread renders.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::group::StopwatchGroup;
+/// use std::{io::sink, thread, time::Duration};
+///
+/// let group = StopwatchGroup::new();
+/// let workers: Vec<_> = ["frontend", "backend"]
+///     .into_iter()
+///     .map(|label| {
+///         let lane = group.add(label);
+///         thread::spawn(move || {
+///             thread::sleep(Duration::from_millis(20));
+///             lane.stop();
+///         })
+///     })
+///     .collect();
+///
+/// // Redraws every lane until all of them have stopped.
+/// group.run(&mut sink()).unwrap();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// assert_eq!(group.len(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StopwatchGroup {
+    lanes: Arc<Mutex<Vec<LaneState>>>,
+    origin: Instant,
+    direction: Direction,
+}
+
+impl Default for StopwatchGroup {
+    fn default() -> StopwatchGroup {
+        StopwatchGroup::new()
+    }
+}
+
+impl StopwatchGroup {
+    /// Creates an empty group.
+    pub fn new() -> StopwatchGroup {
+        StopwatchGroup {
+            lanes: Arc::new(Mutex::new(Vec::new())),
+            origin: Instant::now(),
+            direction: Direction::default(),
+        }
+    }
+
+    /// Lays every lane out in `direction`, e.g. `Direction::Rtl` for Hebrew or Arabic labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{group::StopwatchGroup, terminal::Direction};
+    ///
+    /// let group = StopwatchGroup::new().direction(Direction::Rtl);
+    /// group.add("בנייה").stop();
+    /// assert_eq!(group.lines(), ["0:0:0  \u{2068}בנייה\u{2069}"]);
+    /// ```
+    pub fn direction(mut self, direction: Direction) -> StopwatchGroup {
+        self.direction = direction;
+        self
+    }
+
+    /// Adds a running lane labelled `label` and returns a handle to it.
+    pub fn add(&self, label: impl Into<String>) -> Lane {
+        let mut engine = StopwatchEngine::new();
+        engine.start(self.now());
+        let mut lanes = self.lanes.lock().unwrap();
+        lanes.push(LaneState {
+            label: label.into(),
+            engine,
+        });
+        Lane {
+            group: self.clone(),
+            index: lanes.len() - 1,
+        }
+    }
+
+    /// The number of lanes in the group.
+    pub fn len(&self) -> usize {
+        self.lanes.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no lanes have been added.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` while at least one lane is running.
+    pub fn is_running(&self) -> bool {
+        let lanes = self.lanes.lock().unwrap();
+        lanes.iter().any(|lane| lane.engine.is_running())
+    }
+
+    /// The current `label  h:m:s` line of every lane, labels padded to the same width,
+    /// or `h:m:s  label` when laid out right to left.
+    pub fn lines(&self) -> Vec<String> {
+        let now = self.now();
+        let lanes = self.lanes.lock().unwrap();
+        let width = lanes
+            .iter()
+            .map(|lane| lane.label.chars().count())
+            .max()
+            .unwrap_or(0);
+        lanes
+            .iter()
+            .map(|lane| {
+                terminal::label_line(
+                    &lane.label,
+                    width,
+                    &format_hms(lane.engine.elapsed(now).as_secs()),
+                    self.direction,
+                )
+            })
+            .collect()
+    }
+
+    /// Redraws every lane on `writer` once a second until all lanes have stopped.
+    ///
+    /// Lanes added while this runs are picked up on the next redraw.
+    pub fn run<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut renderer = MultiLineRenderer::new();
+        loop {
+            let running = self.is_running();
+            renderer.draw(writer, &self.lines())?;
+            if !running {
+                return Ok(());
+            }
+            // Poll briefly so a finished group returns promptly, redrawing once a second.
+            let next_draw = Instant::now() + Duration::from_secs(1);
+            while Instant::now() < next_draw && self.is_running() {
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn with_lane<T>(&self, index: usize, f: impl FnOnce(&mut StopwatchEngine) -> T) -> T {
+        f(&mut self.lanes.lock().unwrap()[index].engine)
+    }
+}
+
+/// A handle to one lane of a `StopwatchGroup`.
+#[derive(Clone, Debug)]
+pub struct Lane {
+    group: StopwatchGroup,
+    index: usize,
+}
+
+impl Lane {
+    /// Stops this lane. Returns `false` if it was already stopped.
+    pub fn stop(&self) -> bool {
+        let now = self.group.now();
+        self.group.with_lane(self.index, |engine| engine.stop(now))
+    }
+
+    /// Restarts this lane, continuing from its elapsed time. Returns `false` if it was running.
+    pub fn start(&self) -> bool {
+        let now = self.group.now();
+        self.group.with_lane(self.index, |engine| engine.start(now))
+    }
+
+    /// The time this lane has been running.
+    pub fn elapsed(&self) -> Duration {
+        let now = self.group.now();
+        self.group
+            .with_lane(self.index, |engine| engine.elapsed(now))
+    }
+
+    /// The label this lane was added with.
+    pub fn label(&self) -> String {
+        self.group.lanes.lock().unwrap()[self.index].label.clone()
+    }
+}
@@ -0,0 +1,330 @@
+//! A record of completed stopwatch sessions, with statistics and export.
+//!
+//! Sessions are kept in memory in the order they were recorded. Exports use
+//! whole milliseconds for durations and Unix seconds for start times, so they
+//! load directly into spreadsheets and plotting tools.
+
+use crate::engine::format_iso8601;
+use crate::time_source::unix_seconds;
+use std::{
+    fmt::Write as _,
+    time::{Duration, SystemTime},
+};
+
+/// One completed stopwatch session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    /// A free-form label such as the activity or workout name.
+    pub label: String,
+    /// When the session started.
+    pub started: SystemTime,
+    /// The total time measured.
+    pub duration: Duration,
+    /// The individual lap times, in order. Empty if no laps were taken.
+    pub laps: Vec<Duration>,
+    /// When the stopwatch wasn't running, in order. Empty if it was never paused.
+    pub pauses: Vec<Pause>,
+}
+
+/// An interval during which a stopwatch was paused, as kept in its pause ledger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pause {
+    /// When the stopwatch was paused.
+    pub started: SystemTime,
+    /// When it resumed or was stopped, `None` while it is still paused.
+    pub ended: Option<SystemTime>,
+}
+
+impl Pause {
+    /// How long the pause lasted, `None` while it is still in progress.
+    pub fn duration(&self) -> Option<Duration> {
+        self.ended
+            .map(|ended| ended.duration_since(self.started).unwrap_or_default())
+    }
+}
+
+impl Session {
+    /// Creates a session without laps.
+    pub fn new(label: impl Into<String>, started: SystemTime, duration: Duration) -> Session {
+        Session {
+            label: label.into(),
+            started,
+            duration,
+            laps: Vec::new(),
+            pauses: Vec::new(),
+        }
+    }
+
+    /// Adds `lap` to the session's lap times.
+    pub fn lap(mut self, lap: Duration) -> Session {
+        self.laps.push(lap);
+        self
+    }
+
+    /// Adds `pause` to the session's pause ledger.
+    pub fn pause(mut self, pause: Pause) -> Session {
+        self.pauses.push(pause);
+        self
+    }
+
+    /// The combined length of the session's completed pauses.
+    pub fn paused(&self) -> Duration {
+        self.pauses.iter().filter_map(Pause::duration).sum()
+    }
+}
+
+/// Summary statistics over the sessions in a `History`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of sessions.
+    pub sessions: usize,
+    /// The combined duration of all sessions.
+    pub total: Duration,
+    /// The mean session duration, `None` without sessions.
+    pub average: Option<Duration>,
+    /// The shortest session duration, `None` without sessions.
+    pub best: Option<Duration>,
+    /// The number of laps across all sessions.
+    pub laps: usize,
+    /// The mean lap time, `None` without laps.
+    pub average_lap: Option<Duration>,
+    /// The shortest lap time, `None` without laps.
+    pub fastest_lap: Option<Duration>,
+    /// The longest lap time, `None` without laps.
+    pub slowest_lap: Option<Duration>,
+    /// The combined time the sessions spent paused.
+    pub paused: Duration,
+}
+
+/// Completed stopwatch sessions in the order they were recorded.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::history::{History, Session};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let mut history = History::new();
+/// history.record(
+///     Session::new("5k", UNIX_EPOCH, Duration::from_secs(1500))
+///         .lap(Duration::from_secs(290))
+///         .lap(Duration::from_secs(310)),
+/// );
+///
+/// let stats = history.statistics();
+/// assert_eq!(stats.total, Duration::from_secs(1500));
+/// assert_eq!(stats.average_lap, Some(Duration::from_secs(300)));
+/// assert_eq!(stats.fastest_lap, Some(Duration::from_secs(290)));
+///
+/// assert_eq!(
+///     history.to_csv(),
+///     "label,started,duration_ms,laps_ms,paused_ms,pauses\n5k,0,1500000,290000;310000,0,\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    sessions: Vec<Session>,
+}
+
+impl History {
+    /// Creates an empty history.
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// Adds a completed session.
+    pub fn record(&mut self, session: Session) {
+        self.sessions.push(session);
+    }
+
+    /// All recorded sessions, oldest first.
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// Sessions whose label is exactly `label`, oldest first.
+    pub fn with_label<'a>(&'a self, label: &'a str) -> impl Iterator<Item = &'a Session> {
+        self.sessions
+            .iter()
+            .filter(move |session| session.label == label)
+    }
+
+    /// Computes totals and lap statistics over every session.
+    pub fn statistics(&self) -> Statistics {
+        statistics(&self.sessions.iter().collect::<Vec<_>>())
+    }
+
+    /// Computes totals and lap statistics over the sessions labelled `label`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::history::{History, Session};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let mut history = History::new();
+    /// for secs in [40, 30, 50] {
+    ///     history.record(Session::new("cargo build", UNIX_EPOCH, Duration::from_secs(secs)));
+    /// }
+    /// history.record(Session::new("cargo test", UNIX_EPOCH, Duration::from_secs(5)));
+    ///
+    /// let builds = history.statistics_for("cargo build");
+    /// assert_eq!(builds.sessions, 3);
+    /// assert_eq!(builds.average, Some(Duration::from_secs(40)));
+    /// assert_eq!(builds.best, Some(Duration::from_secs(30)));
+    /// ```
+    pub fn statistics_for(&self, label: &str) -> Statistics {
+        statistics(&self.with_label(label).collect::<Vec<_>>())
+    }
+
+    /// Exports the sessions as CSV with a header row.
+    ///
+    /// Each row holds the label, the start time in Unix seconds, the duration in
+    /// milliseconds, the lap times in milliseconds separated by `;`, the total paused
+    /// time in milliseconds and the pauses as `start-end` Unix seconds separated by `;`.
+    /// A pause still in progress has no end.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("label,started,duration_ms,laps_ms,paused_ms,pauses\n");
+        for session in &self.sessions {
+            let laps: Vec<String> = session
+                .laps
+                .iter()
+                .map(|lap| lap.as_millis().to_string())
+                .collect();
+            let pauses: Vec<String> = session
+                .pauses
+                .iter()
+                .map(|pause| {
+                    let ended = pause.ended.map(unix_seconds);
+                    format!(
+                        "{}-{}",
+                        unix_seconds(pause.started),
+                        ended.map(|ended| ended.to_string()).unwrap_or_default()
+                    )
+                })
+                .collect();
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                csv_field(&session.label),
+                unix_seconds(session.started),
+                session.duration.as_millis(),
+                laps.join(";"),
+                session.paused().as_millis(),
+                pauses.join(";")
+            );
+        }
+        csv
+    }
+
+    /// Exports the sessions as a JSON array.
+    ///
+    /// Each duration is given both in milliseconds and, as `duration`, as an ISO 8601
+    /// duration for calendar and API payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::history::{History, Session};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let mut history = History::new();
+    /// history.record(Session::new("Deep \"work\"", UNIX_EPOCH, Duration::from_millis(1500)));
+    /// assert_eq!(
+    ///     history.to_json(),
+    ///     r#"[{"label":"Deep \"work\"","started":0,"duration_ms":1500,"duration":"PT1.5S","laps_ms":[],"paused_ms":0,"pauses":[]}]"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (index, session) in self.sessions.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let laps: Vec<String> = session
+                .laps
+                .iter()
+                .map(|lap| lap.as_millis().to_string())
+                .collect();
+            let pauses: Vec<String> = session
+                .pauses
+                .iter()
+                .map(|pause| {
+                    let ended = pause.ended.map(unix_seconds);
+                    format!(
+                        "{{\"started\":{},\"ended\":{}}}",
+                        unix_seconds(pause.started),
+                        ended.map_or_else(|| String::from("null"), |ended| ended.to_string())
+                    )
+                })
+                .collect();
+            let _ = write!(
+                json,
+                "{{\"label\":\"{}\",\"started\":{},\"duration_ms\":{},\"duration\":\"{}\",\"laps_ms\":[{}],\"paused_ms\":{},\"pauses\":[{}]}}",
+                json_escape(&session.label),
+                unix_seconds(session.started),
+                session.duration.as_millis(),
+                format_iso8601(session.duration),
+                laps.join(","),
+                session.paused().as_millis(),
+                pauses.join(",")
+            );
+        }
+        json.push(']');
+        json
+    }
+}
+
+fn statistics(sessions: &[&Session]) -> Statistics {
+    let laps = || sessions.iter().flat_map(|session| &session.laps);
+    let durations = || sessions.iter().map(|session| session.duration);
+    let total: Duration = durations().sum();
+    let lap_count = laps().count();
+    let lap_total: Duration = laps().sum();
+    Statistics {
+        sessions: sessions.len(),
+        total,
+        average: mean(total, sessions.len()),
+        best: durations().min(),
+        laps: lap_count,
+        average_lap: mean(lap_total, lap_count),
+        fastest_lap: laps().min().copied(),
+        slowest_lap: laps().max().copied(),
+        paused: sessions.iter().map(|session| session.paused()).sum(),
+    }
+}
+
+/// `total / count`, `None` when `count` is zero.
+fn mean(total: Duration, count: usize) -> Option<Duration> {
+    u32::try_from(count)
+        .ok()
+        .filter(|&count| count > 0)
+        .map(|count| total / count)
+}
+
+/// Quotes `text` if it contains a separator, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", u32::from(c));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
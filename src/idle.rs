@@ -0,0 +1,205 @@
+//! An idle timer that fires after a period without activity.
+//!
+//! The application reports activity (key presses, mouse moves, requests) by
+//! calling `IdleTimer::activity`; the crate doesn't hook OS input itself.
+//! `IdleTimer::pause_while_idle` pauses a countdown nobody is attending, so
+//! unattended work sessions don't count.
+
+use crate::timer::TimerController;
+use std::{
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct IdleState {
+    last_activity: Instant,
+    /// Bumped on every activity, so the watcher can tell a new idle period apart.
+    generation: u64,
+    stopped: bool,
+}
+
+/// Fires a callback once each time `timeout` passes without activity.
+///
+/// Clones share the same state, so activity can be reported from any thread.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::idle::IdleTimer;
+/// use std::{sync::mpsc, thread, time::Duration};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let idle = IdleTimer::new(Duration::from_millis(500));
+/// idle.start(move || sender.send("locked").unwrap());
+///
+/// // Activity keeps pushing the deadline back.
+/// for _ in 0..3 {
+///     thread::sleep(Duration::from_millis(100));
+///     idle.activity();
+/// }
+/// assert!(receiver.try_recv().is_err());
+///
+/// assert_eq!(receiver.recv().unwrap(), "locked");
+/// idle.stop();
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdleTimer {
+    timeout: Duration,
+    state: Arc<(Mutex<IdleState>, Condvar)>,
+}
+
+impl IdleTimer {
+    /// Creates a new `IdleTimer` that considers `timeout` without activity as idle.
+    ///
+    /// The idle period starts counting from now.
+    pub fn new(timeout: Duration) -> IdleTimer {
+        IdleTimer {
+            timeout,
+            state: Arc::new((
+                Mutex::new(IdleState {
+                    last_activity: Instant::now(),
+                    generation: 0,
+                    stopped: false,
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    /// Records activity, restarting the idle period.
+    pub fn activity(&self) {
+        let (state, condvar) = &*self.state;
+        let mut state = state.lock().unwrap();
+        state.last_activity = Instant::now();
+        state.generation += 1;
+        condvar.notify_all();
+    }
+
+    /// How long it has been since the last activity.
+    pub fn idle_for(&self) -> Duration {
+        self.state.0.lock().unwrap().last_activity.elapsed()
+    }
+
+    /// Returns `true` once `timeout` has passed without activity.
+    pub fn is_idle(&self) -> bool {
+        self.idle_for() >= self.timeout
+    }
+
+    /// Runs `on_idle` on a background thread each time the timer goes idle.
+    ///
+    /// The callback fires once per idle period; it fires again only after new
+    /// activity followed by another `timeout` of inactivity. The thread exits
+    /// when `stop` is called.
+    pub fn start<F>(&self, on_idle: F)
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.start_with_return(on_idle, || {});
+    }
+
+    /// Like `start`, and also runs `on_return` on the first activity after each idle
+    /// period, e.g. to resume whatever `on_idle` paused.
+    pub fn start_with_return<F, G>(&self, mut on_idle: F, mut on_return: G)
+    where
+        F: FnMut() + Send + 'static,
+        G: FnMut() + Send + 'static,
+    {
+        let timeout = self.timeout;
+        let shared = self.state.clone();
+
+        thread::spawn(move || {
+            let (state, condvar) = &*shared;
+            let mut guard = state.lock().unwrap();
+            loop {
+                if guard.stopped {
+                    return;
+                }
+                let deadline = guard.last_activity + timeout;
+                let now = Instant::now();
+                if now < deadline {
+                    guard = condvar.wait_timeout(guard, deadline - now).unwrap().0;
+                    continue;
+                }
+
+                let generation = guard.generation;
+                drop(guard);
+                on_idle();
+                guard = state.lock().unwrap();
+
+                // Stay quiet until there is new activity.
+                guard = condvar
+                    .wait_while(guard, |state| {
+                        state.generation == generation && !state.stopped
+                    })
+                    .unwrap();
+                if !guard.stopped {
+                    drop(guard);
+                    on_return();
+                    guard = state.lock().unwrap();
+                }
+            }
+        });
+    }
+
+    /// Pauses the timer run behind `controller` each time the timer goes idle, and
+    /// resumes it on the next activity.
+    ///
+    /// A run that was already paused when the timer went idle is left paused, so a
+    /// deliberate pause isn't undone by activity. Like `start`, the watching thread
+    /// exits when `stop` is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{
+    ///     idle::IdleTimer,
+    ///     timer::{TimerStruct, TimerTrait},
+    /// };
+    /// use std::{io::sink, thread, time::Duration};
+    ///
+    /// let (handle, controller) = TimerStruct::new(0, 25, 0).unwrap().spawn(sink());
+    /// let idle = IdleTimer::new(Duration::from_millis(100));
+    /// idle.pause_while_idle(controller.clone());
+    ///
+    /// thread::sleep(Duration::from_millis(300));
+    /// assert!(controller.is_paused()); // nobody was there
+    ///
+    /// idle.activity();
+    /// thread::sleep(Duration::from_millis(50));
+    /// assert!(!controller.is_paused());
+    ///
+    /// idle.stop();
+    /// controller.cancel();
+    /// handle.join().unwrap().unwrap();
+    /// ```
+    pub fn pause_while_idle(&self, controller: TimerController) {
+        let paused_by_idle = Arc::new(AtomicBool::new(false));
+        let resumed_by_activity = paused_by_idle.clone();
+        let resume = controller.clone();
+        self.start_with_return(
+            move || {
+                if !controller.is_paused() {
+                    controller.pause();
+                    paused_by_idle.store(true, Ordering::SeqCst);
+                }
+            },
+            move || {
+                if resumed_by_activity.swap(false, Ordering::SeqCst) {
+                    resume.resume();
+                }
+            },
+        );
+    }
+
+    /// Stops the background thread started with `start`.
+    pub fn stop(&self) {
+        let (state, condvar) = &*self.state;
+        state.lock().unwrap().stopped = true;
+        condvar.notify_all();
+    }
+}
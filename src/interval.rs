@@ -0,0 +1,379 @@
+//! JavaScript-style `set_timeout` and `set_interval` primitives.
+//!
+//! Each call runs its closure on a dedicated background thread and returns a
+//! `CancelHandle` that can stop it from any other thread.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A handle used to cancel a pending `set_timeout` or a running `set_interval`.
+///
+/// Dropping the handle does not cancel anything, mirroring how discarding a
+/// JavaScript timer id leaves the timer running.
+#[derive(Clone, Debug)]
+pub struct CancelHandle {
+    state: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl Default for CancelHandle {
+    fn default() -> CancelHandle {
+        CancelHandle::new()
+    }
+}
+
+impl CancelHandle {
+    /// Creates a handle that isn't attached to anything yet, for APIs that take one to
+    /// stop a long-running loop.
+    pub fn new() -> CancelHandle {
+        CancelHandle {
+            state: Arc::new((Mutex::new(false), Condvar::new())),
+        }
+    }
+
+    /// Cancels the timeout or interval. Calling this more than once has no further effect.
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.state;
+        *cancelled.lock().unwrap() = true;
+        condvar.notify_all();
+    }
+
+    /// Returns `true` once `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.0.lock().unwrap()
+    }
+
+    /// Blocks until the handle is cancelled, e.g. by an `Interval` reaching its
+    /// `max_runs`.
+    pub fn wait(&self) {
+        let (cancelled, condvar) = &*self.state;
+        drop(condvar.wait_while(cancelled.lock().unwrap(), |cancelled| !*cancelled));
+    }
+
+    /// Blocks until `deadline` is reached or the handle is cancelled.
+    ///
+    /// Returns `true` if the wait ended because of a cancellation.
+    pub(crate) fn wait_until(&self, deadline: Instant) -> bool {
+        let (cancelled, condvar) = &*self.state;
+        let mut guard = cancelled.lock().unwrap();
+        loop {
+            if *guard {
+                return true;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            guard = condvar.wait_timeout(guard, deadline - now).unwrap().0;
+        }
+    }
+}
+
+/// Runs `operation` once after `delay` has elapsed, unless cancelled first.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::interval::set_timeout;
+/// use std::{sync::mpsc, time::Duration};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// set_timeout(Duration::from_millis(10), move || sender.send("done").unwrap());
+/// assert_eq!(receiver.recv().unwrap(), "done");
+///
+/// let handle = set_timeout(Duration::from_secs(60), || unreachable!());
+/// handle.cancel();
+/// ```
+pub fn set_timeout<F>(delay: Duration, operation: F) -> CancelHandle
+where
+    F: FnOnce() + Send + 'static,
+{
+    let handle = CancelHandle::new();
+    let thread_handle = handle.clone();
+    let deadline = Instant::now().checked_add(delay);
+
+    thread::spawn(move || {
+        // A delay too long to represent never elapses.
+        let Some(deadline) = deadline else {
+            return thread_handle.wait();
+        };
+        if !thread_handle.wait_until(deadline) {
+            operation();
+        }
+    });
+
+    handle
+}
+
+/// The shortest period an `Interval` ticks at; shorter ones are raised to it.
+pub const MIN_PERIOD: Duration = Duration::from_millis(1);
+
+/// Controls when the first tick of an `Interval` fires.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FirstTick {
+    /// The first tick fires one full period after the interval starts.
+    #[default]
+    AfterPeriod,
+    /// The first tick fires as soon as the interval starts.
+    Immediate,
+}
+
+/// Random variation applied to each tick of an `Interval`.
+///
+/// Jitter moves individual ticks around their regular schedule without
+/// shifting the schedule itself, so it never accumulates as drift.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::interval::{Interval, Jitter};
+/// use std::time::Duration;
+///
+/// // Fractions are clamped to `0.0..=1.0`; NaN and infinities mean no jitter.
+/// let handle = Interval::new(Duration::from_millis(10))
+///     .jitter(Jitter::Percent(f64::NAN))
+///     .max_runs(2)
+///     .start(|| {});
+/// handle.wait();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Jitter {
+    /// Moves each tick by up to ± this fraction of the period (`0.1` is ±10%).
+    Percent(f64),
+    /// Moves each tick by up to ± this duration.
+    Range(Duration),
+}
+
+impl Jitter {
+    /// The largest offset this jitter can apply to a tick of the given period.
+    fn max_offset(&self, period: Duration) -> Duration {
+        match *self {
+            Jitter::Percent(fraction) if fraction.is_finite() => {
+                period.mul_f64(fraction.clamp(0.0, 1.0))
+            }
+            Jitter::Percent(_) => Duration::ZERO,
+            Jitter::Range(range) => range,
+        }
+    }
+}
+
+/// A small xorshift generator; jitter only needs spread, not cryptographic quality.
+struct JitterRng(u64);
+
+impl JitterRng {
+    fn new() -> JitterRng {
+        // `RandomState` is seeded randomly per process, which is all the entropy we need.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0x9E37_79B9_7F4A_7C15);
+        JitterRng(hasher.finish() | 1)
+    }
+
+    /// Returns a value in `[-1.0, 1.0)`.
+    fn next_signed_unit(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+
+    /// Applies a random offset of at most `max_offset` in either direction to `instant`.
+    fn jitter(&mut self, instant: Instant, max_offset: Duration) -> Instant {
+        let offset = self.next_signed_unit();
+        let magnitude = max_offset.mul_f64(offset.abs());
+        if offset < 0.0 {
+            instant.checked_sub(magnitude).unwrap_or(instant)
+        } else {
+            instant.checked_add(magnitude).unwrap_or(instant)
+        }
+    }
+}
+
+/// A configurable repeating interval, the builder behind `set_interval`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::interval::{FirstTick, Interval};
+/// use std::{sync::mpsc, time::Duration};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let handle = Interval::new(Duration::from_secs(60))
+///     .first_tick(FirstTick::Immediate)
+///     .start(move || {
+///         let _ = sender.send(());
+///     });
+/// // The first tick doesn't wait for the 60 second period.
+/// receiver.recv().unwrap();
+/// handle.cancel();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Interval {
+    period: Duration,
+    first_tick: FirstTick,
+    jitter: Option<Jitter>,
+    max_runs: Option<u64>,
+}
+
+impl Interval {
+    /// Creates a new `Interval` firing every `period`.
+    ///
+    /// A period shorter than `MIN_PERIOD`, such as zero, is raised to it so `operation`
+    /// can't spin the CPU. A period too long to schedule never fires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::interval::{FirstTick, Interval};
+    /// use std::{sync::mpsc, time::Duration};
+    ///
+    /// let handle = Interval::new(Duration::ZERO).max_runs(3).start(|| {});
+    /// handle.wait();
+    ///
+    /// // Only the immediate first tick ever fires.
+    /// let (sender, receiver) = mpsc::channel();
+    /// let handle = Interval::new(Duration::MAX)
+    ///     .first_tick(FirstTick::Immediate)
+    ///     .start(move || sender.send(()).unwrap());
+    /// receiver.recv().unwrap();
+    /// assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+    /// handle.cancel();
+    /// ```
+    pub fn new(period: Duration) -> Interval {
+        Interval {
+            period: period.max(MIN_PERIOD),
+            first_tick: FirstTick::default(),
+            jitter: None,
+            max_runs: None,
+        }
+    }
+
+    /// Sets when the first tick fires. Defaults to `FirstTick::AfterPeriod`.
+    pub fn first_tick(mut self, first_tick: FirstTick) -> Interval {
+        self.first_tick = first_tick;
+        self
+    }
+
+    /// Applies random `jitter` to every tick, spreading out intervals that would
+    /// otherwise fire in lockstep (polling, retries).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::interval::{Interval, Jitter};
+    /// use std::time::Duration;
+    ///
+    /// let handle = Interval::new(Duration::from_secs(30))
+    ///     .jitter(Jitter::Percent(0.1)) // each poll lands between 27s and 33s
+    ///     .start(|| println!("polling"));
+    /// handle.cancel();
+    /// ```
+    pub fn jitter(mut self, jitter: Jitter) -> Interval {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    /// Stops after `runs` ticks, cancelling the returned handle so `CancelHandle::wait`
+    /// returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::interval::Interval;
+    /// use std::{
+    ///     sync::{Arc, atomic::{AtomicU32, Ordering}},
+    ///     time::Duration,
+    /// };
+    ///
+    /// let ticks = Arc::new(AtomicU32::new(0));
+    /// let counter = Arc::clone(&ticks);
+    /// let handle = Interval::new(Duration::from_millis(10))
+    ///     .max_runs(3)
+    ///     .start(move || {
+    ///         counter.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// handle.wait();
+    /// assert_eq!(ticks.load(Ordering::SeqCst), 3);
+    /// ```
+    pub fn max_runs(mut self, runs: u64) -> Interval {
+        self.max_runs = Some(runs);
+        self
+    }
+
+    /// Starts running `operation` on every tick until the returned handle is cancelled.
+    ///
+    /// Ticks are scheduled relative to the moment the interval was started, so
+    /// time spent inside `operation` doesn't accumulate as drift.
+    pub fn start<F>(self, mut operation: F) -> CancelHandle
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let handle = CancelHandle::new();
+        let thread_handle = handle.clone();
+        let start = Instant::now();
+        let period = self.period;
+        let max_offset = self.jitter.map(|jitter| jitter.max_offset(period));
+
+        thread::spawn(move || {
+            let mut jitter = max_offset.map(|max_offset| (JitterRng::new(), max_offset));
+            let mut next = match self.first_tick {
+                FirstTick::AfterPeriod => start.checked_add(period),
+                FirstTick::Immediate => Some(start),
+            };
+            let mut runs = 0;
+            loop {
+                if self.max_runs.is_some_and(|max_runs| runs >= max_runs) {
+                    thread_handle.cancel();
+                    break;
+                }
+                // The next tick is too far away to represent, so it never comes.
+                let Some(scheduled) = next else {
+                    thread_handle.wait();
+                    break;
+                };
+                let deadline = match &mut jitter {
+                    Some((rng, max_offset)) => rng.jitter(scheduled, *max_offset),
+                    None => scheduled,
+                };
+                if thread_handle.wait_until(deadline) {
+                    break;
+                }
+                operation();
+                runs += 1;
+                next = scheduled.checked_add(period);
+            }
+        });
+
+        handle
+    }
+}
+
+/// Runs `operation` every `period` until cancelled.
+///
+/// This is shorthand for `Interval::new(period).start(operation)`, so a zero
+/// `period` is raised to `MIN_PERIOD`; use `Interval` directly for more control
+/// over the ticks.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::interval::set_interval;
+/// use std::{sync::mpsc, time::Duration};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let handle = set_interval(Duration::from_millis(10), move || {
+///     let _ = sender.send(());
+/// });
+/// receiver.recv().unwrap();
+/// receiver.recv().unwrap();
+/// handle.cancel();
+/// ```
+pub fn set_interval<F>(period: Duration, operation: F) -> CancelHandle
+where
+    F: FnMut() + Send + 'static,
+{
+    Interval::new(period).start(operation)
+}
@@ -0,0 +1,131 @@
+//! Countdown progress for addressable LED strips (WS2812 and friends).
+//!
+//! The crate doesn't talk to the hardware; it fills a fixed-size frame buffer
+//! that the caller clocks out with whatever driver their board uses.
+
+use crate::engine::Easing;
+use core::time::Duration;
+
+/// A pixel colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgb {
+    /// The red channel.
+    pub r: u8,
+    /// The green channel.
+    pub g: u8,
+    /// The blue channel.
+    pub b: u8,
+}
+
+impl Rgb {
+    /// A pixel that is switched off.
+    pub const OFF: Rgb = Rgb::new(0, 0, 0);
+
+    /// Creates a new `Rgb` colour.
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+
+    /// The colour in the green-red-blue byte order WS2812 LEDs expect on the wire.
+    pub const fn grb(self) -> [u8; 3] {
+        [self.g, self.r, self.b]
+    }
+}
+
+/// Returns how many of `pixels` should be lit with `remaining` out of `total` left.
+///
+/// The bar drains as the countdown runs: it is full at the start, and the last
+/// pixel only goes out when the countdown reaches zero.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::led::lit_pixels;
+/// use std::time::Duration;
+///
+/// let total = Duration::from_secs(60);
+/// assert_eq!(lit_pixels(total, total, 8), 8);
+/// assert_eq!(lit_pixels(Duration::from_secs(30), total, 8), 4);
+/// assert_eq!(lit_pixels(Duration::from_secs(1), total, 8), 1);
+/// assert_eq!(lit_pixels(Duration::ZERO, total, 8), 0);
+/// ```
+pub fn lit_pixels(remaining: Duration, total: Duration, pixels: usize) -> usize {
+    if total.is_zero() {
+        return 0;
+    }
+    let remaining = remaining.min(total).as_nanos();
+    let lit = (remaining * pixels as u128).div_ceil(total.as_nanos());
+    lit as usize
+}
+
+/// A frame buffer of `N` pixels showing countdown progress.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::led::{ProgressStrip, Rgb};
+/// use std::time::Duration;
+///
+/// let mut strip = ProgressStrip::<4>::new(Rgb::new(255, 0, 0));
+/// let frame = strip.render(Duration::from_secs(15), Duration::from_secs(60));
+/// assert_eq!(frame, &[Rgb::new(255, 0, 0), Rgb::OFF, Rgb::OFF, Rgb::OFF]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressStrip<const N: usize> {
+    lit: Rgb,
+    unlit: Rgb,
+    easing: Easing,
+    frame: [Rgb; N],
+}
+
+impl<const N: usize> ProgressStrip<N> {
+    /// Creates a new `ProgressStrip` lighting pixels with `lit` and leaving the rest off.
+    pub const fn new(lit: Rgb) -> ProgressStrip<N> {
+        ProgressStrip {
+            lit,
+            unlit: Rgb::OFF,
+            easing: Easing::Linear,
+            frame: [Rgb::OFF; N],
+        }
+    }
+
+    /// Sets the colour of the pixels that aren't lit. Defaults to `Rgb::OFF`.
+    pub const fn unlit(mut self, unlit: Rgb) -> ProgressStrip<N> {
+        self.unlit = unlit;
+        self
+    }
+
+    /// Drains the strip along `easing` instead of evenly. Defaults to `Easing::Linear`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{
+    ///     engine::Easing,
+    ///     led::{ProgressStrip, Rgb},
+    /// };
+    /// use std::time::Duration;
+    ///
+    /// let mut strip = ProgressStrip::<4>::new(Rgb::new(255, 0, 0)).easing(Easing::Quadratic);
+    /// let frame = strip.render(Duration::from_secs(30), Duration::from_secs(60));
+    /// assert_eq!(frame.iter().filter(|&&pixel| pixel != Rgb::OFF).count(), 3);
+    /// ```
+    pub const fn easing(mut self, easing: Easing) -> ProgressStrip<N> {
+        self.easing = easing;
+        self
+    }
+
+    /// Fills the frame for `remaining` out of `total` and returns it, first pixel first.
+    pub fn render(&mut self, remaining: Duration, total: Duration) -> &[Rgb; N] {
+        let lit = lit_pixels(self.easing.apply(remaining, total), total, N);
+        for (index, pixel) in self.frame.iter_mut().enumerate() {
+            *pixel = if index < lit { self.lit } else { self.unlit };
+        }
+        &self.frame
+    }
+
+    /// The most recently rendered frame.
+    pub fn frame(&self) -> &[Rgb; N] {
+        &self.frame
+    }
+}
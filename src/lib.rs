@@ -1,18 +1,145 @@
-#[cfg(target_arch = "wasm32")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod calendar;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod clock;
+#[cfg(all(feature = "std", unix))]
+pub mod control;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod doctor;
+pub mod engine;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod group;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod idle;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod interval;
+#[cfg(feature = "led")]
+pub mod led;
+#[cfg(feature = "locale")]
+pub mod locale;
+#[cfg(feature = "std")]
+pub mod measure;
+#[cfg(feature = "notify")]
+pub mod notify;
+#[cfg(feature = "std")]
+pub mod output;
+pub mod parse;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod schedule;
+pub mod segment;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod sequence;
+pub mod share;
+#[cfg(feature = "sound")]
+pub mod sound;
+pub mod splits;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod supervise;
+#[cfg(feature = "std")]
+pub mod terminal;
+#[cfg(feature = "std")]
+pub mod time_source;
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
 pub mod wasm;
 
-/// Module for countdown timer functionalities.
+/// Builds a `Duration` from hours, minutes and seconds at compile time, rejecting a zero
+/// duration with a build error.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::duration_hms;
+/// use std::time::Duration;
+///
+/// const BREAK: Duration = duration_hms!(0, 15, 0);
+/// assert_eq!(BREAK, Duration::from_secs(900));
+/// assert_eq!(duration_hms!(1, 30, 0), Duration::from_secs(5400));
+/// ```
+///
+/// ```compile_fail
+/// let nothing = clock_timer::duration_hms!(0, 0, 0);
+/// ```
+#[macro_export]
+macro_rules! duration_hms {
+    ($hours:expr, $minutes:expr, $seconds:expr $(,)?) => {
+        const { $crate::engine::duration_hms($hours, $minutes, $seconds) }
+    };
+}
+
+/// Builds a `TimerStruct` from hours, minutes and seconds at compile time, without the
+/// `Result` of `TimerTrait::new`. See `TimerStruct::const_new`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::timer;
+///
+/// let pomodoro = timer!(0, 25, 0);
+/// assert_eq!(pomodoro.duration, 1500);
+/// ```
+///
+/// ```compile_fail
+/// let nothing = clock_timer::timer!(0, 0, 0);
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! timer {
+    ($hours:expr, $minutes:expr, $seconds:expr $(,)?) => {
+        const { $crate::timer::TimerStruct::const_new($hours, $minutes, $seconds) }
+    };
+}
 
+/// Module for countdown timer functionalities.
+#[cfg(feature = "std")]
 pub mod timer {
-    use std::{io::Write, thread, time::Duration};
+    #[cfg(unix)]
+    use crate::control::{ControlServer, OwnerWatch};
+    use crate::engine::{self, Easing};
+    use crate::format;
+    #[cfg(feature = "locale")]
+    use crate::locale::Locale;
+    #[cfg(feature = "notify")]
+    use crate::notify::Notification;
+    use crate::output::{
+        self, ColorChoice, ColorZone, OutputMode, ProgressWriter, Theme, Tick, TickStyle,
+    };
+    #[cfg(feature = "sound")]
+    use crate::sound::Chime;
+    use crate::time_source::Clock;
+    use std::{
+        cmp::Reverse,
+        fmt,
+        io::{self, Write},
+        panic::{self, AssertUnwindSafe},
+        path::PathBuf,
+        process::Command,
+        sync::{
+            Arc, Condvar, Mutex, PoisonError,
+            atomic::{AtomicU32, Ordering},
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant, SystemTime},
+    };
+
+    /// Environment variable holding the timer's target duration in seconds for `on_finish_exec`.
+    pub const TARGET_SECS_ENV: &str = "CLOCK_TIMER_TARGET_SECS";
+    /// Environment variable holding the elapsed time in seconds for `on_finish_exec`.
+    pub const ELAPSED_SECS_ENV: &str = "CLOCK_TIMER_ELAPSED_SECS";
 
-    pub trait TimerTrait {
-        fn new(hours: u32, minutes: u32, seconds: u32) -> Result<TimerStruct, &'static str>;
-        fn start_timer<W: Write>(&self, writer: &mut W);
+    pub trait TimerTrait: Sized {
+        fn new(hours: u32, minutes: u32, seconds: u32) -> Result<Self, &'static str>;
+        fn start_timer<W: Write>(&self, writer: &mut W) -> io::Result<()>;
     }
 
     /// Represents a countdown timer.
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Debug)]
     pub struct TimerStruct {
         /// The total duration of the timer in seconds.
         pub duration: u32,
@@ -22,278 +149,3282 @@ pub mod timer {
         pub minutes: u32,
         /// The initial seconds component of the timer.
         pub seconds: u32,
+        /// A desktop notification shown when the timer reaches 0.
+        #[cfg(feature = "notify")]
+        pub notification: Option<Notification>,
+        /// A chime played when the timer reaches 0.
+        #[cfg(feature = "sound")]
+        pub chime: Option<Chime>,
+        /// A shell command run when the timer reaches 0.
+        pub on_finish_exec: Option<String>,
+        /// Callbacks fired as the countdown crosses specific remaining times.
+        pub checkpoints: Vec<Checkpoint>,
+        /// Reduced render cadence while a lot of time remains.
+        pub low_power: Option<LowPower>,
+        /// Green, yellow and red display thresholds for speakers.
+        pub color_zones: Option<ColorZones>,
+        /// The colours the zones are drawn in.
+        pub theme: Theme,
+        /// Whether the display is coloured at all.
+        pub color: ColorChoice,
+        /// How the remaining time maps onto progress displays.
+        pub easing: Easing,
+        /// Counting up past zero once the target is reached.
+        pub overtime: Option<Overtime>,
+        /// A policy that may extend the countdown when it reaches zero.
+        pub auto_extend: Option<AutoExtend>,
+        /// Callbacks fired at fixed intervals with the remaining time.
+        pub announcements: Vec<Announcement>,
+        /// How each tick is written.
+        pub output: OutputMode,
+        /// A file rewritten with the current time on every tick.
+        pub status_file: Option<PathBuf>,
+        /// A Unix domain socket accepting control commands while the timer runs.
+        #[cfg(unix)]
+        pub control_socket: Option<PathBuf>,
+        /// A process whose exit cancels the countdown.
+        #[cfg(unix)]
+        pub owner: Option<u32>,
+        /// A secondary channel receiving `remaining=NN` lines.
+        pub progress: Option<ProgressWriter>,
+        /// How many rendered ticks may wait for a slow writer on a separate output thread.
+        /// Without one, ticks are written from the timing loop.
+        pub output_buffer: Option<usize>,
+        /// The clock driving the countdown instead of real time, if any.
+        pub clock: Option<Arc<dyn Clock>>,
+        /// How often checkpoints and announcements are checked.
+        pub tick_interval: Duration,
+        /// How often the display is redrawn.
+        pub refresh_interval: Duration,
+        /// What the countdown does when the machine wakes from suspend.
+        pub on_resume: OnResume,
+        /// What happens when a checkpoint, announcement or overtime callback panics.
+        pub on_callback_panic: PanicPolicy,
+        /// The locale the display is rendered in, plain ASCII without one.
+        #[cfg(feature = "locale")]
+        pub locale: Option<Locale>,
     }
 
-    impl TimerTrait for TimerStruct {
-        /// Creates a new `TimerStruct` instance.
+    /// What a countdown does with time the machine spent suspended, set with
+    /// `TimerBuilder::on_resume`.
+    ///
+    /// The monotonic clock stops while a laptop sleeps, so a countdown sleeping on it
+    /// freezes and ends late. A suspend is detected when the system clock moved more than
+    /// `SUSPEND_THRESHOLD` further than the monotonic clock during a tick. Timers driven by
+    /// a `Clock` never see a suspend.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum OnResume {
+        /// Counts the suspended time, so the countdown still ends at its wall-clock time.
+        /// Checkpoints and announcements passed while suspended fire on waking.
+        CatchUp,
+        /// Pauses the countdown on waking until `TimerController::resume` confirms it.
+        Pause,
+        /// Ignores the suspended time and carries on where the countdown froze.
+        #[default]
+        Continue,
+    }
+
+    /// What a timer does when one of its callbacks panics, set with
+    /// `TimerBuilder::on_callback_panic`.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum PanicPolicy {
+        /// Carries on counting down. The panic hook still reports the panic, by default
+        /// on standard error.
+        LogAndContinue,
+        /// Ends the run with an `io::Error` wrapping a `CallbackPanicked`.
+        StopTimer,
+        /// Lets the panic unwind out of `start_timer`, or out of the thread started by
+        /// `spawn`.
+        #[default]
+        Propagate,
+    }
+
+    /// A panic caught in a timer callback under `PanicPolicy::StopTimer`.
+    ///
+    /// The run returns it as the inner error of an `io::Error` of kind `Other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{
+    ///     time_source::VirtualClock,
+    ///     timer::{CallbackPanicked, PanicPolicy, TimerBuilder, TimerTrait},
+    /// };
+    /// use std::{io::sink, time::Duration};
+    ///
+    /// let timer = TimerBuilder::new(0, 1, 0)
+    ///     .clock(VirtualClock::new())
+    ///     .at_remaining(Duration::from_secs(30), |_| panic!("halfway"))
+    ///     .on_callback_panic(PanicPolicy::StopTimer)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let error = timer.start_timer(&mut sink()).unwrap_err();
+    /// let panicked = error.get_ref().unwrap().downcast_ref::<CallbackPanicked>().unwrap();
+    /// assert_eq!(panicked.to_string(), "checkpoint callback panicked: halfway");
+    /// ```
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct CallbackPanicked {
+        /// The kind of callback that panicked: `checkpoint`, `announcement`, `overtime` or
+        /// `extension`.
+        pub callback: &'static str,
+        /// The panic message, if it was a string.
+        pub message: String,
+    }
+
+    impl fmt::Display for CallbackPanicked {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} callback panicked: {}", self.callback, self.message)
+        }
+    }
+
+    impl std::error::Error for CallbackPanicked {}
+
+    /// How much further the system clock has to move than the monotonic clock during a
+    /// tick for the gap to count as a suspend rather than clock drift.
+    pub const SUSPEND_THRESHOLD: Duration = Duration::from_secs(5);
+
+    /// Render cadence for battery-friendly countdowns, set with `TimerBuilder::low_power`.
+    ///
+    /// While more than `threshold` remains the timer only wakes up every `interval`,
+    /// then it goes back to rendering every second.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct LowPower {
+        /// How often to wake up and render while more than `threshold` remains.
+        pub interval: Duration,
+        /// The remaining time below which the timer renders every second again.
+        pub threshold: Duration,
+    }
+
+    impl Default for LowPower {
+        /// Renders every 10 seconds until the last minute.
+        fn default() -> LowPower {
+            LowPower {
+                interval: Duration::from_secs(10),
+                threshold: Duration::from_secs(60),
+            }
+        }
+    }
+
+    /// Display colour thresholds for presentation timers, set with `TimerBuilder::color_zones`.
+    ///
+    /// The display is green until `yellow` remains, yellow until `red` remains, then red
+    /// through zero and any overtime.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct ColorZones {
+        /// The remaining time at which the display turns yellow.
+        pub yellow: Duration,
+        /// The remaining time at which the display turns red.
+        pub red: Duration,
+    }
+
+    impl Default for ColorZones {
+        /// Yellow with five minutes left, red with one.
+        fn default() -> ColorZones {
+            ColorZones {
+                yellow: Duration::from_secs(5 * 60),
+                red: Duration::from_secs(60),
+            }
+        }
+    }
+
+    impl ColorZones {
+        /// The zone the display is in with `remaining` time left.
         ///
-        /// # Arguments
+        /// # Examples
         ///
-        /// * `hours` - The hours component of the timer duration.
-        /// * `minutes` - The minutes component of the timer duration.
-        /// * `seconds` - The seconds component of the timer duration.
+        /// ```
+        /// use clock_timer::{output::ColorZone, timer::ColorZones};
+        /// use std::time::Duration;
+        ///
+        /// let zones = ColorZones::default();
+        /// assert_eq!(zones.zone(Duration::from_secs(600)), ColorZone::Green);
+        /// assert_eq!(zones.zone(Duration::from_secs(300)), ColorZone::Yellow);
+        /// assert_eq!(zones.zone(Duration::ZERO), ColorZone::Red);
+        /// ```
+        pub fn zone(&self, remaining: Duration) -> ColorZone {
+            if remaining <= self.red {
+                ColorZone::Red
+            } else if remaining <= self.yellow {
+                ColorZone::Yellow
+            } else {
+                ColorZone::Green
+            }
+        }
+    }
+
+    /// A callback receiving a remaining time, shared so `TimerStruct` stays `Clone`.
+    type DurationCallback = Arc<Mutex<Box<dyn FnMut(Duration) + Send>>>;
+
+    /// A callback receiving a colour zone, shared like `DurationCallback`.
+    type ZoneCallback = Arc<Mutex<Box<dyn FnMut(ColorZone) + Send>>>;
+
+    /// A callback deciding on an extension, shared like `DurationCallback`.
+    type ExtendCallback = Arc<Mutex<Box<dyn FnMut(u32) -> Option<Duration> + Send>>>;
+
+    /// How a timer run ended.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum TimerOutcome {
+        /// The countdown reached 0 and the finish actions ran.
+        Finished,
+        /// The run was cancelled through its `TimerController` before reaching 0.
+        Cancelled {
+            /// The remaining time when the run was cancelled.
+            remaining: Duration,
+        },
+    }
+
+    /// The result of a timer run started with `TimerStruct::spawn`.
+    pub type TimerResult = io::Result<TimerOutcome>;
+
+    /// Reads a running timer without being able to pause, resume, cancel or adjust it.
+    ///
+    /// Obtained from `TimerController::watcher`. Clones are cheap and watch the same run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::timer::{TimerStruct, TimerTrait};
+    /// use std::{io::sink, thread, time::Duration};
+    ///
+    /// let (handle, controller) = TimerStruct::new(0, 5, 0).unwrap().spawn(sink());
+    /// let mut watcher = controller.watcher();
+    ///
+    /// let overlay = thread::spawn(move || {
+    ///     // Blocks until the presenter's controller does something.
+    ///     assert!(watcher.wait_for_change(Duration::from_secs(10)));
+    ///     watcher.is_paused()
+    /// });
+    /// controller.pause();
+    /// assert!(overlay.join().unwrap());
+    ///
+    /// controller.cancel();
+    /// handle.join().unwrap().unwrap();
+    /// ```
+    #[derive(Clone, Debug)]
+    pub struct ClockWatcher {
+        state: Arc<(Mutex<ControlState>, Condvar)>,
+        /// The change count as of the last `wait_for_change`.
+        seen: u64,
+    }
+
+    impl ClockWatcher {
+        /// The remaining time, as of the last tick or adjustment.
+        pub fn remaining(&self) -> Duration {
+            self.state.0.lock().unwrap().remaining
+        }
+
+        /// Returns `true` while the countdown is paused.
+        pub fn is_paused(&self) -> bool {
+            self.state.0.lock().unwrap().paused
+        }
+
+        /// Returns `true` once the run has been cancelled.
+        pub fn is_cancelled(&self) -> bool {
+            self.state.0.lock().unwrap().cancelled
+        }
+
+        /// The number of times `TimerBuilder::auto_extend` extended the countdown.
+        pub fn extensions(&self) -> u32 {
+            self.state.0.lock().unwrap().extensions
+        }
+
+        /// Returns `true` once the countdown has reached zero.
+        pub fn is_finished(&self) -> bool {
+            self.state.0.lock().unwrap().finished
+        }
+
+        /// The wakeups and busy time of the run so far. See `TimerController::usage`.
+        pub fn usage(&self) -> TimerUsage {
+            self.state.0.lock().unwrap().usage
+        }
+
+        /// Waits up to `timeout` for the timer to be paused, resumed, cancelled, adjusted or
+        /// to finish, counting from the previous call, or from when the watcher was created.
         ///
         /// # Returns
         ///
-        /// * `Ok(TimerStruct)` if the total duration calculated from `hours`, `minutes`,
-        ///   and `seconds` is greater than 0.
-        /// * `Err("Duration need to be 1 or more seconds.")` if the total duration is 0.
+        /// * `true` if something changed; the getters show the new state.
+        /// * `false` if `timeout` passed first.
+        pub fn wait_for_change(&mut self, timeout: Duration) -> bool {
+            let (state, condvar) = &*self.state;
+            let seen = self.seen;
+            let (state, _) = condvar
+                .wait_timeout_while(state.lock().unwrap(), timeout, |state| {
+                    state.changes == seen
+                })
+                .unwrap();
+            self.seen = state.changes;
+            self.seen != seen
+        }
+    }
+
+    /// Shared state behind a `TimerController`.
+    #[derive(Debug)]
+    struct ControlState {
+        paused: bool,
+        cancelled: bool,
+        finished: bool,
+        /// Set when `remaining` was changed through the controller rather than by a tick.
+        adjusted: bool,
+        remaining: Duration,
+        usage: TimerUsage,
+        /// Bumped by every control operation and on finishing, for `ClockWatcher`.
+        changes: u64,
+        /// The extensions granted by the `AutoExtend` policy.
+        extensions: u32,
+    }
+
+    /// How much work a timer run has done, from `TimerController::usage`.
+    ///
+    /// A high wakeup count for the time counted down points at a short
+    /// `TimerBuilder::tick_interval` or `refresh_interval`. Time spent sleeping, paused
+    /// and in finish actions isn't counted as busy.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct TimerUsage {
+        /// How many times the tick loop woke up, including the first tick.
+        pub wakeups: u64,
+        /// The time spent between waking up and going back to sleep: rendering, writing
+        /// the status file and progress channel, and running callbacks.
+        pub busy: Duration,
+    }
+
+    /// Why `TimerController::sleep` returned.
+    #[derive(Debug, PartialEq, Eq)]
+    enum Wake {
+        /// The full duration passed.
+        Elapsed,
+        /// The run was cancelled.
+        Cancelled,
+        /// Time was added or subtracted after sleeping for the given unpaused time.
+        Adjusted(Duration),
+        /// The machine was suspended and `OnResume::CatchUp` counted the given time as
+        /// slept, more than was asked for.
+        CaughtUp(Duration),
+    }
+
+    /// Pauses, resumes, cancels and queries a timer started with `TimerStruct::spawn`.
+    ///
+    /// Clones control the same run, so the controller can be handed to other threads.
+    #[derive(Clone, Debug)]
+    pub struct TimerController {
+        state: Arc<(Mutex<ControlState>, Condvar)>,
+    }
+
+    impl TimerController {
+        pub(crate) fn new(duration: u32) -> TimerController {
+            TimerController {
+                state: Arc::new((
+                    Mutex::new(ControlState {
+                        paused: false,
+                        cancelled: false,
+                        finished: false,
+                        adjusted: false,
+                        remaining: Duration::from_secs(duration.into()),
+                        usage: TimerUsage::default(),
+                        changes: 0,
+                        extensions: 0,
+                    }),
+                    Condvar::new(),
+                )),
+            }
+        }
+
+        /// Pauses the countdown. Time spent paused doesn't count towards the timer.
+        pub fn pause(&self) {
+            self.update(|state| state.paused = true);
+        }
+
+        /// Resumes a paused countdown.
+        pub fn resume(&self) {
+            self.update(|state| state.paused = false);
+        }
+
+        /// Cancels the run. The timer thread stops at once, skipping the finish actions.
+        pub fn cancel(&self) {
+            self.update(|state| state.cancelled = true);
+        }
+
+        /// Adds `time` to the remaining time.
+        ///
+        /// The timer re-renders at once. The total used for percentages grows by the same
+        /// amount, so the elapsed time is unchanged. Has no effect once the countdown has
+        /// reached zero.
         ///
         /// # Examples
         ///
         /// ```
-        /// use your_crate_name::TimerStruct; // Replace your_crate_name with your actual crate name
+        /// use clock_timer::timer::{TimerStruct, TimerTrait};
+        /// use std::{io::sink, time::Duration};
         ///
-        /// let timer = TimerStruct::new(0, 1, 30).expect("Failed to create timer"); // 1 minute 30 seconds
-        /// let invalid_timer = TimerStruct::new(0, 0, 0); // This will return an Err
+        /// let timer = TimerStruct::new(0, 1, 0).unwrap();
+        /// let (handle, controller) = timer.spawn(sink());
+        /// controller.pause();
+        ///
+        /// controller.add_time(Duration::from_secs(60));
+        /// // Two minutes, less the moment the timer ran before it was paused.
+        /// assert!(controller.remaining() > Duration::from_secs(119));
+        ///
+        /// // Subtracting more than is left clamps to zero rather than underflowing.
+        /// controller.subtract_time(Duration::from_secs(600));
+        /// assert_eq!(controller.remaining(), Duration::ZERO);
+        ///
+        /// controller.resume();
+        /// handle.join().unwrap().unwrap();
         /// ```
-        fn new(hours: u32, minutes: u32, seconds: u32) -> Result<TimerStruct, &'static str> {
-            let duration = (hours * 3600) + (minutes * 60) + seconds;
+        pub fn add_time(&self, time: Duration) {
+            self.adjust(|remaining| remaining.saturating_add(time));
+        }
+
+        /// Subtracts `time` from the remaining time, clamping at zero.
+        ///
+        /// Like `add_time`, the timer re-renders at once; reaching zero finishes the run as
+        /// usual. Has no effect once the countdown has reached zero.
+        pub fn subtract_time(&self, time: Duration) {
+            self.adjust(|remaining| remaining.saturating_sub(time));
+        }
+
+        /// Returns `true` while the countdown is paused.
+        pub fn is_paused(&self) -> bool {
+            self.state.0.lock().unwrap().paused
+        }
+
+        /// Returns `true` once `cancel` has been called.
+        pub fn is_cancelled(&self) -> bool {
+            self.state.0.lock().unwrap().cancelled
+        }
+
+        /// The number of times `TimerBuilder::auto_extend` extended the countdown.
+        pub fn extensions(&self) -> u32 {
+            self.state.0.lock().unwrap().extensions
+        }
+
+        /// The remaining time as of the last rendered tick.
+        pub fn remaining(&self) -> Duration {
+            self.state.0.lock().unwrap().remaining
+        }
+
+        /// The wakeups and busy time of the run so far.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     time_source::VirtualClock,
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        /// use std::{io::sink, time::Duration};
+        ///
+        /// let timer = TimerBuilder::new(0, 0, 10)
+        ///     .clock(VirtualClock::new())
+        ///     .tick_interval(Duration::from_millis(100))
+        ///     .build()
+        ///     .unwrap();
+        /// let (handle, controller) = timer.spawn(sink());
+        /// handle.join().unwrap().unwrap();
+        ///
+        /// // A tick every 100ms for 10 seconds, plus the first.
+        /// assert_eq!(controller.usage().wakeups, 101);
+        /// ```
+        pub fn usage(&self) -> TimerUsage {
+            self.state.0.lock().unwrap().usage
+        }
+
+        /// A read-only handle on the same run, for components that should see the timer
+        /// but not control it.
+        pub fn watcher(&self) -> ClockWatcher {
+            let changes = self.state.0.lock().unwrap().changes;
+            ClockWatcher {
+                state: self.state.clone(),
+                seen: changes,
+            }
+        }
+
+        /// Counts a wakeup that kept the tick loop busy for `busy`.
+        fn account(&self, busy: Duration) {
+            let usage = &mut self.state.0.lock().unwrap().usage;
+            usage.wakeups += 1;
+            usage.busy += busy;
+        }
+
+        fn update(&self, change: impl FnOnce(&mut ControlState)) {
+            let (state, condvar) = &*self.state;
+            let mut state = state.lock().unwrap();
+            change(&mut state);
+            state.changes += 1;
+            condvar.notify_all();
+        }
+
+        fn adjust(&self, change: impl FnOnce(Duration) -> Duration) {
+            self.update(|state| {
+                if !state.finished {
+                    state.remaining = change(state.remaining);
+                    state.adjusted = true;
+                }
+            });
+        }
+
+        fn set_remaining(&self, remaining: Duration) {
+            self.state.0.lock().unwrap().remaining = remaining;
+        }
+
+        /// Stops accepting `add_time` and `subtract_time` once the countdown has reached zero.
+        fn finish(&self) {
+            self.update(|state| {
+                state.remaining = Duration::ZERO;
+                state.finished = true;
+                state.adjusted = false;
+            });
+        }
+
+        /// Clears a pending adjustment and returns the adjusted remaining time, less `slept`.
+        fn take_adjusted(&self, slept: Duration) -> Duration {
+            let mut state = self.state.0.lock().unwrap();
+            state.adjusted = false;
+            state.remaining = state.remaining.saturating_sub(slept);
+            state.remaining
+        }
+
+        /// Sleeps for `duration` of unpaused time on `clock`, or real time without one,
+        /// returning early on cancellation or when the remaining time is adjusted.
+        ///
+        /// In real time, a suspend during the sleep is handled according to `on_resume`.
+        fn sleep(
+            &self,
+            duration: Duration,
+            clock: Option<&dyn Clock>,
+            on_resume: OnResume,
+        ) -> Wake {
+            let (state, condvar) = &*self.state;
+            let mut slept = Duration::ZERO;
+            let mut guard = state.lock().unwrap();
+            loop {
+                if guard.cancelled {
+                    return Wake::Cancelled;
+                }
+                if guard.adjusted {
+                    return Wake::Adjusted(slept);
+                }
+                if guard.paused {
+                    guard = condvar
+                        .wait_while(guard, |state| {
+                            state.paused && !state.cancelled && !state.adjusted
+                        })
+                        .unwrap();
+                    continue;
+                }
+                if slept > duration {
+                    return Wake::CaughtUp(slept);
+                }
+                if slept == duration {
+                    return Wake::Elapsed;
+                }
+                match clock {
+                    Some(clock) => {
+                        drop(guard);
+                        clock.sleep(duration - slept);
+                        slept = duration;
+                        guard = state.lock().unwrap();
+                    }
+                    None => {
+                        let started = Instant::now();
+                        let wall_started = SystemTime::now();
+                        guard = condvar.wait_timeout(guard, duration - slept).unwrap().0;
+                        let monotonic = started.elapsed();
+                        // A wall clock set backwards reads as no suspend.
+                        let wall = wall_started.elapsed().unwrap_or(Duration::ZERO);
+                        let suspended = wall.saturating_sub(monotonic);
+                        if suspended <= SUSPEND_THRESHOLD {
+                            // Waking late by a hair must not look like catching up.
+                            slept = (slept + monotonic).min(duration);
+                            continue;
+                        }
+                        match on_resume {
+                            OnResume::CatchUp => slept += monotonic + suspended,
+                            OnResume::Pause => {
+                                slept = (slept + monotonic).min(duration);
+                                guard.paused = true;
+                            }
+                            OnResume::Continue => slept = (slept + monotonic).min(duration),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A group of identically configured timers started with `TimerStruct::spawn_batch` or
+    /// `TimerStruct::spawn_each`, one per worker.
+    #[derive(Debug)]
+    pub struct TimerBatch {
+        timers: Vec<(String, TimerController, JoinHandle<TimerResult>)>,
+    }
+
+    impl TimerBatch {
+        /// The number of timers in the batch.
+        pub fn len(&self) -> usize {
+            self.timers.len()
+        }
+
+        /// Returns `true` if the batch has no timers.
+        pub fn is_empty(&self) -> bool {
+            self.timers.is_empty()
+        }
+
+        /// The label and controller of every timer, in spawn order.
+        pub fn controllers(&self) -> impl Iterator<Item = (&str, &TimerController)> {
+            self.timers
+                .iter()
+                .map(|(label, controller, _)| (label.as_str(), controller))
+        }
+
+        /// Cancels every timer that is still running.
+        pub fn cancel_all(&self) {
+            for (_, controller, _) in &self.timers {
+                controller.cancel();
+            }
+        }
+
+        /// Returns `true` once every timer has finished or been cancelled.
+        pub fn is_finished(&self) -> bool {
+            self.timers
+                .iter()
+                .all(|(_, _, handle)| handle.is_finished())
+        }
+
+        /// Waits for every timer and returns each label with how its run ended, in spawn order.
+        ///
+        /// A timer thread that panicked is reported as an error.
+        pub fn join(self) -> Vec<(String, TimerResult)> {
+            self.timers
+                .into_iter()
+                .map(|(label, _, handle)| {
+                    let result = handle
+                        .join()
+                        .unwrap_or_else(|_| Err(io::Error::other("timer thread panicked")));
+                    (label, result)
+                })
+                .collect()
+        }
+    }
+
+    /// A callback fired every `every` of elapsed time, set with `announce_every`.
+    ///
+    /// Timers pass the remaining time to the callback, stopwatches the elapsed time.
+    #[derive(Clone)]
+    pub struct Announcement {
+        /// The interval between announcements.
+        pub every: Duration,
+        callback: DurationCallback,
+    }
+
+    impl Announcement {
+        /// Creates a new `Announcement` calling `callback` every `every`.
+        ///
+        /// A zero `every` never fires.
+        pub fn new<F>(every: Duration, callback: F) -> Announcement
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            Announcement {
+                every,
+                callback: Arc::new(Mutex::new(Box::new(callback))),
+            }
+        }
+
+        /// Fires the callback with `value` if a multiple of `every` lies in `(previous, elapsed]`.
+        pub(crate) fn announce(&self, previous: Duration, elapsed: Duration, value: Duration) {
+            if self.every.is_zero() {
+                return;
+            }
+            let every = self.every.as_nanos();
+            if elapsed.as_nanos() / every > previous.as_nanos() / every {
+                // A callback that panicked under `PanicPolicy::LogAndContinue` poisoned the lock.
+                (self.callback.lock().unwrap_or_else(PoisonError::into_inner))(value);
+            }
+        }
+
+        /// How long after `elapsed` the next announcement is due.
+        fn until_next(&self, elapsed: Duration) -> Option<Duration> {
+            if self.every.is_zero() {
+                return None;
+            }
+            let every = self.every.as_nanos();
+            let next = (elapsed.as_nanos() / every + 1) * every;
+            u64::try_from(next - elapsed.as_nanos())
+                .ok()
+                .map(Duration::from_nanos)
+        }
+    }
+
+    impl fmt::Debug for Announcement {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Announcement")
+                .field("every", &self.every)
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// A policy consulted when a countdown reaches zero, set with
+    /// `TimerBuilder::auto_extend`.
+    #[derive(Clone)]
+    pub struct AutoExtend {
+        callback: ExtendCallback,
+    }
+
+    impl AutoExtend {
+        /// Creates a policy asking `callback` whether to extend. It receives the number of
+        /// extensions granted so far and returns the time to add, or `None` to finish.
+        pub fn new<F>(callback: F) -> AutoExtend
+        where
+            F: FnMut(u32) -> Option<Duration> + Send + 'static,
+        {
+            AutoExtend {
+                callback: Arc::new(Mutex::new(Box::new(callback))),
+            }
+        }
+
+        fn extension(&self, granted: u32) -> Option<Duration> {
+            (self.callback.lock().unwrap_or_else(PoisonError::into_inner))(granted)
+                .filter(|extension| !extension.is_zero())
+        }
+    }
+
+    impl fmt::Debug for AutoExtend {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("AutoExtend").finish_non_exhaustive()
+        }
+    }
+
+    /// Keeps a finished timer counting up, set with `TimerBuilder::overtime`.
+    ///
+    /// Overtime is displayed with a leading `+` (e.g. `+0:1:23`). Clones of a
+    /// timer share the overtime counter, so `TimerStruct::overtime_elapsed` can
+    /// be read from another thread while `start_timer` runs.
+    #[derive(Clone)]
+    pub struct Overtime {
+        /// How long to keep counting past zero before `start_timer` returns.
+        pub limit: Duration,
+        callback: Option<DurationCallback>,
+        elapsed: Arc<AtomicU32>,
+    }
+
+    impl fmt::Debug for Overtime {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Overtime")
+                .field("limit", &self.limit)
+                .field("elapsed", &self.elapsed.load(Ordering::SeqCst))
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// A callback fired once when a countdown's remaining time crosses a mark.
+    #[derive(Clone)]
+    pub struct Checkpoint {
+        /// The remaining time at which the callback fires.
+        pub remaining: Duration,
+        callback: DurationCallback,
+    }
+
+    impl Checkpoint {
+        /// Creates a new `Checkpoint` calling `callback` when `remaining` time is left.
+        ///
+        /// The callback receives the actual remaining time when it fires.
+        pub fn new<F>(remaining: Duration, callback: F) -> Checkpoint
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            Checkpoint {
+                remaining,
+                callback: Arc::new(Mutex::new(Box::new(callback))),
+            }
+        }
+
+        fn fire(&self, remaining: Duration) {
+            (self.callback.lock().unwrap_or_else(PoisonError::into_inner))(remaining);
+        }
+    }
+
+    impl fmt::Debug for Checkpoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Checkpoint")
+                .field("remaining", &self.remaining)
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// Builds a `TimerStruct` with optional behaviour that `TimerTrait::new` doesn't cover.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::timer::TimerBuilder;
+    ///
+    /// let timer = TimerBuilder::new(0, 3, 0).build().expect("Failed to create timer");
+    /// assert_eq!(timer.duration, 180);
+    /// ```
+    #[derive(Clone)]
+    pub struct TimerBuilder {
+        days: u32,
+        hours: u32,
+        minutes: u32,
+        seconds: u32,
+        #[cfg(feature = "notify")]
+        notification: Option<Notification>,
+        #[cfg(feature = "sound")]
+        chime: Option<Chime>,
+        on_finish_exec: Option<String>,
+        checkpoints: Vec<Checkpoint>,
+        low_power: Option<LowPower>,
+        color_zones: Option<ColorZones>,
+        theme: Theme,
+        color: ColorChoice,
+        easing: Easing,
+        on_zone: Option<ZoneCallback>,
+        overtime: Option<Duration>,
+        auto_extend: Option<AutoExtend>,
+        on_overtime: Option<DurationCallback>,
+        announcements: Vec<Announcement>,
+        output: OutputMode,
+        status_file: Option<PathBuf>,
+        #[cfg(unix)]
+        control_socket: Option<PathBuf>,
+        #[cfg(unix)]
+        owner: Option<u32>,
+        progress: Option<ProgressWriter>,
+        output_buffer: Option<usize>,
+        clock: Option<Arc<dyn Clock>>,
+        tick_interval: Duration,
+        refresh_interval: Duration,
+        on_resume: OnResume,
+        on_callback_panic: PanicPolicy,
+        #[cfg(feature = "locale")]
+        locale: Option<Locale>,
+    }
+
+    impl fmt::Debug for TimerBuilder {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut debug = f.debug_struct("TimerBuilder");
+            debug
+                .field("days", &self.days)
+                .field("hours", &self.hours)
+                .field("minutes", &self.minutes)
+                .field("seconds", &self.seconds);
+            #[cfg(feature = "notify")]
+            debug.field("notification", &self.notification);
+            #[cfg(feature = "sound")]
+            debug.field("chime", &self.chime);
+            debug
+                .field("on_finish_exec", &self.on_finish_exec)
+                .field("checkpoints", &self.checkpoints)
+                .field("low_power", &self.low_power)
+                .field("color_zones", &self.color_zones)
+                .field("theme", &self.theme)
+                .field("color", &self.color)
+                .field("easing", &self.easing)
+                .field("overtime", &self.overtime)
+                .field("auto_extend", &self.auto_extend)
+                .field("announcements", &self.announcements)
+                .field("output", &self.output)
+                .field("status_file", &self.status_file)
+                .field("progress", &self.progress)
+                .field("output_buffer", &self.output_buffer)
+                .field("clock", &self.clock)
+                .field("tick_interval", &self.tick_interval)
+                .field("refresh_interval", &self.refresh_interval)
+                .field("on_resume", &self.on_resume)
+                .field("on_callback_panic", &self.on_callback_panic);
+            #[cfg(unix)]
+            debug
+                .field("control_socket", &self.control_socket)
+                .field("owner", &self.owner);
+            #[cfg(feature = "locale")]
+            debug.field("locale", &self.locale);
+            debug.finish_non_exhaustive()
+        }
+    }
+
+    impl TimerBuilder {
+        /// Creates a new `TimerBuilder` for a timer of the given duration.
+        pub fn new(hours: u32, minutes: u32, seconds: u32) -> TimerBuilder {
+            TimerBuilder {
+                days: 0,
+                hours,
+                minutes,
+                seconds,
+                #[cfg(feature = "notify")]
+                notification: None,
+                #[cfg(feature = "sound")]
+                chime: None,
+                on_finish_exec: None,
+                checkpoints: Vec::new(),
+                low_power: None,
+                color_zones: None,
+                theme: Theme::DEFAULT,
+                color: ColorChoice::Auto,
+                easing: Easing::Linear,
+                on_zone: None,
+                overtime: None,
+                auto_extend: None,
+                on_overtime: None,
+                announcements: Vec::new(),
+                output: OutputMode::default(),
+                status_file: None,
+                #[cfg(unix)]
+                control_socket: None,
+                #[cfg(unix)]
+                owner: None,
+                progress: None,
+                output_buffer: None,
+                clock: None,
+                tick_interval: Duration::from_secs(1),
+                refresh_interval: Duration::from_secs(1),
+                on_resume: OnResume::default(),
+                on_callback_panic: PanicPolicy::default(),
+                #[cfg(feature = "locale")]
+                locale: None,
+            }
+        }
+
+        /// Adds `days` to the duration given to `new`, for countdowns that span days.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::TimerBuilder;
+        ///
+        /// let timer = TimerBuilder::new(12, 0, 0).days(2).build().unwrap();
+        /// assert_eq!(timer.duration, 60 * 3600);
+        /// ```
+        pub fn days(mut self, days: u32) -> TimerBuilder {
+            self.days = days;
+            self
+        }
+
+        /// Shows a desktop notification with the given body text when the timer completes.
+        ///
+        /// The title defaults to `Notification::DEFAULT_TITLE` unless set with
+        /// `notification_title`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::TimerBuilder;
+        ///
+        /// let timer = TimerBuilder::new(0, 4, 0)
+        ///     .notify_on_finish("Tea is ready!")
+        ///     .build()
+        ///     .unwrap();
+        /// assert_eq!(timer.notification.unwrap().body, "Tea is ready!");
+        /// ```
+        #[cfg(feature = "notify")]
+        pub fn notify_on_finish(mut self, body: impl Into<String>) -> TimerBuilder {
+            match &mut self.notification {
+                Some(notification) => notification.body = body.into(),
+                None => {
+                    self.notification = Some(Notification::new(Notification::DEFAULT_TITLE, body))
+                }
+            }
+            self
+        }
+
+        /// Sets the title of the notification shown when the timer completes.
+        #[cfg(feature = "notify")]
+        pub fn notification_title(mut self, title: impl Into<String>) -> TimerBuilder {
+            match &mut self.notification {
+                Some(notification) => notification.title = title.into(),
+                None => self.notification = Some(Notification::new(title, "")),
+            }
+            self
+        }
+
+        /// Plays `chime` when the timer completes.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{sound::Chime, timer::TimerBuilder};
+        ///
+        /// let timer = TimerBuilder::new(0, 0, 30)
+        ///     .chime_on_finish(Chime::Bell)
+        ///     .build()
+        ///     .unwrap();
+        /// assert_eq!(timer.chime, Some(Chime::Bell));
+        /// ```
+        #[cfg(feature = "sound")]
+        pub fn chime_on_finish(mut self, chime: Chime) -> TimerBuilder {
+            self.chime = Some(chime);
+            self
+        }
+
+        /// Runs `command` through the platform shell (`sh -c` or `cmd /C`) when the timer completes.
+        ///
+        /// The command receives the configured duration and the time the run actually took,
+        /// including any time paused or added, in seconds through the `CLOCK_TIMER_TARGET_SECS`
+        /// and `CLOCK_TIMER_ELAPSED_SECS` environment variables. If it can't be started or exits
+        /// with a failure status, `start_timer` returns the error.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::{fs, io::sink, thread, time::Duration};
+        ///
+        /// let timer = TimerBuilder::new(0, 25, 0)
+        ///     .on_finish_exec("notify-send done")
+        ///     .build()
+        ///     .unwrap();
+        /// assert_eq!(timer.on_finish_exec.as_deref(), Some("notify-send done"));
+        ///
+        /// // A one-second timer that is paused for a second and extended by another.
+        /// let path = std::env::temp_dir().join("clock-timer-finish-exec-doctest.txt");
+        /// let command = if cfg!(windows) {
+        ///     format!("echo %CLOCK_TIMER_TARGET_SECS% %CLOCK_TIMER_ELAPSED_SECS%> \"{}\"", path.display())
+        /// } else {
+        ///     format!("echo $CLOCK_TIMER_TARGET_SECS $CLOCK_TIMER_ELAPSED_SECS > '{}'", path.display())
+        /// };
+        /// let timer = TimerBuilder::new(0, 0, 1).on_finish_exec(command).build().unwrap();
+        /// let (handle, controller) = timer.spawn(sink());
+        /// controller.pause();
+        /// thread::sleep(Duration::from_secs(1));
+        /// controller.add_time(Duration::from_secs(1));
+        /// controller.resume();
+        /// handle.join().unwrap().unwrap();
+        ///
+        /// let output = fs::read_to_string(&path).unwrap();
+        /// let secs: Vec<u32> = output.split_whitespace().map(|n| n.parse().unwrap()).collect();
+        /// assert_eq!(secs[0], 1);
+        /// assert!(secs[1] >= 3, "{output}");
+        /// fs::remove_file(path).unwrap();
+        /// ```
+        pub fn on_finish_exec(mut self, command: impl Into<String>) -> TimerBuilder {
+            self.on_finish_exec = Some(command.into());
+            self
+        }
+
+        /// Calls `callback` once when the countdown reaches `remaining` time left.
+        ///
+        /// Several checkpoints can be registered; each fires exactly once per run, in
+        /// order of decreasing `remaining`. Marks at or above the full duration are
+        /// never crossed and therefore never fire.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::{io::sink, sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let timer = TimerBuilder::new(0, 0, 1)
+        ///     .at_remaining(Duration::from_secs(60), |_| println!("one minute left!"))
+        ///     .at_remaining(Duration::ZERO, move |remaining| sender.send(remaining).unwrap())
+        ///     .build()
+        ///     .unwrap();
+        /// timer.start_timer(&mut sink()).unwrap();
+        ///
+        /// // The one minute mark is never crossed by a one second timer.
+        /// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [Duration::ZERO]);
+        /// ```
+        pub fn at_remaining<F>(mut self, remaining: Duration, callback: F) -> TimerBuilder
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            self.checkpoints.push(Checkpoint::new(remaining, callback));
+            self
+        }
+
+        /// Lengthens the render interval while a lot of time remains, reducing wakeups.
+        ///
+        /// Checkpoints still fire on time: the timer wakes up early to hit their marks.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{LowPower, TimerBuilder};
+        /// use std::time::Duration;
+        ///
+        /// // Update every 30 seconds until the last two minutes.
+        /// let timer = TimerBuilder::new(1, 0, 0)
+        ///     .low_power(LowPower {
+        ///         interval: Duration::from_secs(30),
+        ///         threshold: Duration::from_secs(120),
+        ///     })
+        ///     .build()
+        ///     .unwrap();
+        /// assert!(timer.low_power.is_some());
+        /// ```
+        pub fn low_power(mut self, low_power: LowPower) -> TimerBuilder {
+            self.low_power = Some(low_power);
+            self
+        }
+
+        /// Colours the display green, yellow and red as the `zones` thresholds are crossed,
+        /// for speakers keeping an eye on a talk's time.
+        ///
+        /// The terminal display and polybar output are coloured. `build` rejects a red
+        /// threshold above the yellow one.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     output::{ColorZone, OutputMode},
+        ///     time_source::VirtualClock,
+        ///     timer::{ColorZones, TimerBuilder, TimerTrait},
+        /// };
+        /// use std::{sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let talk = TimerBuilder::new(0, 0, 3)
+        ///     .color_zones(ColorZones {
+        ///         yellow: Duration::from_secs(2),
+        ///         red: Duration::from_secs(1),
+        ///     })
+        ///     .on_zone(move |zone| sender.send(zone).unwrap())
+        ///     .output(OutputMode::Tty)
+        ///     .clock(VirtualClock::new())
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// talk.start_timer(&mut output).unwrap();
+        /// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [ColorZone::Yellow, ColorZone::Red]);
+        /// # #[cfg(unix)]
+        /// assert!(String::from_utf8(output).unwrap().starts_with("\x1b[32m0:0:3\x1b[0m"));
+        /// ```
+        pub fn color_zones(mut self, zones: ColorZones) -> TimerBuilder {
+            self.color_zones = Some(zones);
+            self
+        }
+
+        /// Calls `callback` with the new zone whenever the countdown enters the yellow or
+        /// red zone set with `color_zones`.
+        ///
+        /// A zone the countdown starts in isn't announced.
+        pub fn on_zone<F>(mut self, callback: F) -> TimerBuilder
+        where
+            F: FnMut(ColorZone) + Send + 'static,
+        {
+            self.on_zone = Some(Arc::new(Mutex::new(Box::new(callback))));
+            self
+        }
+
+        /// Draws the `color_zones` colours in `theme`, e.g. `Theme::HIGH_CONTRAST` for a
+        /// projector or `Theme::COLOR_BLIND` for red-green colour blindness.
+        pub fn theme(mut self, theme: Theme) -> TimerBuilder {
+            self.theme = theme;
+            self
+        }
+
+        /// Chooses whether the display is coloured. The default, `ColorChoice::Auto`,
+        /// respects the `NO_COLOR` environment variable.
+        pub fn color(mut self, choice: ColorChoice) -> TimerBuilder {
+            self.color = choice;
+            self
+        }
+
+        /// Maps the remaining time onto progress displays along `easing`: the waybar
+        /// percentage and the `Glyph` output. The time shown stays exact.
+        pub fn easing(mut self, easing: Easing) -> TimerBuilder {
+            self.easing = easing;
+            self
+        }
+
+        /// Calls `callback` with the remaining time every `every` of elapsed time, e.g. to
+        /// speak or log progress once a minute.
+        ///
+        /// Announcements are measured from the start of the countdown, and a low-power
+        /// cadence wakes up in time for them.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::{io::sink, sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let timer = TimerBuilder::new(0, 0, 2)
+        ///     .announce_every(Duration::from_secs(1), move |remaining| {
+        ///         sender.send(remaining).unwrap()
+        ///     })
+        ///     .build()
+        ///     .unwrap();
+        /// timer.start_timer(&mut sink()).unwrap();
+        ///
+        /// assert_eq!(
+        ///     receiver.try_iter().collect::<Vec<_>>(),
+        ///     [Duration::from_secs(1), Duration::ZERO]
+        /// );
+        /// ```
+        pub fn announce_every<F>(mut self, every: Duration, callback: F) -> TimerBuilder
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            self.announcements.push(Announcement::new(every, callback));
+            self
+        }
+
+        /// Keeps counting up for up to `limit` after the timer reaches 0, for meeting and
+        /// cooking timers where running over matters.
+        ///
+        /// The finish actions (notification, chime, `on_finish_exec`) still run at 0;
+        /// `start_timer` then returns once `limit` of overtime has passed.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::{sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let timer = TimerBuilder::new(0, 0, 1)
+        ///     .overtime(Duration::from_secs(1))
+        ///     .on_overtime(move |overtime| sender.send(overtime).unwrap())
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// timer.start_timer(&mut output).unwrap();
+        ///
+        /// assert!(String::from_utf8(output).unwrap().ends_with("+0:0:1\n"));
+        /// assert_eq!(timer.overtime_elapsed(), Duration::from_secs(1));
+        /// assert_eq!(receiver.recv().unwrap(), Duration::from_secs(1));
+        /// ```
+        pub fn overtime(mut self, limit: Duration) -> TimerBuilder {
+            self.overtime = Some(limit);
+            self
+        }
+
+        /// Asks `callback` whether to keep going each time the countdown reaches zero, e.g.
+        /// to add five minutes while a build is still running, at most three times.
+        ///
+        /// The callback receives the number of extensions granted so far. Returning
+        /// `Some(time)` adds `time` and counts on without rendering zero; `None` finishes
+        /// as usual. Checkpoints already passed don't fire again. The extensions granted
+        /// are available from `TimerController::extensions`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     time_source::VirtualClock,
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        /// use std::{io::sink, time::Duration};
+        ///
+        /// let build_running = || true;
+        /// let timer = TimerBuilder::new(0, 10, 0)
+        ///     .clock(VirtualClock::new())
+        ///     .auto_extend(move |granted| {
+        ///         (build_running() && granted < 3).then_some(Duration::from_secs(5 * 60))
+        ///     })
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let (handle, controller) = timer.spawn(sink());
+        /// handle.join().unwrap().unwrap();
+        /// assert_eq!(controller.extensions(), 3);
+        /// ```
+        pub fn auto_extend<F>(mut self, callback: F) -> TimerBuilder
+        where
+            F: FnMut(u32) -> Option<Duration> + Send + 'static,
+        {
+            self.auto_extend = Some(AutoExtend::new(callback));
+            self
+        }
+
+        /// Calls `callback` once when the timer first runs past its target.
+        ///
+        /// The callback receives the overtime elapsed at that point. It only fires when
+        /// overtime is enabled with `overtime`.
+        pub fn on_overtime<F>(mut self, callback: F) -> TimerBuilder
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            self.on_overtime = Some(Arc::new(Mutex::new(Box::new(callback))));
+            self
+        }
+
+        /// Sets how each tick is written. Defaults to `OutputMode::Auto`, which overwrites a
+        /// single line on a terminal and prints a line per tick when output is redirected.
+        ///
+        /// The machine-readable modes write one line per tick and leave out the terminal
+        /// bell of `Chime::Bell`, which would corrupt their output.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{output::OutputMode, timer::{TimerBuilder, TimerTrait}};
+        ///
+        /// let timer = TimerBuilder::new(0, 0, 1)
+        ///     .output(OutputMode::I3blocks)
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// timer.start_timer(&mut output).unwrap();
+        /// assert_eq!(String::from_utf8(output).unwrap(), "0:0:1\n0:0:0\n");
+        /// ```
+        pub fn output(mut self, output: OutputMode) -> TimerBuilder {
+            self.output = output;
+            self
+        }
+
+        /// Rewrites `path` with the current time on every tick, alongside the normal output.
+        ///
+        /// This suits status lines that poll a command, e.g. `#(cat /tmp/timer)` in a tmux
+        /// `status-right`. See `output::write_status_file` for how the file is updated.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::io::sink;
+        ///
+        /// let path = std::env::temp_dir().join("clock-timer-tmux-doctest");
+        /// let timer = TimerBuilder::new(0, 0, 1)
+        ///     .status_file(&path)
+        ///     .build()
+        ///     .unwrap();
+        /// timer.start_timer(&mut sink()).unwrap();
+        /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "0:0:0\n");
+        /// # std::fs::remove_file(&path).unwrap();
+        /// ```
+        pub fn status_file(mut self, path: impl Into<PathBuf>) -> TimerBuilder {
+            self.status_file = Some(path.into());
+            self
+        }
+
+        /// Listens on a Unix domain socket at `path` while the timer runs, so other processes
+        /// can pause, resume, query or extend it. See the `control` module for the protocol.
+        ///
+        /// Starting the timer fails if another timer is already listening at `path`.
+        ///
+        /// # Examples
+        ///
+        /// ```no_run
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::io::stdout;
+        ///
+        /// // From another terminal: `echo pause | nc -U /tmp/tea.sock`
+        /// let timer = TimerBuilder::new(0, 4, 0)
+        ///     .control_socket("/tmp/tea.sock")
+        ///     .build()
+        ///     .unwrap();
+        /// timer.start_timer(&mut stdout()).unwrap();
+        /// ```
+        #[cfg(unix)]
+        pub fn control_socket(mut self, path: impl Into<PathBuf>) -> TimerBuilder {
+            self.control_socket = Some(path.into());
+            self
+        }
+
+        /// Ties the timer to process `pid`: once that process exits, the countdown is
+        /// cancelled, so a timer started for a script that crashed doesn't outlive it.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     output::OutputMode,
+        ///     timer::{TimerBuilder, TimerOutcome, TimerTrait},
+        /// };
+        /// use std::{io::sink, process::Command};
+        ///
+        /// let mut script = Command::new("sleep").arg("0.2").spawn().unwrap();
+        /// let timer = TimerBuilder::new(0, 1, 0)
+        ///     .owner(script.id())
+        ///     .output(OutputMode::Plain)
+        ///     .build()
+        ///     .unwrap();
+        /// let (handle, _controller) = timer.spawn(sink());
+        ///
+        /// script.wait().unwrap();
+        /// let outcome = handle.join().unwrap().unwrap();
+        /// assert!(matches!(outcome, TimerOutcome::Cancelled { .. }));
+        /// ```
+        #[cfg(unix)]
+        pub fn owner(mut self, pid: u32) -> TimerBuilder {
+            self.owner = Some(pid);
+            self
+        }
+
+        /// Writes a compact `remaining=NN` line to `writer` on every tick, alongside the
+        /// normal output. See `ProgressWriter` for the format.
+        ///
+        /// # Examples
+        ///
+        /// Reporting progress on file descriptor 3, as set up by a wrapping program:
+        ///
+        /// ```no_run
+        /// # #[cfg(unix)]
+        /// # {
+        /// use clock_timer::timer::{TimerBuilder, TimerTrait};
+        /// use std::{fs::File, io::stdout, os::fd::{FromRawFd, OwnedFd}};
+        ///
+        /// // SAFETY: the parent process opened fd 3 for us and nothing else uses it.
+        /// let progress = File::from(unsafe { OwnedFd::from_raw_fd(3) });
+        /// let timer = TimerBuilder::new(0, 5, 0)
+        ///     .progress_writer(progress)
+        ///     .build()
+        ///     .unwrap();
+        /// timer.start_timer(&mut stdout()).unwrap();
+        /// # }
+        /// ```
+        pub fn progress_writer<W: Write + Send + 'static>(mut self, writer: W) -> TimerBuilder {
+            self.progress = Some(ProgressWriter::new(writer));
+            self
+        }
+
+        /// Writes the display from a separate output thread, so a writer that blocks, like a
+        /// full pipe or a stalled network stream, never delays the countdown.
+        ///
+        /// Up to `capacity` rendered ticks wait for the writer; when it falls further behind
+        /// the oldest waiting tick is dropped, since a newer one supersedes it. `build`
+        /// rejects a zero capacity.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     output::OutputMode,
+        ///     time_source::VirtualClock,
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        /// use std::{
+        ///     io::{self, Write},
+        ///     panic::{self, AssertUnwindSafe},
+        ///     thread,
+        ///     time::Duration,
+        /// };
+        ///
+        /// /// A sink that takes a while to accept each write, like a congested socket.
+        /// struct Slow(Vec<u8>);
+        ///
+        /// impl Write for Slow {
+        ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        ///         thread::sleep(Duration::from_millis(20));
+        ///         self.0.write(buf)
+        ///     }
+        ///
+        ///     fn flush(&mut self) -> io::Result<()> {
+        ///         Ok(())
+        ///     }
+        /// }
+        ///
+        /// let timer = TimerBuilder::new(0, 0, 30)
+        ///     .output(OutputMode::Plain)
+        ///     .clock(VirtualClock::new())
+        ///     .output_buffer(2)
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut writer = Slow(Vec::new());
+        /// timer.start_timer(&mut writer).unwrap();
+        /// // Some ticks may have been dropped on the way, but never the final one.
+        /// assert!(String::from_utf8(writer.0).unwrap().ends_with("0:0:1\n0:0:0\n"));
+        ///
+        /// // A panicking callback still unwinds out of `start_timer`.
+        /// let timer = TimerBuilder::new(0, 0, 5)
+        ///     .output(OutputMode::Plain)
+        ///     .clock(VirtualClock::new())
+        ///     .output_buffer(2)
+        ///     .announce_every(Duration::from_secs(1), |_| panic!("announcer failed"))
+        ///     .build()
+        ///     .unwrap();
+        /// let run = panic::catch_unwind(AssertUnwindSafe(|| timer.start_timer(&mut io::sink())));
+        /// assert!(run.is_err());
+        /// ```
+        pub fn output_buffer(mut self, capacity: usize) -> TimerBuilder {
+            self.output_buffer = Some(capacity);
+            self
+        }
+
+        /// Checks checkpoints and announcements every `interval` instead of every second.
+        ///
+        /// A short tick makes sub-second marks fire on time without redrawing more often;
+        /// the display still refreshes at the `refresh_interval`. `build` rejects a zero
+        /// interval.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     output::OutputMode,
+        ///     time_source::VirtualClock,
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        /// use std::{sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let timer = TimerBuilder::new(0, 0, 2)
+        ///     .tick_interval(Duration::from_millis(100))
+        ///     .at_remaining(Duration::from_millis(1500), move |remaining| {
+        ///         sender.send(remaining).unwrap()
+        ///     })
+        ///     .output(OutputMode::Plain)
+        ///     .clock(VirtualClock::new())
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// timer.start_timer(&mut output).unwrap();
+        /// assert_eq!(receiver.recv().unwrap(), Duration::from_millis(1500));
+        /// // Still one line per second.
+        /// assert_eq!(String::from_utf8(output).unwrap(), "0:0:2\n0:0:1\n0:0:0\n");
+        /// ```
+        pub fn tick_interval(mut self, interval: Duration) -> TimerBuilder {
+            self.tick_interval = interval;
+            self
+        }
+
+        /// Redraws the display every `interval` instead of every second.
+        ///
+        /// `build` rejects an interval shorter than the `tick_interval`. A low-power cadence
+        /// set with `low_power` takes over while a lot of time remains.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::TimerBuilder;
+        /// use std::time::Duration;
+        ///
+        /// let timer = TimerBuilder::new(0, 5, 0)
+        ///     .refresh_interval(Duration::from_secs(5))
+        ///     .build()
+        ///     .unwrap();
+        /// assert_eq!(timer.refresh_interval, Duration::from_secs(5));
+        ///
+        /// let invalid = TimerBuilder::new(0, 5, 0)
+        ///     .tick_interval(Duration::from_secs(2))
+        ///     .refresh_interval(Duration::from_secs(1))
+        ///     .build();
+        /// assert!(invalid.is_err());
+        /// ```
+        pub fn refresh_interval(mut self, interval: Duration) -> TimerBuilder {
+            self.refresh_interval = interval;
+            self
+        }
+
+        /// Chooses what the countdown does when the machine wakes from suspend, `Continue`
+        /// by default.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{OnResume, TimerBuilder};
+        ///
+        /// // A tea timer should still go off on time after the lid was closed.
+        /// let timer = TimerBuilder::new(0, 4, 0)
+        ///     .on_resume(OnResume::CatchUp)
+        ///     .build()
+        ///     .unwrap();
+        /// assert_eq!(timer.on_resume, OnResume::CatchUp);
+        /// ```
+        pub fn on_resume(mut self, policy: OnResume) -> TimerBuilder {
+            self.on_resume = policy;
+            self
+        }
+
+        /// Chooses what happens when a checkpoint, announcement, colour zone, overtime or
+        /// `auto_extend` callback panics, `Propagate` by default. See `CallbackPanicked` for an example.
+        pub fn on_callback_panic(mut self, policy: PanicPolicy) -> TimerBuilder {
+            self.on_callback_panic = policy;
+            self
+        }
+
+        /// Renders the display in `locale`: its digits and separator on the terminal and in
+        /// status bars, and its title for a notification left at the default title.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     locale::Locale,
+        ///     output::OutputMode,
+        ///     time_source::VirtualClock,
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        ///
+        /// let timer = TimerBuilder::new(0, 0, 2)
+        ///     .locale(Locale::ARABIC)
+        ///     .output(OutputMode::Plain)
+        ///     .clock(VirtualClock::new())
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// timer.start_timer(&mut output).unwrap();
+        /// assert_eq!(String::from_utf8(output).unwrap(), "٠:٠:٢\n٠:٠:١\n٠:٠:٠\n");
+        /// ```
+        #[cfg(feature = "locale")]
+        pub fn locale(mut self, locale: Locale) -> TimerBuilder {
+            self.locale = Some(locale);
+            self
+        }
+
+        /// Drives the countdown from `clock` instead of real time.
+        ///
+        /// With a `VirtualClock` the whole run completes instantly with the same ticks it
+        /// would render in real time, which makes tick loops cheap to test and lets
+        /// simulations run accelerated. Pausing, cancelling and adjusting a spawned timer
+        /// take effect once the current sleep on `clock` returns.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     output::OutputMode,
+        ///     time_source::{Clock, VirtualClock},
+        ///     timer::{TimerBuilder, TimerTrait},
+        /// };
+        /// use std::time::Duration;
+        ///
+        /// let clock = VirtualClock::new();
+        /// let timer = TimerBuilder::new(0, 0, 5)
+        ///     .output(OutputMode::Plain)
+        ///     .clock(clock.clone())
+        ///     .build()
+        ///     .unwrap();
+        ///
+        /// let mut output = Vec::new();
+        /// timer.start_timer(&mut output).unwrap();
+        /// assert_eq!(String::from_utf8(output).unwrap(), "0:0:5\n0:0:4\n0:0:3\n0:0:2\n0:0:1\n0:0:0\n");
+        /// assert_eq!(clock.now(), Duration::from_secs(5));
+        /// ```
+        pub fn clock<C: Clock + 'static>(mut self, clock: C) -> TimerBuilder {
+            self.clock = Some(Arc::new(clock));
+            self
+        }
+
+        /// Builds the `TimerStruct`.
+        ///
+        /// # Returns
+        ///
+        /// * `Ok(TimerStruct)` if the configured duration is greater than 0.
+        /// * `Err("Duration need to be 1 or more seconds.")` if the total duration is 0.
+        pub fn build(self) -> Result<TimerStruct, &'static str> {
+            let mut timer =
+                TimerStruct::from_dhms(self.days, self.hours, self.minutes, self.seconds)?;
+            #[cfg(feature = "notify")]
+            {
+                timer.notification = self.notification;
+            }
+            #[cfg(feature = "sound")]
+            {
+                timer.chime = self.chime;
+            }
+            timer.on_finish_exec = self.on_finish_exec;
+            timer.checkpoints = self.checkpoints;
+            timer.low_power = self.low_power;
+            if let Some(zones) = self.color_zones {
+                if zones.red > zones.yellow {
+                    return Err("Red zone threshold can't be above the yellow threshold.");
+                }
+                if let Some(on_zone) = self.on_zone {
+                    for (mark, zone) in [
+                        (zones.yellow, ColorZone::Yellow),
+                        (zones.red, ColorZone::Red),
+                    ] {
+                        let on_zone = on_zone.clone();
+                        timer.checkpoints.push(Checkpoint::new(mark, move |_| {
+                            (on_zone.lock().unwrap_or_else(PoisonError::into_inner))(zone)
+                        }));
+                    }
+                }
+            }
+            timer.color_zones = self.color_zones;
+            timer.theme = self.theme;
+            timer.color = self.color;
+            timer.easing = self.easing;
+            timer.auto_extend = self.auto_extend;
+            timer.overtime = self.overtime.map(|limit| Overtime {
+                limit,
+                callback: self.on_overtime,
+                elapsed: Arc::new(AtomicU32::new(0)),
+            });
+            timer.announcements = self.announcements;
+            timer.output = self.output;
+            timer.status_file = self.status_file;
+            #[cfg(unix)]
+            {
+                if self.owner == Some(0)
+                    || self.owner.is_some_and(|pid| i32::try_from(pid).is_err())
+                {
+                    return Err("Owner must be a valid process ID.");
+                }
+                timer.control_socket = self.control_socket;
+                timer.owner = self.owner;
+            }
+            timer.progress = self.progress;
+            if self.output_buffer == Some(0) {
+                return Err("Output buffer must hold at least 1 tick.");
+            }
+            timer.output_buffer = self.output_buffer;
+            timer.clock = self.clock;
+            if self.tick_interval.is_zero() {
+                return Err("Tick interval must be greater than 0.");
+            }
+            if self.refresh_interval < self.tick_interval {
+                return Err("Refresh interval can't be shorter than the tick interval.");
+            }
+            timer.tick_interval = self.tick_interval;
+            timer.refresh_interval = self.refresh_interval;
+            timer.on_resume = self.on_resume;
+            timer.on_callback_panic = self.on_callback_panic;
+            #[cfg(all(feature = "locale", feature = "notify"))]
+            if let (Some(locale), Some(notification)) = (self.locale, &mut timer.notification)
+                && notification.title == Notification::DEFAULT_TITLE
+            {
+                notification.title = locale.finished.to_string();
+            }
+            #[cfg(feature = "locale")]
+            {
+                timer.locale = self.locale;
+            }
+            Ok(timer)
+        }
+    }
+
+    impl TimerTrait for TimerStruct {
+        /// Creates a new `TimerStruct` instance.
+        ///
+        /// # Arguments
+        ///
+        /// * `hours` - The hours component of the timer duration.
+        /// * `minutes` - The minutes component of the timer duration.
+        /// * `seconds` - The seconds component of the timer duration.
+        ///
+        /// # Returns
+        ///
+        /// * `Ok(TimerStruct)` if the total duration calculated from `hours`, `minutes`,
+        ///   and `seconds` is greater than 0.
+        /// * `Err("Duration need to be 1 or more seconds.")` if the total duration is 0.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerStruct, TimerTrait};
+        ///
+        /// let timer = TimerStruct::new(0, 1, 30).expect("Failed to create timer"); // 1 minute 30 seconds
+        /// let invalid_timer = TimerStruct::new(0, 0, 0); // This will return an Err
+        /// ```
+        fn new(hours: u32, minutes: u32, seconds: u32) -> Result<TimerStruct, &'static str> {
+            let duration = engine::hms_secs(hours, minutes, seconds);
+
+            if duration == 0 {
+                return Err("Duration need to be 1 or more seconds.");
+            }
+            if duration > u64::from(u32::MAX) {
+                return Err("Duration doesn't fit in u32 seconds.");
+            }
+
+            Ok(TimerStruct::const_new(hours, minutes, seconds))
+        }
+
+        /// Starts the countdown timer.
+        ///
+        /// The timer will print the remaining time to the provided writer every second
+        /// (less often while a `TimerBuilder::low_power` cadence applies), overwriting the
+        /// previous line on a terminal or as set with `TimerBuilder::output`. When the timer reaches 0, it prints the final
+        /// `0:0:0` with a newline and stops. Checkpoints registered with
+        /// `TimerBuilder::at_remaining` fire as their marks are crossed. If a notification was configured through
+        /// `TimerBuilder::notify_on_finish`, it is shown at that point, and a chime set with
+        /// `TimerBuilder::chime_on_finish` is played. Finally the command set with
+        /// `TimerBuilder::on_finish_exec` is run. With `TimerBuilder::overtime` set, the timer
+        /// then keeps counting up as `+h:m:s` until the overtime limit is reached.
+        ///
+        /// # Arguments
+        ///
+        /// * `writer` - A mutable reference to any type that implements the `std::io::Write`
+        ///   trait (e.g., `&mut std::io::Stdout`).
+        ///
+        /// # Returns
+        ///
+        /// * `Ok(())` once the countdown has finished.
+        /// * `Err(io::Error)` if writing to `writer` failed, or if the `on_finish_exec` command
+        ///   couldn't be started or exited with a failure status.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerStruct, TimerTrait};
+        /// use std::io::stdout;
+        ///
+        /// let timer = TimerStruct::new(0, 0, 1).unwrap(); // 1-second timer
+        /// let mut writer = stdout();
+        /// timer.start_timer(&mut writer).unwrap();
+        /// println!("Timer finished!");
+        /// ```
+        fn start_timer<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+            self.run(writer, &TimerController::new(self.duration))
+                .map(|_| ())
+        }
+    }
+
+    impl fmt::Display for TimerStruct {
+        /// Formats the full duration as zero-padded `HH:MM:SS`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerStruct, TimerTrait};
+        ///
+        /// let timer = TimerStruct::new(1, 2, 3).unwrap();
+        /// assert_eq!(timer.to_string(), "01:02:03");
+        /// ```
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            format::format_into_fmt(self.duration.into(), f).map(|_| ())
+        }
+    }
+
+    impl fmt::Display for TimerController {
+        /// Formats the remaining time as zero-padded `HH:MM:SS`.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            format::format_into_fmt(self.remaining().as_secs(), f).map(|_| ())
+        }
+    }
+
+    impl TimerStruct {
+        /// Creates a timer in a const context, where an invalid duration is a compile error
+        /// instead of an `Err`. `timer!` wraps it in a `const` block.
+        ///
+        /// # Panics
+        ///
+        /// If the duration is 0 or doesn't fit in a `u32` number of seconds. In a `const`
+        /// item or block that panic fails the build.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::TimerStruct;
+        ///
+        /// const TEA: TimerStruct = TimerStruct::const_new(0, 4, 0);
+        /// assert_eq!(TEA.duration, 240);
+        /// ```
+        ///
+        /// ```compile_fail
+        /// use clock_timer::timer::TimerStruct;
+        ///
+        /// const NOTHING: TimerStruct = TimerStruct::const_new(0, 0, 0);
+        /// ```
+        pub const fn const_new(hours: u32, minutes: u32, seconds: u32) -> TimerStruct {
+            let duration = match engine::hms_secs(hours, minutes, seconds) {
+                0 => panic!("Duration need to be 1 or more seconds."),
+                duration if duration <= u32::MAX as u64 => duration as u32,
+                _ => panic!("Duration doesn't fit in u32 seconds."),
+            };
+
+            TimerStruct {
+                duration,
+                hours,
+                minutes,
+                seconds,
+                #[cfg(feature = "notify")]
+                notification: None,
+                #[cfg(feature = "sound")]
+                chime: None,
+                on_finish_exec: None,
+                checkpoints: Vec::new(),
+                low_power: None,
+                color_zones: None,
+                theme: Theme::DEFAULT,
+                color: ColorChoice::Auto,
+                easing: Easing::Linear,
+                overtime: None,
+                auto_extend: None,
+                announcements: Vec::new(),
+                output: OutputMode::Auto,
+                status_file: None,
+                #[cfg(unix)]
+                control_socket: None,
+                #[cfg(unix)]
+                owner: None,
+                progress: None,
+                output_buffer: None,
+                clock: None,
+                tick_interval: Duration::from_secs(1),
+                refresh_interval: Duration::from_secs(1),
+                on_resume: OnResume::Continue,
+                on_callback_panic: PanicPolicy::Propagate,
+                #[cfg(feature = "locale")]
+                locale: None,
+            }
+        }
+
+        /// Creates a timer from days, hours, minutes and seconds, for countdowns that span
+        /// days. The days are kept as part of the `hours` component.
+        ///
+        /// # Returns
+        ///
+        /// * `Ok(TimerStruct)` if the total duration is greater than 0.
+        /// * `Err("Duration need to be 1 or more seconds.")` if the total duration is 0.
+        /// * `Err("Duration doesn't fit in u32 seconds.")` if it is too long.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{output::Tick, timer::TimerStruct};
+        ///
+        /// let launch = TimerStruct::from_dhms(3, 4, 12, 9).unwrap();
+        /// assert_eq!(launch.hours, 76);
+        ///
+        /// let tick = Tick {
+        ///     remaining_secs: launch.duration.into(),
+        ///     total_secs: launch.duration.into(),
+        ///     overtime_secs: 0,
+        ///     last: false,
+        /// };
+        /// assert_eq!(tick.formatted(), "3d 04:12:09");
+        /// ```
+        pub fn from_dhms(
+            days: u32,
+            hours: u32,
+            minutes: u32,
+            seconds: u32,
+        ) -> Result<TimerStruct, &'static str> {
+            let hours = days
+                .checked_mul(24)
+                .and_then(|day_hours| day_hours.checked_add(hours))
+                .ok_or("Duration doesn't fit in u32 seconds.")?;
+            TimerStruct::new(hours, minutes, seconds)
+        }
+
+        /// The full duration the timer counts down from.
+        pub fn total(&self) -> Duration {
+            Duration::from_secs(self.duration.into())
+        }
+
+        /// Runs the countdown on a background thread instead of blocking the caller.
+        ///
+        /// The thread renders to `writer` exactly like `start_timer`. The returned
+        /// `TimerController` pauses, resumes, cancels and queries it from any thread,
+        /// and joining the handle yields how the run ended.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerOutcome, TimerStruct, TimerTrait};
+        /// use std::{io::sink, thread, time::Duration};
+        ///
+        /// let timer = TimerStruct::new(0, 1, 0).unwrap();
+        /// let (handle, controller) = timer.spawn(sink());
+        ///
+        /// controller.pause();
+        /// assert!(controller.is_paused());
+        /// thread::sleep(Duration::from_millis(50)); // the main thread keeps working
+        /// controller.cancel();
+        ///
+        /// let outcome = handle.join().unwrap().unwrap();
+        /// assert_eq!(outcome, TimerOutcome::Cancelled { remaining: Duration::from_secs(60) });
+        /// ```
+        pub fn spawn<W: Write + Send + 'static>(
+            &self,
+            mut writer: W,
+        ) -> (JoinHandle<TimerResult>, TimerController) {
+            let controller = TimerController::new(self.duration);
+            let timer = self.clone();
+            let thread_controller = controller.clone();
+            let handle = thread::spawn(move || timer.run(&mut writer, &thread_controller));
+            (handle, controller)
+        }
+
+        /// Spawns `count` copies of this timer labelled `0` to `count - 1`, each on its own
+        /// thread and without rendering, e.g. to give every worker of a job the same budget.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerOutcome, TimerStruct, TimerTrait};
+        ///
+        /// let budget = TimerStruct::new(0, 10, 0).unwrap();
+        /// let batch = budget.spawn_batch(4);
+        /// assert_eq!(batch.len(), 4);
+        ///
+        /// // Workers cancel their own timer when they finish in time.
+        /// for (_, controller) in batch.controllers() {
+        ///     controller.cancel();
+        /// }
+        /// for (label, result) in batch.join() {
+        ///     assert!(matches!(result.unwrap(), TimerOutcome::Cancelled { .. }), "{label}");
+        /// }
+        /// ```
+        pub fn spawn_batch(&self, count: usize) -> TimerBatch {
+            self.spawn_each(0..count)
+        }
+
+        /// Spawns one copy of this timer per item of `items`, labelled with the item, each on
+        /// its own thread and without rendering.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::timer::{TimerStruct, TimerTrait};
+        ///
+        /// let budget = TimerStruct::new(0, 1, 0).unwrap();
+        /// let batch = budget.spawn_each(["eu-west", "us-east"]);
+        /// let labels: Vec<&str> = batch.controllers().map(|(label, _)| label).collect();
+        /// assert_eq!(labels, ["eu-west", "us-east"]);
+        /// batch.cancel_all();
+        /// ```
+        pub fn spawn_each<I>(&self, items: I) -> TimerBatch
+        where
+            I: IntoIterator,
+            I::Item: fmt::Display,
+        {
+            let timers = items
+                .into_iter()
+                .map(|item| {
+                    let (handle, controller) = self.spawn(io::sink());
+                    (item.to_string(), controller, handle)
+                })
+                .collect();
+            TimerBatch { timers }
+        }
+
+        /// How long the timer has run past its target in the current or last run.
+        ///
+        /// Always zero unless overtime was enabled with `TimerBuilder::overtime`.
+        pub fn overtime_elapsed(&self) -> Duration {
+            self.overtime.as_ref().map_or(Duration::ZERO, |overtime| {
+                Duration::from_secs(overtime.elapsed.load(Ordering::SeqCst).into())
+            })
+        }
+
+        /// Runs `start_timer`, sleeping through `controller` so it can be paused and cancelled.
+        pub(crate) fn run<W: Write>(
+            &self,
+            writer: &mut W,
+            controller: &TimerController,
+        ) -> TimerResult {
+            match self.output_buffer {
+                Some(capacity) => output::through_output_thread(writer, capacity, |queued| {
+                    self.count_down(queued, controller)
+                }),
+                None => self.count_down(writer, controller),
+            }
+        }
+
+        /// The tick loop behind `run`, rendering to `writer` directly.
+        fn count_down<W: Write>(
+            &self,
+            writer: &mut W,
+            controller: &TimerController,
+        ) -> TimerResult {
+            let started = Instant::now();
+            let mut remaining = Duration::from_secs(self.duration.into());
+            // Grows and shrinks with `add_time` and `subtract_time` so elapsed time is kept.
+            let mut total = remaining;
+            let mut next_render = Duration::ZERO;
+            let overtime_limit = self.overtime.as_ref().map_or(0, |overtime| {
+                overtime.elapsed.store(0, Ordering::SeqCst);
+                u32::try_from(overtime.limit.as_secs()).unwrap_or(u32::MAX)
+            });
+            // Unbinds the socket when the run ends, however it ends.
+            #[cfg(unix)]
+            let _control = match &self.control_socket {
+                Some(path) => Some(ControlServer::bind(path, controller.clone())?),
+                None => None,
+            };
+            #[cfg(unix)]
+            let _owner = match self.owner {
+                Some(pid) => Some(OwnerWatch::start(pid, controller.clone())?),
+                None => None,
+            };
+
+            // Checkpoints still waiting to fire, latest mark first.
+            let mut pending_checkpoints: Vec<&Checkpoint> = self
+                .checkpoints
+                .iter()
+                .filter(|checkpoint| checkpoint.remaining < total)
+                .collect();
+            pending_checkpoints.sort_by_key(|checkpoint| Reverse(checkpoint.remaining));
+            let mut previous_elapsed = Duration::ZERO;
+
+            loop {
+                let awake = Instant::now();
+                if remaining.is_zero()
+                    && let Some(extension) = self.extend(controller)?
+                {
+                    remaining = extension;
+                    total += extension;
+                    controller.set_remaining(remaining);
+                }
+                let elapsed = total - remaining;
+                if elapsed >= next_render || remaining.is_zero() {
+                    self.render(
+                        writer,
+                        &Tick {
+                            remaining_secs: ceil_secs(remaining),
+                            total_secs: total.as_secs(),
+                            overtime_secs: 0,
+                            // Without overtime, reaching 0 is the final display.
+                            last: remaining.is_zero() && overtime_limit == 0,
+                        },
+                    )?;
+                    next_render = next_boundary(elapsed, self.refresh_interval);
+                }
+
+                let due = pending_checkpoints
+                    .partition_point(|checkpoint| remaining <= checkpoint.remaining);
+                for checkpoint in pending_checkpoints.drain(..due) {
+                    self.guard("checkpoint", || checkpoint.fire(remaining))?;
+                }
+
+                for announcement in &self.announcements {
+                    self.guard("announcement", || {
+                        announcement.announce(previous_elapsed, elapsed, remaining)
+                    })?;
+                }
+                previous_elapsed = elapsed;
+                controller.account(awake.elapsed());
+
+                if remaining.is_zero() {
+                    controller.finish();
+
+                    // A missing notification daemon shouldn't turn a finished timer into a failure.
+                    #[cfg(feature = "notify")]
+                    if let Some(notification) = &self.notification {
+                        let _ = notification.show();
+                    }
+
+                    #[cfg(feature = "sound")]
+                    if let Some(chime) = &self.chime {
+                        let _ = if self.output.is_tty() {
+                            chime.play(writer)
+                        } else {
+                            chime.play(&mut io::sink())
+                        };
+                    }
+
+                    let total_secs = u32::try_from(total.as_secs()).unwrap_or(u32::MAX);
+                    if let Some(command) = &self.on_finish_exec {
+                        // Wall-clock time, so paused and added time count towards it.
+                        let elapsed_secs = started.elapsed().as_secs_f64().round() as u32;
+                        run_finish_command(command, self.duration, elapsed_secs)?;
+                    }
+                    self.count_overtime(writer, total_secs, overtime_limit, controller)?;
+                    return Ok(TimerOutcome::Finished);
+                }
+
+                let step = self.tick_step(remaining, elapsed, next_render, &pending_checkpoints);
+                match controller.sleep(step, self.clock.as_deref(), self.on_resume) {
+                    Wake::Cancelled => {
+                        self.end_line(writer)?;
+                        return Ok(TimerOutcome::Cancelled { remaining });
+                    }
+                    Wake::Elapsed => {
+                        remaining -= step;
+                        controller.set_remaining(remaining);
+                    }
+                    Wake::CaughtUp(slept) => {
+                        remaining = remaining.saturating_sub(slept);
+                        controller.set_remaining(remaining);
+                        next_render = Duration::ZERO;
+                    }
+                    Wake::Adjusted(slept) => {
+                        let slept = slept.min(remaining);
+                        remaining = controller.take_adjusted(slept);
+                        total = elapsed + slept + remaining;
+                        // Show the new time at once rather than at the next redraw.
+                        next_render = Duration::ZERO;
+                    }
+                }
+            }
+        }
+
+        /// Counts up from zero for `limit` seconds, rendering `+h:m:s` every second.
+        ///
+        /// Cancelling `controller` ends the overtime early; the timer has still finished.
+        fn count_overtime<W: Write>(
+            &self,
+            writer: &mut W,
+            total_secs: u32,
+            limit: u32,
+            controller: &TimerController,
+        ) -> io::Result<()> {
+            let Some(overtime) = &self.overtime else {
+                return Ok(());
+            };
 
-            if duration == 0 {
-                return Err("Duration need to be 1 or more seconds.");
+            for elapsed in 1..=limit {
+                if controller.sleep(
+                    Duration::from_secs(1),
+                    self.clock.as_deref(),
+                    self.on_resume,
+                ) == Wake::Cancelled
+                {
+                    return self.end_line(writer);
+                }
+                let awake = Instant::now();
+                overtime.elapsed.store(elapsed, Ordering::SeqCst);
+
+                if let (1, Some(callback)) = (elapsed, &overtime.callback) {
+                    self.guard("overtime", || {
+                        (callback.lock().unwrap_or_else(PoisonError::into_inner))(
+                            Duration::from_secs(1),
+                        )
+                    })?;
+                }
+
+                self.render(
+                    writer,
+                    &Tick {
+                        remaining_secs: 0,
+                        total_secs: total_secs.into(),
+                        overtime_secs: elapsed.into(),
+                        last: elapsed == limit,
+                    },
+                )?;
+                controller.account(awake.elapsed());
             }
+            Ok(())
+        }
 
-            Ok(TimerStruct {
-                duration,
-                hours,
-                minutes,
-                seconds,
-            })
+        /// Writes `tick` to `writer` and, if configured, to the status file and progress channel.
+        fn render<W: Write>(&self, writer: &mut W, tick: &Tick) -> io::Result<()> {
+            let zone = self
+                .color_zones
+                .map(|zones| zones.zone(Duration::from_secs(tick.remaining_secs)));
+            let style = TickStyle {
+                zone,
+                theme: self.color.enabled().then_some(&self.theme),
+                easing: self.easing,
+            };
+            #[cfg(feature = "locale")]
+            if let Some(locale) = &self.locale {
+                self.output
+                    .write_localized_styled_tick(writer, tick, style, locale)?;
+            } else {
+                self.output.write_styled_tick(writer, tick, style)?;
+            }
+            #[cfg(not(feature = "locale"))]
+            self.output.write_styled_tick(writer, tick, style)?;
+            if let Some(path) = &self.status_file {
+                output::write_status_file(path, tick)?;
+            }
+            if let Some(progress) = &self.progress {
+                progress.write_tick(tick)?;
+            }
+            Ok(())
         }
 
-        /// Starts the countdown timer.
-        ///
-        /// The timer will print the remaining time to the provided writer every second,
-        /// overwriting the previous line. When the timer reaches 0, it prints the final
-        /// `0:0:0` with a newline and stops.
-        ///
-        /// # Arguments
-        ///
-        /// * `writer` - A mutable reference to any type that implements the `std::io::Write`
-        ///              trait (e.g., `&mut std::io::Stdout`).
-        ///
-        /// # Examples
-        ///
-        /// ```
-        /// use clock-timer::TimerStruct; // Replace your_crate_name with your actual crate name
-        /// use std::io::{self, stdout};
-        ///
-        /// let timer = TimerStruct::new(0, 0, 5).unwrap(); // 5-second timer
-        /// let mut writer = stdout();
-        /// timer.start_timer(&mut writer);
-        /// println!("Timer finished!");
-        /// ```
-        fn start_timer<W: Write>(&self, writer: &mut W) {
-            let mut current_duration = self.duration;
-            let one_second = Duration::from_secs(1);
+        /// Consults the `auto_extend` policy at zero, recording an extension it grants.
+        fn extend(&self, controller: &TimerController) -> io::Result<Option<Duration>> {
+            let Some(policy) = &self.auto_extend else {
+                return Ok(None);
+            };
+            let mut extension = None;
+            self.guard("extension", || {
+                extension = policy.extension(controller.extensions())
+            })?;
+            if extension.is_some() {
+                controller.update(|state| state.extensions += 1);
+            }
+            Ok(extension)
+        }
 
-            loop {
-                // Calculate display components from the current total duration
-                let display_hours = current_duration / 3600;
-                let remaining_seconds_after_hours = current_duration % 3600;
-                let display_minutes = remaining_seconds_after_hours / 60;
-                let display_seconds = remaining_seconds_after_hours % 60;
-
-                let time_display_string =
-                    format!("{}:{}:{}", display_hours, display_minutes, display_seconds);
-
-                if current_duration == 0 {
-                    // If duration is 0, this is the final display. Print with a newline and break.
-                    writeln!(writer, "{}", time_display_string).unwrap();
-                    break;
-                } else {
-                    // For all other durations, print with a carriage return to overwrite the line.
-                    write!(writer, "{}\r", time_display_string).unwrap();
-                    writer.flush().unwrap(); // Ensure the output is flushed immediately
-                }
+        /// Calls the `kind` callback in `call`, handling a panic according to
+        /// `on_callback_panic`.
+        fn guard(&self, kind: &'static str, call: impl FnOnce()) -> io::Result<()> {
+            if self.on_callback_panic == PanicPolicy::Propagate {
+                call();
+                return Ok(());
+            }
+            // Callbacks only see state through their own locks, which the next call
+            // recovers from poisoning.
+            let Err(payload) = panic::catch_unwind(AssertUnwindSafe(call)) else {
+                return Ok(());
+            };
+            if self.on_callback_panic == PanicPolicy::LogAndContinue {
+                return Ok(());
+            }
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => payload.downcast_ref::<&str>().map_or_else(
+                    || String::from("Box<dyn Any>"),
+                    |message| message.to_string(),
+                ),
+            };
+            Err(io::Error::other(CallbackPanicked {
+                callback: kind,
+                message,
+            }))
+        }
+
+        /// Leaves the last rendered time on screen rather than under the prompt when a run ends
+        /// early. Line-based outputs already end every tick with a newline.
+        fn end_line<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+            if self.output.is_tty() {
+                writeln!(writer)?;
+            }
+            Ok(())
+        }
 
-                thread::sleep(one_second);
-                current_duration -= 1;
+        /// Returns how long to sleep before the next tick when `remaining` is left and `elapsed`
+        /// has passed, waking for the redraw due at `next_render` and honouring the low-power
+        /// cadence, the next pending checkpoint and announcements.
+        fn tick_step(
+            &self,
+            remaining: Duration,
+            elapsed: Duration,
+            next_render: Duration,
+            pending_checkpoints: &[&Checkpoint],
+        ) -> Duration {
+            let tick = self.tick_interval;
+            let mut step = match self.low_power {
+                // Far from the end, the low-power cadence replaces the refresh interval.
+                Some(low_power) if remaining > low_power.threshold => low_power
+                    .interval
+                    .max(tick)
+                    .min(remaining - low_power.threshold),
+                _ => tick.min(next_render.saturating_sub(elapsed)),
+            };
+
+            // Wake up in time for the next checkpoint rather than overshooting its mark,
+            // rounding up to a whole tick.
+            if let Some(checkpoint) = pending_checkpoints
+                .first()
+                .filter(|checkpoint| checkpoint.remaining < remaining)
+            {
+                step = step.min((remaining - checkpoint.remaining).max(tick));
+            }
+
+            // Likewise for announcements.
+            for announcement in &self.announcements {
+                if let Some(until_next) = announcement.until_next(elapsed) {
+                    step = step.min(until_next.max(tick));
+                }
             }
+
+            step.min(remaining)
+        }
+    }
+
+    /// Whole seconds in `duration`, rounded up so a countdown shows `0:0:0` only once done.
+    fn ceil_secs(duration: Duration) -> u64 {
+        duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+    }
+
+    /// The first multiple of `every` after `elapsed`.
+    fn next_boundary(elapsed: Duration, every: Duration) -> Duration {
+        let every_nanos = every.as_nanos().max(1);
+        let next = (elapsed.as_nanos() / every_nanos + 1) * every_nanos;
+        Duration::from_nanos(u64::try_from(next).unwrap_or(u64::MAX))
+    }
+
+    /// Runs an `on_finish_exec` command through the platform shell and waits for it to exit.
+    fn run_finish_command(command: &str, target_secs: u32, elapsed_secs: u32) -> io::Result<()> {
+        #[cfg(windows)]
+        let mut shell = {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C").arg(command);
+            shell
+        };
+        #[cfg(not(windows))]
+        let mut shell = {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(command);
+            shell
+        };
+
+        let status = shell
+            .env(TARGET_SECS_ENV, target_secs.to_string())
+            .env(ELAPSED_SECS_ENV, elapsed_secs.to_string())
+            .status()
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to run `{}`: {}", command, e)))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "`{}` exited with {}",
+                command, status
+            )))
         }
     }
 }
 
 /// Re-exports `TimerStruct` from the `timer` module for easier access.
+#[cfg(feature = "std")]
 pub use timer::TimerStruct;
 
+/// Re-exports the `set_timeout`/`set_interval` primitives for easier access.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub use interval::{CancelHandle, set_interval, set_timeout};
+
 /// Module for stopwatch functionalities.
+#[cfg(feature = "std")]
 pub mod stopwatch {
+    use crate::engine::{StopwatchEngine, format_hms};
+    use crate::format;
+    use crate::history::Pause;
+    use crate::measure::{self, Scope};
+    use crate::time_source::Clock;
+    use crate::timer::Announcement;
     #[cfg(not(target_arch = "wasm32"))]
     use ctrlc;
     use std::{
+        fmt,
         io::Write,
         process,
         sync::{
-            Arc,
-            atomic::{AtomicU32, Ordering},
+            Arc, Condvar, Mutex, OnceLock, PoisonError,
+            atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering},
         },
-        thread,
-        time::Duration,
+        thread::{self, JoinHandle},
+        time::{Duration, Instant, SystemTime},
     };
 
-    pub trait StopwatchTrait<T>
-    where
-        T: Fn(u32) + std::marker::Send + Copy + 'static,
-    {
-        fn new(operation_on_stop: T) -> StopwatchStruct<T>;
+    /// The closure run when a stopwatch stops, shared between the stopwatch and its Ctrl-C handler.
+    pub type OperationOnStop = Arc<Mutex<Box<dyn FnMut(Duration, StopReason) + Send>>>;
+
+    /// A predicate checked with the elapsed time on every tick, stopping the stopwatch once
+    /// it returns `true`.
+    pub type StopCondition = Arc<Mutex<Box<dyn FnMut(Duration) -> bool + Send>>>;
+
+    /// A running stopwatch's elapsed seconds and on-stop closure, for the Ctrl-C handler.
+    type Interruptible = (u64, Arc<AtomicU32>, OperationOnStop);
+
+    /// The stopwatches running in this process, all stopped by a single Ctrl-C.
+    static RUNNING: Mutex<Vec<Interruptible>> = Mutex::new(Vec::new());
+
+    /// Tells the entries of `RUNNING` apart.
+    static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+    /// Keeps a stopwatch in `RUNNING` until its loop ends.
+    struct RunningGuard(u64);
+
+    impl RunningGuard {
+        /// Registers a stopwatch, installing the process-wide Ctrl-C handler the first
+        /// time. `ctrlc` allows one handler per process, so the stopwatches share it.
+        fn register(elapsed_secs: Arc<AtomicU32>, on_stop: OperationOnStop) -> RunningGuard {
+            // If the application installed its own handler first, Ctrl-C is left to it.
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                static HANDLER: OnceLock<()> = OnceLock::new();
+                HANDLER.get_or_init(|| {
+                    let _ = ctrlc::set_handler(interrupt);
+                });
+            }
+            let id = NEXT_RUN.fetch_add(1, Ordering::SeqCst);
+            RUNNING
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((id, elapsed_secs, on_stop));
+            RunningGuard(id)
+        }
+    }
+
+    impl Drop for RunningGuard {
+        fn drop(&mut self) {
+            RUNNING
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|(id, _, _)| *id != self.0);
+        }
+    }
+
+    /// Runs on Ctrl-C, and with the `signals` feature on `SIGTERM` or `SIGHUP`: stops every
+    /// running stopwatch with `StopReason::Interrupted`, then exits the process.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn interrupt() {
+        let running = std::mem::take(&mut *RUNNING.lock().unwrap_or_else(PoisonError::into_inner));
+        for (_, elapsed_secs, on_stop) in running {
+            let final_time = elapsed_secs.load(Ordering::SeqCst);
+            // Redraw the final time with a newline so the shell prompt doesn't overwrite it.
+            println!("\r{}", format_hms(final_time.into()));
+            (on_stop.lock().unwrap_or_else(PoisonError::into_inner))(
+                Duration::from_secs(final_time.into()),
+                StopReason::Interrupted,
+            );
+        }
+        process::exit(0);
+    }
+
+    pub trait StopwatchTrait: Sized {
+        fn new<F>(operation_on_stop: F) -> Self
+        where
+            F: FnMut(Duration, StopReason) + Send + 'static;
         fn start_stopwatch<W: Write>(&mut self, writer: &mut W);
     }
 
     /// Represents the current status of the stopwatch.
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub enum StopwatchStatus {
         /// The stopwatch is currently stopped.
         Stopped,
         /// The stopwatch is currently running.
         Running,
+        /// The stopwatch is paused; the loop keeps waiting but time doesn't accumulate.
+        Paused,
+    }
+
+    impl StopwatchStatus {
+        /// Reverses `status as u8`, for the lock-free copy read by `snapshot`.
+        fn from_code(code: u8) -> StopwatchStatus {
+            match code {
+                0 => StopwatchStatus::Stopped,
+                1 => StopwatchStatus::Running,
+                _ => StopwatchStatus::Paused,
+            }
+        }
+    }
+
+    /// A view of a running stopwatch, from `StopwatchStruct::snapshot`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct StopwatchSnapshot {
+        /// The elapsed time as of the last tick.
+        pub elapsed: Duration,
+        /// The current status.
+        pub status: StopwatchStatus,
+        /// The number of laps recorded with `lap`.
+        pub lap_count: u32,
+        /// The number of times the stopwatch has been paused.
+        pub pause_count: u32,
+        /// The combined length of the completed pauses, not counting one in progress.
+        pub paused: Duration,
+    }
+
+    /// Why a stopwatch stopped, passed to its `operation_on_stop` closure.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum StopReason {
+        /// `stop` was called on the stopwatch or one of its clones.
+        Manual,
+        /// Ctrl-C, or with the `signals` feature a `SIGTERM` or `SIGHUP`, was received.
+        Interrupted,
+        /// The elapsed time reached the limit set with `max_duration`.
+        MaxDuration,
+        /// The predicate set with `stop_when` returned `true`.
+        Condition,
+        /// The wall-clock deadline set with `stop_at` passed.
+        Deadline,
     }
 
     /// Represents a stopwatch that measures elapsed time.
     ///
-    /// It takes a generic type `T` which must be a closure that accepts a `u32`
-    /// (the final `current_time` when the stopwatch stops).
-    #[derive(Debug, Clone)]
-    pub struct StopwatchStruct<T>
-    where
-        T: Fn(u32) + std::marker::Send + Copy + 'static,
-    {
+    /// The closure run on stop is boxed internally, so it can capture owned state
+    /// (`String`s, channel senders, ...) and mutate it.
+    ///
+    /// Clones share their status, so `stop` on any clone, from any thread, ends a
+    /// running `start_stopwatch` loop.
+    #[derive(Clone)]
+    pub struct StopwatchStruct {
         /// The current elapsed time in seconds.
         pub current_time: u32,
-        /// The current status of the stopwatch (Running or Stopped).
-        pub status: StopwatchStatus,
+        /// The status shared by every clone, with a condvar to wake the running loop.
+        status: Arc<(Mutex<StopwatchStatus>, Condvar)>,
+        /// The elapsed seconds as of the last tick, shared by every clone.
+        elapsed_secs: Arc<AtomicU32>,
+        /// A copy of the status that `snapshot` reads without taking the lock.
+        status_code: Arc<AtomicU8>,
+        /// The elapsed time at each lap, shared by every clone.
+        laps: Arc<Mutex<Vec<Duration>>>,
+        /// The number of laps, readable without taking the lock.
+        lap_count: Arc<AtomicU32>,
+        /// Every pause, shared by every clone.
+        pauses: Arc<Mutex<Vec<Pause>>>,
+        /// The number of pauses, readable without taking the lock.
+        pause_count: Arc<AtomicU32>,
+        /// The combined length of the completed pauses in milliseconds, readable without
+        /// taking the lock.
+        paused_ms: Arc<AtomicU64>,
         /// A closure that will be executed when the stopwatch is stopped.
-        /// It receives the final `current_time` as an argument.
-        pub operation_on_stop: T,
+        /// It receives the final elapsed time as an argument.
+        pub operation_on_stop: OperationOnStop,
+        /// Callbacks fired at fixed intervals with the elapsed time.
+        pub announcements: Vec<Announcement>,
+        /// The clock driving the stopwatch instead of real time, if any.
+        pub clock: Option<Arc<dyn Clock>>,
+        /// The elapsed time at which the stopwatch stops by itself, if any.
+        pub max_duration: Option<Duration>,
+        /// The wall-clock time at which the stopwatch stops by itself, if any.
+        pub deadline: Option<SystemTime>,
+        /// A predicate checked on every tick that stops the stopwatch once it returns `true`.
+        pub stop_condition: Option<StopCondition>,
+    }
+
+    impl fmt::Debug for StopwatchStruct {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("StopwatchStruct")
+                .field("current_time", &self.current_time)
+                .field("status", &self.status())
+                .field("announcements", &self.announcements)
+                .field("clock", &self.clock)
+                .field("max_duration", &self.max_duration)
+                .field("deadline", &self.deadline)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl fmt::Display for StopwatchStruct {
+        /// Formats the elapsed time as zero-padded `HH:MM:SS`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        ///
+        /// let stopwatch = StopwatchStruct::new(|_, _| {});
+        /// assert_eq!(stopwatch.to_string(), "00:00:00");
+        /// ```
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            format::format_into_fmt(self.elapsed().as_secs(), f).map(|_| ())
+        }
     }
 
-    impl<T> StopwatchStruct<T>
-    where
-        T: Fn(u32) + std::marker::Send + Copy + 'static,
-    {
+    impl StopwatchStruct {
         /// Creates a new `StopwatchStruct` instance.
         ///
         /// # Arguments
         ///
-        /// * `operation_on_stop` - A closure that will be called when the stopwatch status
-        ///   is set to `Stopped`. It receives the total elapsed time in seconds as its argument.
+        /// * `operation_on_stop` - A closure that will be called when the stopwatch is
+        ///   stopped. It receives the total elapsed time and the `StopReason` as its arguments.
         ///
         /// # Returns
         ///
-        /// A new `StopwatchStruct` initialized with `current_time` at 0 and `status` as `Running`.
+        /// A new `StopwatchStruct` initialized with `current_time` at 0 and its status as `Running`.
         ///
         /// # Examples
         ///
         /// ```
-        /// use clock-timer::stopwatch::{StopwatchStruct, StopwatchStatus}; // Replace clock-timer
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        /// use std::sync::mpsc;
         ///
-        /// let mut stopwatch = StopwatchStruct::new(|time| {
-        ///     println!("Stopwatch stopped at {} seconds.", time);
+        /// let (sender, receiver) = mpsc::channel();
+        /// let label = String::from("Stopwatch");
+        /// let stopwatch = StopwatchStruct::new(move |time, _reason| {
+        ///     println!("{} stopped at {} seconds.", label, time.as_secs());
+        ///     let _ = sender.send(time);
         /// });
         /// ```
-        pub fn new(operation_on_stop: T) -> StopwatchStruct<T> {
+        pub fn new<F>(operation_on_stop: F) -> StopwatchStruct
+        where
+            F: FnMut(Duration, StopReason) + Send + 'static,
+        {
             StopwatchStruct {
                 current_time: 0,
-                status: StopwatchStatus::Running,
-                operation_on_stop,
+                status: Arc::new((Mutex::new(StopwatchStatus::Running), Condvar::new())),
+                elapsed_secs: Arc::new(AtomicU32::new(0)),
+                status_code: Arc::new(AtomicU8::new(StopwatchStatus::Running as u8)),
+                laps: Arc::new(Mutex::new(Vec::new())),
+                lap_count: Arc::new(AtomicU32::new(0)),
+                pauses: Arc::new(Mutex::new(Vec::new())),
+                pause_count: Arc::new(AtomicU32::new(0)),
+                paused_ms: Arc::new(AtomicU64::new(0)),
+                operation_on_stop: Arc::new(Mutex::new(Box::new(operation_on_stop))),
+                announcements: Vec::new(),
+                clock: None,
+                max_duration: None,
+                deadline: None,
+                stop_condition: None,
+            }
+        }
+
+        /// Returns the current status, as seen by every clone of this stopwatch.
+        pub fn status(&self) -> StopwatchStatus {
+            *self.status.0.lock().unwrap()
+        }
+
+        /// Sets the status of this stopwatch and all of its clones.
+        ///
+        /// Setting `StopwatchStatus::Stopped` wakes a running `start_stopwatch` loop
+        /// immediately rather than at its next tick.
+        pub fn set_status(&self, status: StopwatchStatus) {
+            let (shared, condvar) = &*self.status;
+            let mut shared = shared.lock().unwrap();
+            let previous = std::mem::replace(&mut *shared, status);
+            self.record_pause(previous, status);
+            self.status_code.store(status as u8, Ordering::SeqCst);
+            condvar.notify_all();
+        }
+
+        /// Stops the stopwatch, ending a `start_stopwatch` loop running on any clone.
+        ///
+        /// This is shorthand for `set_status(StopwatchStatus::Stopped)`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::{StopwatchStatus, StopwatchStruct};
+        /// use std::{io::sink, sync::mpsc, thread, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let mut stopwatch = StopwatchStruct::new(move |time, _| sender.send(time).unwrap());
+        ///
+        /// let handle = stopwatch.clone();
+        /// thread::spawn(move || {
+        ///     thread::sleep(Duration::from_millis(50));
+        ///     handle.stop();
+        /// });
+        ///
+        /// // Returns once the other thread stops the clone.
+        /// stopwatch.start_stopwatch(&mut sink());
+        /// assert_eq!(stopwatch.status(), StopwatchStatus::Stopped);
+        /// assert!(receiver.recv().unwrap() < Duration::from_secs(1));
+        /// ```
+        pub fn stop(&self) {
+            self.set_status(StopwatchStatus::Stopped);
+        }
+
+        /// Pauses a running stopwatch. Does nothing once it has been stopped.
+        pub fn pause(&self) {
+            self.transition(StopwatchStatus::Running, StopwatchStatus::Paused);
+        }
+
+        /// Resumes a paused stopwatch. Does nothing once it has been stopped.
+        pub fn resume(&self) {
+            self.transition(StopwatchStatus::Paused, StopwatchStatus::Running);
+        }
+
+        /// The elapsed time as of the last tick of the loop running on any clone.
+        pub fn elapsed(&self) -> Duration {
+            Duration::from_secs(self.elapsed_secs.load(Ordering::SeqCst).into())
+        }
+
+        /// Records a lap at the elapsed time as of the last tick and returns that time.
+        pub fn lap(&self) -> Duration {
+            let elapsed = self.elapsed();
+            let mut laps = self.laps.lock().unwrap();
+            laps.push(elapsed);
+            self.lap_count.store(
+                u32::try_from(laps.len()).unwrap_or(u32::MAX),
+                Ordering::SeqCst,
+            );
+            elapsed
+        }
+
+        /// The elapsed time at every lap recorded with `lap`, on any clone.
+        pub fn laps(&self) -> Vec<Duration> {
+            self.laps.lock().unwrap().clone()
+        }
+
+        /// Every interval the stopwatch spent paused, with wall-clock timestamps, oldest
+        /// first. Stopping a paused stopwatch ends its last pause.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        /// use std::{thread, time::Duration};
+        ///
+        /// let stopwatch = StopwatchStruct::new(|_, _| {});
+        /// stopwatch.pause();
+        /// thread::sleep(Duration::from_millis(20));
+        /// stopwatch.resume();
+        /// stopwatch.pause();
+        ///
+        /// let pauses = stopwatch.pauses();
+        /// assert_eq!(pauses.len(), 2);
+        /// assert!(pauses[0].duration().unwrap() >= Duration::from_millis(20));
+        /// assert_eq!(pauses[1].ended, None); // still paused
+        ///
+        /// stopwatch.stop();
+        /// assert!(stopwatch.pauses()[1].ended.is_some());
+        /// assert!(stopwatch.paused_time() >= Duration::from_millis(20));
+        /// assert_eq!(stopwatch.snapshot().pause_count, 2);
+        /// ```
+        pub fn pauses(&self) -> Vec<Pause> {
+            self.pauses.lock().unwrap().clone()
+        }
+
+        /// The combined time spent paused, including a pause still in progress.
+        pub fn paused_time(&self) -> Duration {
+            let now = SystemTime::now();
+            self.pauses
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|pause| {
+                    pause
+                        .duration()
+                        .unwrap_or_else(|| now.duration_since(pause.started).unwrap_or_default())
+                })
+                .sum()
+        }
+
+        /// The elapsed time, status, lap count and pause totals, read from atomics without
+        /// taking any lock, so monitoring threads can poll it at any rate while the loop runs.
+        ///
+        /// Each field is read separately, so a snapshot taken during a tick may pair the
+        /// new status with the previous elapsed time.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::{StopwatchStatus, StopwatchStruct};
+        /// use std::{io::sink, time::Duration};
+        ///
+        /// let (handle, controller) = StopwatchStruct::new(|_, _| {}).spawn(sink());
+        /// controller.lap();
+        /// controller.pause();
+        ///
+        /// let snapshot = controller.snapshot();
+        /// assert_eq!(snapshot.status, StopwatchStatus::Paused);
+        /// assert_eq!(snapshot.lap_count, 1);
+        /// assert!(snapshot.elapsed < Duration::from_secs(1));
+        ///
+        /// controller.stop();
+        /// handle.join().unwrap();
+        /// ```
+        pub fn snapshot(&self) -> StopwatchSnapshot {
+            StopwatchSnapshot {
+                elapsed: self.elapsed(),
+                status: StopwatchStatus::from_code(self.status_code.load(Ordering::SeqCst)),
+                lap_count: self.lap_count.load(Ordering::SeqCst),
+                pause_count: self.pause_count.load(Ordering::SeqCst),
+                paused: Duration::from_millis(self.paused_ms.load(Ordering::SeqCst)),
+            }
+        }
+
+        /// Runs `start_stopwatch` on a background thread instead of blocking the caller.
+        ///
+        /// The returned clone controls the running stopwatch from any thread through
+        /// `pause`, `resume`, `stop` and `elapsed`. Joining the handle yields the final
+        /// elapsed time once the stopwatch is stopped.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::{StopwatchStatus, StopwatchStruct};
+        /// use std::{io::sink, thread, time::Duration};
+        ///
+        /// let (handle, controller) = StopwatchStruct::new(|_, _| {}).spawn(sink());
+        ///
+        /// controller.pause();
+        /// assert_eq!(controller.status(), StopwatchStatus::Paused);
+        /// thread::sleep(Duration::from_millis(50)); // the main thread keeps working
+        /// controller.stop();
+        ///
+        /// assert!(handle.join().unwrap() < Duration::from_secs(1));
+        ///
+        /// // Several stopwatches can run side by side.
+        /// let (first, first_controller) = StopwatchStruct::new(|_, _| {}).spawn(sink());
+        /// let (second, second_controller) = StopwatchStruct::new(|_, _| {}).spawn(sink());
+        /// first_controller.stop();
+        /// second_controller.stop();
+        /// assert!(first.join().is_ok() && second.join().is_ok());
+        /// ```
+        pub fn spawn<W: Write + Send + 'static>(
+            mut self,
+            mut writer: W,
+        ) -> (JoinHandle<Duration>, StopwatchStruct) {
+            let controller = self.clone();
+            let handle = thread::spawn(move || {
+                self.start_stopwatch(&mut writer);
+                Duration::from_secs(self.current_time.into())
+            });
+            (handle, controller)
+        }
+
+        /// Runs `operation` and returns its result together with how long it took.
+        ///
+        /// This is `measure::time`, reachable from the stopwatch for discoverability.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        ///
+        /// let (result, elapsed) = StopwatchStruct::time(|| "expensive work");
+        /// assert_eq!(result, "expensive work");
+        /// assert!(elapsed.as_secs() < 1);
+        /// ```
+        pub fn time<T, F>(operation: F) -> (T, Duration)
+        where
+            F: FnOnce() -> T,
+        {
+            measure::time(operation)
+        }
+
+        /// Starts a `measure::Scope` that reports its elapsed time under `label` when dropped.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        ///
+        /// let _timing = StopwatchStruct::scope("db query"); // prints "db query: ..." on drop
+        /// ```
+        pub fn scope(label: impl Into<String>) -> Scope {
+            Scope::new(label)
+        }
+
+        /// Moves the shared status from `from` to `to`, leaving any other status untouched.
+        fn transition(&self, from: StopwatchStatus, to: StopwatchStatus) {
+            let (status, condvar) = &*self.status;
+            let mut status = status.lock().unwrap();
+            if *status == from {
+                *status = to;
+                self.record_pause(from, to);
+                self.status_code.store(to as u8, Ordering::SeqCst);
+                condvar.notify_all();
+            }
+        }
+
+        /// Opens or closes a pause in the ledger as the status moves from `from` to `to`.
+        /// Called with the status lock held, so pauses are recorded in order.
+        fn record_pause(&self, from: StopwatchStatus, to: StopwatchStatus) {
+            let paused = StopwatchStatus::Paused;
+            if (from == paused) == (to == paused) {
+                return;
+            }
+            let now = SystemTime::now();
+            let mut pauses = self.pauses.lock().unwrap();
+            if to == paused {
+                pauses.push(Pause {
+                    started: now,
+                    ended: None,
+                });
+                self.pause_count.store(
+                    u32::try_from(pauses.len()).unwrap_or(u32::MAX),
+                    Ordering::SeqCst,
+                );
+            } else if let Some(pause) = pauses.last_mut() {
+                pause.ended = Some(now);
+                let millis = pause.duration().unwrap_or_default().as_millis();
+                self.paused_ms
+                    .fetch_add(u64::try_from(millis).unwrap_or(u64::MAX), Ordering::SeqCst);
+            }
+        }
+
+        /// Calls `callback` with the elapsed time every `every`, e.g. to speak or log
+        /// progress once a minute.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::StopwatchStruct;
+        /// use std::time::Duration;
+        ///
+        /// let stopwatch = StopwatchStruct::new(|_, _| {})
+        ///     .announce_every(Duration::from_secs(60), |elapsed| {
+        ///         println!("{} minutes in", elapsed.as_secs() / 60)
+        ///     });
+        /// assert_eq!(stopwatch.announcements.len(), 1);
+        /// ```
+        pub fn announce_every<F>(mut self, every: Duration, callback: F) -> StopwatchStruct
+        where
+            F: FnMut(Duration) + Send + 'static,
+        {
+            self.announcements.push(Announcement::new(every, callback));
+            self
+        }
+
+        /// Drives the stopwatch from `clock` instead of real time.
+        ///
+        /// A `stop` or `pause` from another thread is seen once the current one second
+        /// sleep on `clock` returns.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     stopwatch::{StopwatchStruct, StopwatchTrait},
+        ///     time_source::VirtualClock,
+        /// };
+        /// use std::{
+        ///     sync::{Arc, Mutex, mpsc},
+        ///     time::Duration,
+        /// };
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let handle = Arc::new(Mutex::new(None::<StopwatchStruct>));
+        /// let stopper = Arc::clone(&handle);
+        /// let mut stopwatch = StopwatchStruct::new(move |elapsed, _| sender.send(elapsed).unwrap())
+        ///     .with_clock(VirtualClock::new())
+        ///     // An hour of simulated time passes in an instant.
+        ///     .announce_every(Duration::from_secs(3600), move |_| {
+        ///         stopper.lock().unwrap().as_ref().unwrap().stop();
+        ///     });
+        /// *handle.lock().unwrap() = Some(stopwatch.clone());
+        ///
+        /// stopwatch.start_stopwatch(&mut std::io::sink());
+        /// assert_eq!(receiver.recv().unwrap(), Duration::from_secs(3600));
+        /// ```
+        pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> StopwatchStruct {
+            self.clock = Some(Arc::new(clock));
+            self
+        }
+
+        /// Stops the stopwatch once its elapsed time reaches `limit`, reporting
+        /// `StopReason::MaxDuration`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     stopwatch::{StopReason, StopwatchStruct},
+        ///     time_source::VirtualClock,
+        /// };
+        /// use std::{io::sink, sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let mut stopwatch = StopwatchStruct::new(move |elapsed, reason| {
+        ///     sender.send((elapsed, reason)).unwrap()
+        /// })
+        /// .with_clock(VirtualClock::new())
+        /// .max_duration(Duration::from_secs(90));
+        ///
+        /// stopwatch.start_stopwatch(&mut sink());
+        /// assert_eq!(
+        ///     receiver.recv().unwrap(),
+        ///     (Duration::from_secs(90), StopReason::MaxDuration)
+        /// );
+        /// ```
+        pub fn max_duration(mut self, limit: Duration) -> StopwatchStruct {
+            self.max_duration = Some(limit);
+            self
+        }
+
+        /// Stops the stopwatch on the first tick where `condition` returns `true` for the
+        /// elapsed time, reporting `StopReason::Condition`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::{
+        ///     stopwatch::{StopReason, StopwatchStruct},
+        ///     time_source::VirtualClock,
+        /// };
+        /// use std::{io::sink, sync::mpsc, time::Duration};
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let mut stopwatch = StopwatchStruct::new(move |elapsed, reason| {
+        ///     sender.send((elapsed, reason)).unwrap()
+        /// })
+        /// .with_clock(VirtualClock::new())
+        /// .stop_when(|elapsed| elapsed.as_secs() == 42);
+        ///
+        /// stopwatch.start_stopwatch(&mut sink());
+        /// assert_eq!(
+        ///     receiver.recv().unwrap(),
+        ///     (Duration::from_secs(42), StopReason::Condition)
+        /// );
+        /// ```
+        pub fn stop_when<F>(mut self, condition: F) -> StopwatchStruct
+        where
+            F: FnMut(Duration) -> bool + Send + 'static,
+        {
+            self.stop_condition = Some(Arc::new(Mutex::new(Box::new(condition))));
+            self
+        }
+
+        /// Stops the stopwatch once the wall clock reaches `deadline`, reporting
+        /// `StopReason::Deadline`. The deadline also passes while the stopwatch is paused.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use clock_timer::stopwatch::{StopReason, StopwatchStruct};
+        /// use std::{
+        ///     io::sink,
+        ///     sync::mpsc,
+        ///     time::{Duration, SystemTime},
+        /// };
+        ///
+        /// let (sender, receiver) = mpsc::channel();
+        /// let mut stopwatch = StopwatchStruct::new(move |_, reason| sender.send(reason).unwrap())
+        ///     .stop_at(SystemTime::now() + Duration::from_millis(50));
+        ///
+        /// stopwatch.start_stopwatch(&mut sink());
+        /// assert_eq!(receiver.recv().unwrap(), StopReason::Deadline);
+        /// ```
+        pub fn stop_at(mut self, deadline: SystemTime) -> StopwatchStruct {
+            self.deadline = Some(deadline);
+            self
+        }
+
+        /// How long until the deadline, `None` without one.
+        fn until_deadline(&self) -> Option<Duration> {
+            self.deadline.map(|deadline| {
+                deadline
+                    .duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO)
+            })
+        }
+
+        /// The condition that stops the stopwatch at `elapsed`, if any.
+        fn auto_stop(&self, elapsed: Duration) -> Option<StopReason> {
+            if self.max_duration.is_some_and(|limit| elapsed >= limit) {
+                return Some(StopReason::MaxDuration);
+            }
+            if self.until_deadline() == Some(Duration::ZERO) {
+                return Some(StopReason::Deadline);
+            }
+            match &self.stop_condition {
+                Some(condition)
+                    if (condition.lock().unwrap_or_else(PoisonError::into_inner))(elapsed) =>
+                {
+                    Some(StopReason::Condition)
+                }
+                _ => None,
             }
         }
 
+        /// How long to wait before the next tick at `elapsed`: a second, or less if a
+        /// limit or deadline falls sooner.
+        fn next_tick(&self, elapsed: Duration) -> Duration {
+            let mut wait = Duration::from_secs(1);
+            if let Some(limit) = self.max_duration {
+                wait = wait.min(limit.saturating_sub(elapsed));
+            }
+            if let Some(until_deadline) = self.until_deadline() {
+                wait = wait.min(until_deadline);
+            }
+            wait
+        }
+
         /// Starts the stopwatch.
         ///
         /// The stopwatch will increment its `current_time` every second and print the elapsed time
         /// to the provided writer, overwriting the previous line.
         ///
-        /// The timer can be stopped in two ways:
-        /// 1.  Pressing `Ctrl+C`. This will execute the `operation_on_stop` closure and exit the process.
-        /// 2.  Programmatically by setting the `status` field to `StopwatchStatus::Stopped`. This will
-        ///     stop the loop and execute the `operation_on_stop` closure.
+        /// The timer can be stopped in three ways:
+        /// 1.  Pressing `Ctrl+C`, or on Unix with the default `signals` feature a `SIGTERM` or
+        ///     `SIGHUP`. This will print the final time, execute the `operation_on_stop` closure
+        ///     and exit the process.
+        /// 2.  Programmatically by calling `stop` on this stopwatch or any clone of it, from any
+        ///     thread. This will stop the loop and execute the `operation_on_stop` closure.
+        /// 3.  Automatically, once a condition set with `max_duration`, `stop_when` or `stop_at`
+        ///     is met. This also stops every clone and executes the `operation_on_stop` closure.
         ///
         /// # Arguments
         ///
         /// * `writer` - A mutable reference to any type that implements the `std::io::Write`
-        ///              trait (e.g., `&mut std::io::Stdout`).
+        ///   trait (e.g., `&mut std::io::Stdout`).
         ///
         /// # Examples
         ///
         /// ```no_run
-        /// use your_crate_name::stopwatch::{StopwatchStruct, StopwatchStatus}; // Replace your_crate_name
+        /// use clock_timer::stopwatch::StopwatchStruct;
         /// use std::{io::stdout, thread, time::Duration};
         ///
         /// // This stopwatch will be stopped by another thread after 5 seconds.
-        /// let mut stopwatch = StopwatchStruct::new(|time| {
-        ///     println!("\nStopwatch finished at {} seconds!", time);
+        /// let mut stopwatch = StopwatchStruct::new(|time, _| {
+        ///     println!("\nStopwatch finished at {} seconds!", time.as_secs());
         /// });
         ///
-        /// let mut stopwatch_clone = stopwatch.clone();
+        /// let stopwatch_clone = stopwatch.clone();
         /// thread::spawn(move || {
         ///     thread::sleep(Duration::from_secs(5));
-        ///     stopwatch_clone.status = StopwatchStatus::Stopped;
+        ///     stopwatch_clone.stop();
         /// });
         ///
-        /// stopwatch.start_timer(&mut stdout());
+        /// stopwatch.start_stopwatch(&mut stdout());
         /// println!("Stopwatch loop ended.");
         /// ```
         pub fn start_stopwatch<W: Write>(&mut self, writer: &mut W) {
-            // Share the current time with the Ctrl-C handler using an Arc<AtomicU32>.
-            // This is necessary because the handler has a 'static lifetime and needs
-            // access to the time, which is being mutated in the loop.
-            let shared_time = Arc::new(AtomicU32::new(self.current_time));
-            let time_for_handler = shared_time.clone();
+            // Share the current time with the Ctrl-C handler (and any clones) using an
+            // Arc<AtomicU32>. This is necessary because the handler has a 'static lifetime
+            // and needs access to the time, which is being mutated in the loop.
+            let shared_time = self.elapsed_secs.clone();
+            shared_time.store(self.current_time, Ordering::SeqCst);
 
-            // The operation_on_stop closure has the `Copy` trait, so we can create a
-            // copy to move into the 'static Ctrl-C handler.
-            let op_on_stop = self.operation_on_stop;
+            // Until this run ends, Ctrl-C stops it along with any other running stopwatch.
+            let _running =
+                RunningGuard::register(shared_time.clone(), self.operation_on_stop.clone());
 
-            // Set the Ctrl-C handler. This closure is executed when the user presses Ctrl-C.
-            #[cfg(not(target_arch = "wasm32"))]
-            ctrlc::set_handler(move || {
-                // Load the current elapsed time from the shared atomic variable.
-                let final_time = time_for_handler.load(Ordering::SeqCst);
-                // Print a newline to avoid the shell prompt overwriting the final time.
-                println!();
-                // Execute the user-provided closure with the final time.
-                (op_on_stop)(final_time);
-                // Exit the process.
-                process::exit(0);
-            })
-            .expect("Error setting Ctrl-C handler");
+            // Elapsed time is measured against the monotonic clock rather than by counting
+            // loop iterations, so time spent writing doesn't make the stopwatch fall behind.
+            let origin = Instant::now();
+            let clock = self.clock.clone();
+            let now = || match &clock {
+                Some(clock) => clock.now(),
+                None => origin.elapsed(),
+            };
+            let mut engine =
+                StopwatchEngine::from_elapsed(Duration::from_secs(self.current_time.into()));
+            engine.start(now());
+            let mut previous_elapsed = engine.elapsed(now());
 
+            let (status, condvar) = &*self.status;
+            let mut reason = StopReason::Manual;
             loop {
-                // Check for a programmatic stop condition (e.g., set by `stop_timer`).
-                if let StopwatchStatus::Stopped = self.status {
-                    break;
+                // Check for a programmatic stop or pause, possibly issued from a clone on
+                // another thread.
+                match self.status() {
+                    StopwatchStatus::Stopped => break,
+                    StopwatchStatus::Paused => {
+                        engine.stop(now());
+                        let guard = status.lock().unwrap();
+                        let paused =
+                            |status: &mut StopwatchStatus| *status == StopwatchStatus::Paused;
+                        // A deadline keeps approaching while paused, so wake up for it.
+                        match self.until_deadline() {
+                            Some(timeout) => {
+                                drop(condvar.wait_timeout_while(guard, timeout, paused).unwrap())
+                            }
+                            None => drop(condvar.wait_while(guard, paused).unwrap()),
+                        }
+                        if self.until_deadline() == Some(Duration::ZERO) {
+                            reason = StopReason::Deadline;
+                            self.set_status(StopwatchStatus::Stopped);
+                            break;
+                        }
+                        engine.start(now());
+                        continue;
+                    }
+                    StopwatchStatus::Running => {}
                 }
 
-                let current_seconds = shared_time.load(Ordering::SeqCst);
-
-                let hours = current_seconds / 3600;
-                let minutes = (current_seconds % 3600) / 60;
-                let seconds = current_seconds % 60;
-
-                let output_format = format!("{}:{}:{}", hours, minutes, seconds);
+                let elapsed = engine.elapsed(now());
+                let current_seconds = elapsed.as_secs();
+                shared_time.store(
+                    u32::try_from(current_seconds).unwrap_or(u32::MAX),
+                    Ordering::SeqCst,
+                );
 
                 // Write the formatted time. The carriage return `\r` moves the cursor
                 // to the beginning of the line, so the next write overwrites the current one.
-                write!(writer, "{}\r", output_format).unwrap();
+                write!(writer, "{}\r", format_hms(current_seconds)).unwrap();
                 writer.flush().unwrap();
 
-                thread::sleep(Duration::from_secs(1));
+                for announcement in &self.announcements {
+                    announcement.announce(previous_elapsed, elapsed, elapsed);
+                }
+                previous_elapsed = elapsed;
+
+                if let Some(auto_reason) = self.auto_stop(elapsed) {
+                    reason = auto_reason;
+                    self.set_status(StopwatchStatus::Stopped);
+                    break;
+                }
+
+                let wait = self.next_tick(elapsed);
+                if let Some(clock) = &self.clock {
+                    // A callback may have stopped or paused the stopwatch during this tick.
+                    if self.status() == StopwatchStatus::Running {
+                        clock.sleep(wait);
+                    }
+                    continue;
+                }
 
-                // Atomically increment the time for thread-safety.
-                shared_time.fetch_add(1, Ordering::SeqCst);
+                // Sleep until the next tick, waking early if `stop` or `pause` is called.
+                let guard = status.lock().unwrap();
+                let _ = condvar
+                    .wait_timeout_while(guard, wait, |status| *status == StopwatchStatus::Running)
+                    .unwrap();
             }
 
             // This block is only reached on a programmatic stop. Ctrl-C exits the process directly.
-            // Update the struct's time to the final value from the shared atomic.
-            self.current_time = shared_time.load(Ordering::SeqCst);
+            // Update the struct's time to the final elapsed value.
+            engine.stop(now());
+            let mut accumulated = engine.accumulated();
+            if let (StopReason::MaxDuration, Some(limit)) = (reason, self.max_duration) {
+                // The last tick may land a little past the limit; report the limit itself.
+                accumulated = accumulated.min(limit);
+            }
+            self.current_time = u32::try_from(accumulated.as_secs()).unwrap_or(u32::MAX);
 
             // Print a final newline to ensure the shell prompt doesn't overwrite the last display.
             writeln!(writer).unwrap();
 
             // Execute the on-stop operation.
-            (self.operation_on_stop)(self.current_time);
+            (self.operation_on_stop.lock().unwrap())(
+                Duration::from_secs(self.current_time.into()),
+                reason,
+            );
         }
     }
 }
@@ -0,0 +1,308 @@
+//! Locale-aware rendering, enabled with the `locale` feature.
+//!
+//! A `Locale` carries the digits, clock separator and unit words used to show
+//! times to people. The built-in locales are plain data, so enabling the
+//! feature doesn't add any dependency; other languages are a struct literal
+//! away, or a `format::UnitLabels` implementation for richer plural rules.
+//! `Locale` implements `UnitLabels` itself, so it plugs into `engine::humanize`.
+//! Machine-readable outputs (status files, progress channels, the JSON
+//! `remaining_secs` fields) stay in ASCII whatever the locale.
+
+use crate::engine::{format_dhms, humanize};
+use crate::format::{TimeUnit, UnitLabels};
+use std::{env, time::Duration};
+
+/// The singular and plural word for a unit of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unit {
+    /// The word used for exactly one, e.g. `minute`.
+    pub one: &'static str,
+    /// The word used for any other amount, e.g. `minutes`.
+    pub other: &'static str,
+}
+
+impl Unit {
+    /// A unit whose word doesn't change with the amount, as in Japanese or Chinese.
+    const fn invariant(word: &'static str) -> Unit {
+        Unit {
+            one: word,
+            other: word,
+        }
+    }
+
+    /// The word for `amount` of this unit.
+    pub fn word(&self, amount: u64) -> &'static str {
+        if amount == 1 { self.one } else { self.other }
+    }
+}
+
+/// Digits, separators and words for showing times in one language.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::locale::Locale;
+/// use std::time::Duration;
+///
+/// assert_eq!(Locale::ARABIC.clock(83), "٠:١:٢٣");
+/// assert_eq!(Locale::SPANISH.words(Duration::from_secs(3900)), "1 hora 5 minutos");
+/// assert_eq!(Locale::JAPANESE.words(Duration::from_secs(90)), "1分30秒");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// The digits from zero to nine.
+    pub digits: [char; 10],
+    /// The separator between hours, minutes and seconds on the clock display.
+    pub separator: char,
+    /// The words for hours.
+    pub hour: Unit,
+    /// The words for minutes.
+    pub minute: Unit,
+    /// The words for seconds.
+    pub second: Unit,
+    /// The word following the time left, e.g. in a waybar tooltip.
+    pub remaining: &'static str,
+    /// The word following the time counted past zero.
+    pub over: &'static str,
+    /// The default title of the notification shown when a timer completes.
+    pub finished: &'static str,
+    /// Whether amounts and words are separated by spaces (`1 minute`) or not (`1分`).
+    pub spaced: bool,
+}
+
+const ASCII_DIGITS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+impl Default for Locale {
+    /// English.
+    fn default() -> Locale {
+        Locale::ENGLISH
+    }
+}
+
+impl Locale {
+    /// English.
+    pub const ENGLISH: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit {
+            one: "hour",
+            other: "hours",
+        },
+        minute: Unit {
+            one: "minute",
+            other: "minutes",
+        },
+        second: Unit {
+            one: "second",
+            other: "seconds",
+        },
+        remaining: "remaining",
+        over: "over",
+        finished: "Timer finished",
+        spaced: true,
+    };
+
+    /// Spanish.
+    pub const SPANISH: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit {
+            one: "hora",
+            other: "horas",
+        },
+        minute: Unit {
+            one: "minuto",
+            other: "minutos",
+        },
+        second: Unit {
+            one: "segundo",
+            other: "segundos",
+        },
+        remaining: "restante",
+        over: "de más",
+        finished: "Temporizador terminado",
+        spaced: true,
+    };
+
+    /// German.
+    pub const GERMAN: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit {
+            one: "Stunde",
+            other: "Stunden",
+        },
+        minute: Unit {
+            one: "Minute",
+            other: "Minuten",
+        },
+        second: Unit {
+            one: "Sekunde",
+            other: "Sekunden",
+        },
+        remaining: "verbleibend",
+        over: "überzogen",
+        finished: "Timer abgelaufen",
+        spaced: true,
+    };
+
+    /// French.
+    pub const FRENCH: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit {
+            one: "heure",
+            other: "heures",
+        },
+        minute: Unit {
+            one: "minute",
+            other: "minutes",
+        },
+        second: Unit {
+            one: "seconde",
+            other: "secondes",
+        },
+        remaining: "restant",
+        over: "de dépassement",
+        finished: "Minuteur terminé",
+        spaced: true,
+    };
+
+    /// Arabic, with Arabic-Indic digits.
+    pub const ARABIC: Locale = Locale {
+        digits: ['٠', '١', '٢', '٣', '٤', '٥', '٦', '٧', '٨', '٩'],
+        separator: ':',
+        hour: Unit {
+            one: "ساعة",
+            other: "ساعات",
+        },
+        minute: Unit {
+            one: "دقيقة",
+            other: "دقائق",
+        },
+        second: Unit {
+            one: "ثانية",
+            other: "ثوانٍ",
+        },
+        remaining: "متبقية",
+        over: "إضافية",
+        finished: "انتهى المؤقت",
+        spaced: true,
+    };
+
+    /// Japanese.
+    pub const JAPANESE: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit::invariant("時間"),
+        minute: Unit::invariant("分"),
+        second: Unit::invariant("秒"),
+        remaining: "残り",
+        over: "超過",
+        finished: "タイマー終了",
+        spaced: false,
+    };
+
+    /// Simplified Chinese.
+    pub const CHINESE: Locale = Locale {
+        digits: ASCII_DIGITS,
+        separator: ':',
+        hour: Unit::invariant("小时"),
+        minute: Unit::invariant("分钟"),
+        second: Unit::invariant("秒"),
+        remaining: "剩余",
+        over: "超时",
+        finished: "计时结束",
+        spaced: false,
+    };
+
+    /// Looks up a built-in locale by its language tag, such as `es`, `pt-BR` or the
+    /// POSIX form `ja_JP.UTF-8`. Only the language part is used.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Locale)` for English, Spanish, German, French, Arabic, Japanese and Chinese.
+    /// * `None` for any other language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::locale::Locale;
+    ///
+    /// assert_eq!(Locale::from_tag("de_AT.UTF-8"), Some(Locale::GERMAN));
+    /// assert_eq!(Locale::from_tag("zh-Hans-CN"), Some(Locale::CHINESE));
+    /// assert_eq!(Locale::from_tag("tlh"), None);
+    /// ```
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag
+            .split(['-', '_', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::ENGLISH),
+            "es" => Some(Locale::SPANISH),
+            "de" => Some(Locale::GERMAN),
+            "fr" => Some(Locale::FRENCH),
+            "ar" => Some(Locale::ARABIC),
+            "ja" => Some(Locale::JAPANESE),
+            "zh" => Some(Locale::CHINESE),
+            _ => None,
+        }
+    }
+
+    /// The user's locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order, falling
+    /// back to English when none is set to a built-in language.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|tag| Locale::from_tag(&tag))
+            .unwrap_or_default()
+    }
+
+    /// Replaces the ASCII digits in `text` with this locale's digits.
+    pub fn localize_digits(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(digit) if c.is_ascii_digit() => self.digits[digit as usize],
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Formats `total_seconds` like the timer display, `h:m:s` or `3d 04:12:09` from one
+    /// day up, with this locale's digits and separator.
+    pub fn clock(&self, total_seconds: u64) -> String {
+        let text: String = format_dhms(total_seconds)
+            .chars()
+            .map(|c| if c == ':' { self.separator } else { c })
+            .collect();
+        self.localize_digits(&text)
+    }
+
+    /// Spells out `duration` in whole hours, minutes and seconds with this locale's digits,
+    /// leaving out units that are zero, e.g. `1 hour 5 minutes`.
+    pub fn words(&self, duration: Duration) -> String {
+        self.localize_digits(&humanize(duration.as_secs(), self))
+    }
+}
+
+impl UnitLabels for Locale {
+    fn label(&self, unit: TimeUnit, amount: u64) -> &str {
+        match unit {
+            TimeUnit::Hour => self.hour.word(amount),
+            TimeUnit::Minute => self.minute.word(amount),
+            TimeUnit::Second => self.second.word(amount),
+        }
+    }
+
+    fn gap(&self) -> &str {
+        if self.spaced { " " } else { "" }
+    }
+
+    fn separator(&self) -> &str {
+        self.gap()
+    }
+}
@@ -0,0 +1,194 @@
+//! Lightweight code profiling: time a closure or a scope.
+//!
+//! Scopes report on drop to a sink, which defaults to printing
+//! `label: elapsed` on stderr and can be replaced process-wide with `set_sink`
+//! or per scope with `Scope::with_sink`. `since` and `ago` measure from a
+//! wall-clock time instead, for labels such as "session started 3 minutes ago".
+
+use crate::engine::humanize;
+use crate::format::{English, UnitLabels};
+use std::{
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// A destination for scope reports, receiving the scope label and its elapsed time.
+type Sink = Box<dyn FnMut(&str, Duration) + Send>;
+
+/// The process-wide sink used by scopes without their own; `None` means stderr.
+static GLOBAL_SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+/// Runs `operation` and returns its result together with how long it took.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::measure::time;
+///
+/// let (sum, elapsed) = time(|| (1..=1000u64).sum::<u64>());
+/// assert_eq!(sum, 500_500);
+/// println!("summing took {:?}", elapsed);
+/// ```
+pub fn time<T, F>(operation: F) -> (T, Duration)
+where
+    F: FnOnce() -> T,
+{
+    let started = Instant::now();
+    let result = operation();
+    (result, started.elapsed())
+}
+
+/// How long ago `time` was, zero if it lies in the future.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::measure::since;
+/// use std::time::{Duration, SystemTime};
+///
+/// let started = SystemTime::now() - Duration::from_secs(90);
+/// assert!(since(started) >= Duration::from_secs(90));
+/// assert_eq!(since(SystemTime::now() + Duration::from_secs(60)), Duration::ZERO);
+/// ```
+pub fn since(time: SystemTime) -> Duration {
+    SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+}
+
+/// Describes how long ago `time` was in its largest whole unit, such as `3 minutes ago`,
+/// or `just now` within the last second and for times in the future.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::measure::ago;
+/// use std::time::{Duration, SystemTime};
+///
+/// let lap = SystemTime::now() - Duration::from_secs(3 * 60 + 20);
+/// assert_eq!(ago(lap), "3 minutes ago");
+/// assert_eq!(ago(SystemTime::now()), "just now");
+/// ```
+pub fn ago(time: SystemTime) -> String {
+    ago_with(time, &English)
+}
+
+/// Like `ago`, with the unit words from `labels`, e.g. a `locale::Locale`.
+pub fn ago_with<L: UnitLabels + ?Sized>(time: SystemTime, labels: &L) -> String {
+    let seconds = since(time).as_secs();
+    if seconds == 0 {
+        return String::from("just now");
+    }
+    // Only the largest unit is kept: "2 hours ago" rather than "2 hours 4 minutes ago".
+    let rounded = match seconds {
+        0..60 => seconds,
+        60..3600 => seconds - seconds % 60,
+        _ => seconds - seconds % 3600,
+    };
+    format!("{} ago", humanize(rounded, labels))
+}
+
+/// Replaces the process-wide sink that scopes report to.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::measure::{Scope, set_sink};
+///
+/// set_sink(|label, elapsed| eprintln!("[profile] {} took {:?}", label, elapsed));
+/// let _scope = Scope::new("startup");
+/// ```
+pub fn set_sink<F>(sink: F)
+where
+    F: FnMut(&str, Duration) + Send + 'static,
+{
+    // Replacing a sink that panicked is exactly what a poisoned lock calls for.
+    *GLOBAL_SINK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(sink));
+}
+
+/// A guard that reports how long it was alive when dropped.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::measure::Scope;
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// {
+///     let _scope = Scope::with_sink("db query", move |label, elapsed| {
+///         sender.send((label.to_string(), elapsed)).unwrap()
+///     });
+///     // ... run the query ...
+/// }
+/// let (label, _elapsed) = receiver.recv().unwrap();
+/// assert_eq!(label, "db query");
+/// ```
+pub struct Scope {
+    label: String,
+    started: Instant,
+    sink: Option<Sink>,
+}
+
+impl Scope {
+    /// Starts timing a scope that reports to the process-wide sink.
+    pub fn new(label: impl Into<String>) -> Scope {
+        Scope {
+            label: label.into(),
+            started: Instant::now(),
+            sink: None,
+        }
+    }
+
+    /// Starts timing a scope that reports to `sink` instead of the process-wide one.
+    pub fn with_sink<F>(label: impl Into<String>, sink: F) -> Scope
+    where
+        F: FnMut(&str, Duration) + Send + 'static,
+    {
+        Scope {
+            label: label.into(),
+            started: Instant::now(),
+            sink: Some(Box::new(sink)),
+        }
+    }
+
+    /// The label the scope reports under.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The time elapsed since the scope started.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("label", &self.label)
+            .field("started", &self.started)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        if let Some(sink) = &mut self.sink {
+            sink(&self.label, elapsed);
+            return;
+        }
+        // A sink that panicked earlier shouldn't stop later scopes from reporting.
+        let mut global = GLOBAL_SINK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match global.as_mut() {
+            Some(sink) => sink(&self.label, elapsed),
+            None => eprintln!("{}: {:?}", self.label, elapsed),
+        }
+    }
+}
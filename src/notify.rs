@@ -0,0 +1,112 @@
+//! Native desktop notifications, enabled with the `notify` feature.
+//!
+//! Notifications are delivered through the tools each platform already ships
+//! with (`notify-send` on Linux and the BSDs, `osascript` on macOS and
+//! PowerShell on Windows), so enabling the feature doesn't add any dependency.
+
+use std::{
+    io,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// The program that shows notifications on this platform.
+#[cfg(target_os = "macos")]
+pub(crate) const TOOL: &str = "osascript";
+#[cfg(target_os = "windows")]
+pub(crate) const TOOL: &str = "powershell";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) const TOOL: &str = "notify-send";
+
+/// A desktop notification shown when a timer completes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notification {
+    /// The notification title.
+    pub title: String,
+    /// The notification body text.
+    pub body: String,
+}
+
+impl Notification {
+    /// The title used when only a body is configured.
+    pub const DEFAULT_TITLE: &'static str = "Timer finished";
+
+    /// Creates a new `Notification` with the given title and body.
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Notification {
+        Notification {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+
+    /// Shows the notification on the desktop without waiting for it to be dismissed.
+    ///
+    /// The notification tool keeps running in the background (on Windows for as long as
+    /// the balloon tip is shown) and is reaped on a detached thread once it exits.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the platform notification tool was started.
+    /// * `Err(io::Error)` if the tool is missing or couldn't be started.
+    pub fn show(&self) -> io::Result<()> {
+        let mut child = self
+            .command()
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        thread::spawn(move || child.wait());
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn command(&self) -> Command {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            escape_applescript(&self.body),
+            escape_applescript(&self.title)
+        );
+        let mut command = Command::new(TOOL);
+        command.arg("-e").arg(script);
+        command
+    }
+
+    #[cfg(target_os = "windows")]
+    fn command(&self) -> Command {
+        // A balloon tip from a temporary tray icon works on every supported
+        // Windows version without extra PowerShell modules.
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $icon = New-Object System.Windows.Forms.NotifyIcon; \
+             $icon.Icon = [System.Drawing.SystemIcons]::Information; \
+             $icon.Visible = $true; \
+             $icon.ShowBalloonTip(5000, '{}', '{}', 'Info'); \
+             Start-Sleep -Seconds 5; \
+             $icon.Dispose()",
+            escape_powershell(&self.title),
+            escape_powershell(&self.body)
+        );
+        let mut command = Command::new(TOOL);
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn command(&self) -> Command {
+        let mut command = Command::new(TOOL);
+        // `--` keeps a title or body starting with `-` from being read as an option.
+        command.arg("--").arg(&self.title).arg(&self.body);
+        command
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn escape_applescript(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(target_os = "windows")]
+fn escape_powershell(text: &str) -> String {
+    text.replace('\'', "''")
+}
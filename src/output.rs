@@ -0,0 +1,846 @@
+//! How a running timer renders its ticks.
+//!
+//! On a terminal the default is a single line overwritten with `\r`; when the
+//! output is redirected to a log file or CI it falls back to a line per tick. The
+//! other modes write one line per tick in the format a consumer expects, so the
+//! timer can feed status bars and other programs directly.
+
+use crate::engine::{Easing, format_dhms};
+#[cfg(feature = "locale")]
+use crate::locale::Locale;
+use crate::terminal;
+use std::{
+    collections::VecDeque,
+    env, fmt, fs,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+/// The output format of a running timer, set with `TimerBuilder::output`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// `Tty` when standard output is a terminal, `Plain` otherwise.
+    ///
+    /// The timer is normally written to standard output, so that is what gets
+    /// checked; set `Tty` or `Plain` explicitly when writing elsewhere.
+    #[default]
+    Auto,
+    /// A single terminal line overwritten on every tick.
+    Tty,
+    /// One JSON object per line, e.g.
+    /// `{"type":"tick","remaining_secs":83,"formatted":"0:1:23"}`.
+    ///
+    /// `type` is `tick` while counting down, `finished` on the final line and
+    /// `overtime` while counting past zero, where `overtime_secs` is added.
+    JsonLines,
+    /// The time on its own line per tick, without carriage returns, for piping.
+    Plain,
+    /// One JSON object per line for a waybar `custom` module with `"return-type": "json"`,
+    /// carrying `text`, `tooltip`, `class` (`running`, `finished` or `overtime`) and `percentage`.
+    Waybar,
+    /// One line per tick for a polybar `custom/script` module with `tail = true`.
+    /// Overtime is highlighted with polybar's `%{F#...}` colour tags.
+    Polybar,
+    /// One line per tick for an i3blocks block with `interval=persist`.
+    I3blocks,
+    /// A braille spinner frame and the time as `MM:SS`, e.g. `⠹ 04:59`, one line per
+    /// tick for tight status lines. The time is always in ASCII digits.
+    Spinner,
+    /// A single pie glyph draining from `●` to `○`, one line per tick for prompt
+    /// segments.
+    Glyph,
+}
+
+/// The frames of the `Spinner` display, advanced once per elapsed second.
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// The `Glyph` display from empty to full.
+const PROGRESS_GLYPHS: [char; 5] = ['○', '◔', '◑', '◕', '●'];
+
+/// Whether a timer colours its display, set with `TimerBuilder::color`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colour unless the `NO_COLOR` environment variable is set to a non-empty value.
+    #[default]
+    Auto,
+    /// Always colour, even with `NO_COLOR` set.
+    Always,
+    /// Never colour, including polybar's colour tags.
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output should be coloured under this choice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::ColorChoice;
+    ///
+    /// assert!(ColorChoice::Always.enabled());
+    /// assert!(!ColorChoice::Never.enabled());
+    /// ```
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The colour band a presentation timer is in, set up with `TimerBuilder::color_zones`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorZone {
+    /// Plenty of time left.
+    Green,
+    /// Time to start wrapping up.
+    Yellow,
+    /// Almost out of time, or over.
+    Red,
+}
+
+/// How one colour zone is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZoneStyle {
+    /// The ANSI SGR parameters used on the terminal, e.g. `31` or `1;97;41`.
+    pub ansi: &'static str,
+    /// The colour for polybar's `%{F#...}` tags.
+    pub polybar: &'static str,
+}
+
+/// The colours a timer display is drawn in, set with `TimerBuilder::theme`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::output::{ColorZone, OutputMode, Theme, Tick, TickStyle};
+///
+/// let tick = Tick { remaining_secs: 59, total_secs: 600, overtime_secs: 0, last: true };
+/// let style = TickStyle {
+///     zone: Some(ColorZone::Red),
+///     theme: Some(&Theme::HIGH_CONTRAST),
+///     ..TickStyle::default()
+/// };
+/// let mut line = Vec::new();
+/// OutputMode::Tty.write_styled_tick(&mut line, &tick, style).unwrap();
+/// assert_eq!(String::from_utf8(line).unwrap(), "\x1b[1;97;41m0:0:59\x1b[0m\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The style of the green zone.
+    pub green: ZoneStyle,
+    /// The style of the yellow zone.
+    pub yellow: ZoneStyle,
+    /// The style of the red zone, also used for overtime in polybar.
+    pub red: ZoneStyle,
+}
+
+impl Theme {
+    /// Plain green, yellow and red text.
+    pub const DEFAULT: Theme = Theme {
+        green: ZoneStyle {
+            ansi: "32",
+            polybar: "#0f0",
+        },
+        yellow: ZoneStyle {
+            ansi: "33",
+            polybar: "#ff0",
+        },
+        red: ZoneStyle {
+            ansi: "31",
+            polybar: "#f00",
+        },
+    };
+
+    /// Bold text on solid backgrounds, readable on any terminal palette and from the
+    /// back of a room.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        green: ZoneStyle {
+            ansi: "1;30;102",
+            polybar: "#0f0",
+        },
+        yellow: ZoneStyle {
+            ansi: "1;30;103",
+            polybar: "#ff0",
+        },
+        red: ZoneStyle {
+            ansi: "1;97;41",
+            polybar: "#f00",
+        },
+    };
+
+    /// Sky blue, orange and bold vermillion from the Okabe-Ito palette, which stay
+    /// distinct with red-green colour blindness.
+    pub const COLOR_BLIND: Theme = Theme {
+        green: ZoneStyle {
+            ansi: "38;5;74",
+            polybar: "#56b4e9",
+        },
+        yellow: ZoneStyle {
+            ansi: "38;5;178",
+            polybar: "#e69f00",
+        },
+        red: ZoneStyle {
+            ansi: "1;38;5;166",
+            polybar: "#d55e00",
+        },
+    };
+
+    /// The style `zone` is drawn in.
+    pub fn style(&self, zone: ColorZone) -> ZoneStyle {
+        match zone {
+            ColorZone::Green => self.green,
+            ColorZone::Yellow => self.yellow,
+            ColorZone::Red => self.red,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::DEFAULT
+    }
+}
+
+/// How a tick is drawn beyond its time: its colour and how progress is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickStyle<'a> {
+    /// The colour zone the countdown is in, if zones are set up.
+    pub zone: Option<ColorZone>,
+    /// The colours to draw in, or `None` for no colour at all.
+    pub theme: Option<&'a Theme>,
+    /// How the remaining time maps onto percentages and progress glyphs.
+    pub easing: Easing,
+}
+
+impl Default for TickStyle<'_> {
+    /// No zone, the default theme and linear progress.
+    fn default() -> Self {
+        TickStyle {
+            zone: None,
+            theme: Some(&Theme::DEFAULT),
+            easing: Easing::Linear,
+        }
+    }
+}
+
+impl TickStyle<'_> {
+    /// The colours of the current zone, if there are any.
+    fn zone_style(&self) -> Option<ZoneStyle> {
+        self.theme
+            .zip(self.zone)
+            .map(|(theme, zone)| theme.style(zone))
+    }
+}
+
+/// A single rendered update of a running timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tick {
+    /// Seconds left in the countdown.
+    pub remaining_secs: u64,
+    /// The full countdown duration in seconds.
+    pub total_secs: u64,
+    /// Seconds counted past zero, when overtime is enabled.
+    pub overtime_secs: u64,
+    /// Whether this is the final tick of the run.
+    pub last: bool,
+}
+
+impl Tick {
+    /// The time as the timer displays it: `h:m:s` remaining, or `+h:m:s` in overtime. From
+    /// one day up a days component is shown, as in `3d 04:12:09`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::Tick;
+    ///
+    /// let tick = Tick { remaining_secs: 83, total_secs: 300, overtime_secs: 0, last: false };
+    /// assert_eq!(tick.formatted(), "0:1:23");
+    /// ```
+    pub fn formatted(&self) -> String {
+        if self.overtime_secs > 0 {
+            format!("+{}", format_dhms(self.overtime_secs))
+        } else {
+            format_dhms(self.remaining_secs)
+        }
+    }
+
+    /// The share of the countdown still remaining, from 100 down to 0.
+    pub fn percentage(&self) -> u64 {
+        self.eased_percentage(Easing::Linear)
+    }
+
+    /// The share of the countdown still remaining like `percentage`, mapped through
+    /// `easing`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{engine::Easing, output::Tick};
+    ///
+    /// let tick = Tick { remaining_secs: 6, total_secs: 60, overtime_secs: 0, last: false };
+    /// assert_eq!(tick.percentage(), 10);
+    /// assert_eq!(tick.eased_percentage(Easing::Exponential), 50);
+    /// ```
+    pub fn eased_percentage(&self, easing: Easing) -> u64 {
+        let total = Duration::from_secs(self.total_secs);
+        let remaining = easing.apply(Duration::from_secs(self.remaining_secs), total);
+        ((remaining.as_nanos() * 100)
+            .checked_div(total.as_nanos())
+            .unwrap_or(0) as u64)
+            .min(100)
+    }
+
+    /// The time as `MM:SS`, with the minutes growing past two digits instead of showing
+    /// hours. Overtime is prefixed with `+`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::Tick;
+    ///
+    /// let tick = Tick { remaining_secs: 299, total_secs: 300, overtime_secs: 0, last: false };
+    /// assert_eq!(tick.compact(), "04:59");
+    /// let tick = Tick { remaining_secs: 5400, total_secs: 5400, overtime_secs: 0, last: false };
+    /// assert_eq!(tick.compact(), "90:00");
+    /// ```
+    pub fn compact(&self) -> String {
+        let (sign, secs) = if self.overtime_secs > 0 {
+            ("+", self.overtime_secs)
+        } else {
+            ("", self.remaining_secs)
+        };
+        format!("{}{:02}:{:02}", sign, secs / 60, secs % 60)
+    }
+
+    /// The braille spinner frame for this tick, which turns once per elapsed second.
+    pub fn spinner(&self) -> char {
+        let elapsed = self.total_secs.saturating_sub(self.remaining_secs) + self.overtime_secs;
+        SPINNER_FRAMES[(elapsed % SPINNER_FRAMES.len() as u64) as usize]
+    }
+
+    /// A single character showing the share of the countdown remaining, `●` when full
+    /// down to `○` at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::Tick;
+    ///
+    /// let tick = Tick { remaining_secs: 30, total_secs: 60, overtime_secs: 0, last: false };
+    /// assert_eq!(tick.glyph(), '◑');
+    /// ```
+    pub fn glyph(&self) -> char {
+        self.eased_glyph(Easing::Linear)
+    }
+
+    /// The progress character like `glyph`, with the remaining share mapped through
+    /// `easing`.
+    pub fn eased_glyph(&self, easing: Easing) -> char {
+        // Round to the nearest quarter, keeping anything left off the empty glyph.
+        let mut level = ((self.eased_percentage(easing) * 4 + 50) / 100) as usize;
+        if level == 0 && self.remaining_secs > 0 {
+            level = 1;
+        }
+        PROGRESS_GLYPHS[level]
+    }
+
+    fn class(&self) -> &'static str {
+        if self.overtime_secs > 0 {
+            "overtime"
+        } else if self.remaining_secs == 0 {
+            "finished"
+        } else {
+            "running"
+        }
+    }
+}
+
+impl OutputMode {
+    /// Writes `tick` to `writer` in this mode's format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::{OutputMode, Tick};
+    ///
+    /// let tick = Tick { remaining_secs: 83, total_secs: 300, overtime_secs: 0, last: false };
+    /// let mut line = Vec::new();
+    /// OutputMode::JsonLines.write_tick(&mut line, &tick).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(line).unwrap(),
+    ///     "{\"type\":\"tick\",\"remaining_secs\":83,\"formatted\":\"0:1:23\"}\n"
+    /// );
+    ///
+    /// let tick = Tick { remaining_secs: 30, total_secs: 60, overtime_secs: 0, last: false };
+    /// let mut line = Vec::new();
+    /// OutputMode::Waybar.write_tick(&mut line, &tick).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(line).unwrap(),
+    ///     "{\"text\":\"0:0:30\",\"tooltip\":\"0:0:30 remaining\",\"class\":\"running\",\"percentage\":50}\n"
+    /// );
+    /// ```
+    pub fn write_tick<W: Write + ?Sized>(&self, writer: &mut W, tick: &Tick) -> io::Result<()> {
+        self.write_zoned_tick(writer, tick, None)
+    }
+
+    /// Writes `tick` like `write_tick`, coloured for `zone` where the format supports
+    /// colour: the terminal display and polybar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::{ColorZone, OutputMode, Tick};
+    ///
+    /// let tick = Tick { remaining_secs: 59, total_secs: 600, overtime_secs: 0, last: false };
+    /// let mut line = Vec::new();
+    /// OutputMode::Polybar
+    ///     .write_zoned_tick(&mut line, &tick, Some(ColorZone::Red))
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(line).unwrap(), "%{F#f00}0:0:59%{F-}\n");
+    /// ```
+    pub fn write_zoned_tick<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        tick: &Tick,
+        zone: Option<ColorZone>,
+    ) -> io::Result<()> {
+        let style = TickStyle {
+            zone,
+            ..TickStyle::default()
+        };
+        self.write_styled_tick(writer, tick, style)
+    }
+
+    /// Writes `tick` like `write_zoned_tick`, drawn in `style`.
+    pub fn write_styled_tick<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        tick: &Tick,
+        style: TickStyle<'_>,
+    ) -> io::Result<()> {
+        self.write_text(writer, tick, style, tick.formatted(), ("remaining", "over"))
+    }
+
+    /// Writes `tick` like `write_zoned_tick`, with the time and the tooltip words in
+    /// `locale`. The numeric JSON fields stay as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{
+    ///     locale::Locale,
+    ///     output::{OutputMode, Tick},
+    /// };
+    ///
+    /// let tick = Tick { remaining_secs: 30, total_secs: 60, overtime_secs: 0, last: false };
+    /// let mut line = Vec::new();
+    /// OutputMode::Waybar
+    ///     .write_localized_tick(&mut line, &tick, None, &Locale::SPANISH)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(line).unwrap(),
+    ///     "{\"text\":\"0:0:30\",\"tooltip\":\"0:0:30 restante\",\"class\":\"running\",\"percentage\":50}\n"
+    /// );
+    /// ```
+    #[cfg(feature = "locale")]
+    pub fn write_localized_tick<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        tick: &Tick,
+        zone: Option<ColorZone>,
+        locale: &Locale,
+    ) -> io::Result<()> {
+        let style = TickStyle {
+            zone,
+            ..TickStyle::default()
+        };
+        self.write_localized_styled_tick(writer, tick, style, locale)
+    }
+
+    /// Writes `tick` like `write_localized_tick`, drawn in `style`.
+    #[cfg(feature = "locale")]
+    pub(crate) fn write_localized_styled_tick<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        tick: &Tick,
+        style: TickStyle<'_>,
+        locale: &Locale,
+    ) -> io::Result<()> {
+        let text = if tick.overtime_secs > 0 {
+            format!("+{}", locale.clock(tick.overtime_secs))
+        } else {
+            locale.clock(tick.remaining_secs)
+        };
+        self.write_text(writer, tick, style, text, (locale.remaining, locale.over))
+    }
+
+    /// Writes `tick` displayed as `text` in `style`, with `remaining` or `over` following
+    /// the time in tooltips.
+    fn write_text<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        tick: &Tick,
+        style: TickStyle<'_>,
+        text: String,
+        (remaining, over): (&str, &str),
+    ) -> io::Result<()> {
+        match self {
+            OutputMode::Auto => {
+                return self
+                    .resolve()
+                    .write_text(writer, tick, style, text, (remaining, over));
+            }
+            OutputMode::Tty => {
+                let text = match style.zone_style() {
+                    Some(zone) => terminal::paint(&text, zone.ansi),
+                    None => text,
+                };
+                if tick.last {
+                    // The final display keeps its line. Print with a newline.
+                    return writeln!(writer, "{}", text);
+                }
+                // Return to the start of the line so the next tick overwrites it.
+                terminal::write_in_place(writer, &text)?;
+            }
+            OutputMode::JsonLines => {
+                let kind = match (tick.overtime_secs > 0, tick.last) {
+                    (_, true) => "finished",
+                    (true, false) => "overtime",
+                    (false, false) => "tick",
+                };
+                write!(
+                    writer,
+                    "{{\"type\":\"{}\",\"remaining_secs\":{},\"formatted\":\"{}\"",
+                    kind, tick.remaining_secs, text
+                )?;
+                if tick.overtime_secs > 0 {
+                    write!(writer, ",\"overtime_secs\":{}", tick.overtime_secs)?;
+                }
+                writeln!(writer, "}}")?;
+            }
+            OutputMode::Plain => writeln!(writer, "{}", text)?,
+            OutputMode::Waybar => {
+                let tooltip = if tick.overtime_secs > 0 {
+                    format!("{} {}", text, over)
+                } else {
+                    format!("{} {}", text, remaining)
+                };
+                writeln!(
+                    writer,
+                    "{{\"text\":\"{}\",\"tooltip\":\"{}\",\"class\":\"{}\",\"percentage\":{}}}",
+                    text,
+                    tooltip,
+                    tick.class(),
+                    tick.eased_percentage(style.easing)
+                )?;
+            }
+            OutputMode::Polybar => {
+                let color = if tick.overtime_secs > 0 {
+                    style.theme.map(|theme| theme.red.polybar)
+                } else {
+                    style.zone_style().map(|zone| zone.polybar)
+                };
+                match color {
+                    Some(color) => writeln!(writer, "%{{F{}}}{}%{{F-}}", color, text)?,
+                    None => writeln!(writer, "{}", text)?,
+                }
+            }
+            OutputMode::I3blocks => writeln!(writer, "{}", text)?,
+            OutputMode::Spinner => writeln!(writer, "{} {}", tick.spinner(), tick.compact())?,
+            OutputMode::Glyph => writeln!(writer, "{}", tick.eased_glyph(style.easing))?,
+        }
+        // Consumers read line by line, so each tick has to reach them immediately.
+        writer.flush()
+    }
+
+    /// Resolves `Auto` to the concrete mode for the current standard output.
+    ///
+    /// Every other mode is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::output::OutputMode;
+    ///
+    /// assert_ne!(OutputMode::Auto.resolve(), OutputMode::Auto);
+    /// assert_eq!(OutputMode::Waybar.resolve(), OutputMode::Waybar);
+    /// ```
+    pub fn resolve(self) -> OutputMode {
+        match self {
+            OutputMode::Auto if io::stdout().is_terminal() => OutputMode::Tty,
+            OutputMode::Auto => OutputMode::Plain,
+            mode => mode,
+        }
+    }
+
+    /// Whether this mode is, or resolves to, the interactive terminal display.
+    pub fn is_tty(&self) -> bool {
+        matches!(self.resolve(), OutputMode::Tty)
+    }
+}
+
+/// Replaces the contents of `path` with the formatted time of `tick` and a newline.
+///
+/// The file is written next to `path` and renamed over it, so readers such as a
+/// tmux `#(cat ...)` status segment never see a half-written time.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::output::{Tick, write_status_file};
+///
+/// let path = std::env::temp_dir().join("clock-timer-status-doctest");
+/// let tick = Tick { remaining_secs: 90, total_secs: 300, overtime_secs: 0, last: false };
+/// write_status_file(&path, &tick).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "0:1:30\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn write_status_file(path: &Path, tick: &Tick) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, format!("{}\n", tick.formatted()))?;
+    fs::rename(&temporary, path)
+}
+
+/// A secondary channel receiving compact `remaining=NN` lines, one per tick.
+///
+/// Overtime ticks are written as `overtime=NN`. Wrapping programs can point this
+/// at a dedicated pipe and track progress without parsing the human-readable output.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::output::{ProgressWriter, Tick};
+///
+/// let progress = ProgressWriter::new(Vec::new());
+/// let tick = Tick { remaining_secs: 83, total_secs: 300, overtime_secs: 0, last: false };
+/// progress.write_tick(&tick).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct ProgressWriter {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl ProgressWriter {
+    /// Creates a new `ProgressWriter` writing to `writer`.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> ProgressWriter {
+        ProgressWriter {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// Writes the progress line for `tick` and flushes it.
+    pub fn write_tick(&self, tick: &Tick) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        if tick.overtime_secs > 0 {
+            writeln!(writer, "overtime={}", tick.overtime_secs)?;
+        } else {
+            writeln!(writer, "remaining={}", tick.remaining_secs)?;
+        }
+        writer.flush()
+    }
+}
+
+impl fmt::Debug for ProgressWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressWriter").finish_non_exhaustive()
+    }
+}
+
+/// Runs `body` on a separate thread, handing it a `QueueWriter` whose flushed writes are
+/// copied to `writer` on the calling thread, so a slow or blocked `writer` never holds
+/// `body` up. At most `capacity` flushed writes wait in between; when the queue is full
+/// the oldest is dropped, as a newer tick supersedes it.
+///
+/// # Returns
+///
+/// * The result of `body`, unless writing to `writer` failed, in which case that error.
+///   `body` sees a `BrokenPipe` error from its next write once `writer` has failed.
+pub(crate) fn through_output_thread<W, T, F>(
+    writer: &mut W,
+    capacity: usize,
+    body: F,
+) -> io::Result<T>
+where
+    W: Write + ?Sized,
+    T: Send,
+    F: FnOnce(&mut QueueWriter<'_>) -> io::Result<T> + Send,
+{
+    let queue = OutputQueue {
+        state: Mutex::new(QueueState::default()),
+        changed: Condvar::new(),
+        capacity: capacity.max(1),
+    };
+    thread::scope(|scope| {
+        let producer = scope.spawn(|| {
+            // Closes the queue even if `body` panics, so `drain` returns and the scope joins.
+            let _close = CloseOnDrop(&queue);
+            let mut queued = QueueWriter {
+                queue: &queue,
+                pending: Vec::new(),
+            };
+            body(&mut queued).and_then(|value| queued.flush().map(|()| value))
+        });
+        let drained = queue.drain(writer);
+        let result = producer
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        drained.and(result)
+    })
+}
+
+/// The bounded, drop-oldest queue between `QueueWriter` and the output thread.
+struct OutputQueue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    chunks: VecDeque<Vec<u8>>,
+    /// Set once the producer has finished.
+    closed: bool,
+    /// Set once writing to the real writer has failed.
+    failed: bool,
+}
+
+impl OutputQueue {
+    /// Copies queued writes to `writer` until the producer closes the queue.
+    fn drain<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        loop {
+            let chunk = {
+                let mut state = self.state.lock().unwrap();
+                loop {
+                    if let Some(chunk) = state.chunks.pop_front() {
+                        break chunk;
+                    }
+                    if state.closed {
+                        return Ok(());
+                    }
+                    state = self.changed.wait(state).unwrap();
+                }
+            };
+            if let Err(error) = writer.write_all(&chunk).and_then(|()| writer.flush()) {
+                self.state.lock().unwrap().failed = true;
+                return Err(error);
+            }
+        }
+    }
+}
+
+/// Marks an `OutputQueue` closed when the producer finishes, however it finishes.
+struct CloseOnDrop<'a>(&'a OutputQueue);
+
+impl Drop for CloseOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+        self.0.changed.notify_all();
+    }
+}
+
+/// The writer handed to the producer by `through_output_thread`.
+///
+/// Writes are collected until `flush`, which queues them as one unit, so a dropped entry
+/// is always a whole tick rather than part of one.
+pub(crate) struct QueueWriter<'a> {
+    queue: &'a OutputQueue,
+    pending: Vec<u8>,
+}
+
+impl Write for QueueWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.queue.state.lock().unwrap();
+        if state.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the output thread stopped writing",
+            ));
+        }
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if state.chunks.len() >= self.queue.capacity {
+            state.chunks.pop_front();
+        }
+        state.chunks.push_back(std::mem::take(&mut self.pending));
+        self.queue.changed.notify_all();
+        Ok(())
+    }
+}
+
+/// Redraws a block of terminal lines in place, e.g. one per stopwatch lane.
+///
+/// After the first draw, each call moves the cursor back up over the lines it
+/// drew last time and overwrites them, clearing any leftover characters.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::output::MultiLineRenderer;
+///
+/// let mut renderer = MultiLineRenderer::new();
+/// let mut terminal = Vec::new();
+/// renderer.draw(&mut terminal, &["build  0:0:1", "tests  0:0:1"]).unwrap();
+/// renderer.draw(&mut terminal, &["build  0:0:2", "tests  0:0:2"]).unwrap();
+///
+/// let terminal = String::from_utf8(terminal).unwrap();
+/// assert!(terminal.contains("\x1b[2A")); // moved up over the first two lines
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MultiLineRenderer {
+    drawn: usize,
+}
+
+impl MultiLineRenderer {
+    /// Creates a renderer that hasn't drawn anything yet.
+    pub fn new() -> MultiLineRenderer {
+        MultiLineRenderer::default()
+    }
+
+    /// Draws `lines`, replacing the block drawn by the previous call.
+    pub fn draw<W, S>(&mut self, writer: &mut W, lines: &[S]) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+        S: AsRef<str>,
+    {
+        // Consoles without cursor movement get each block printed below the previous one.
+        if !terminal::ansi_supported() {
+            for line in lines {
+                writeln!(writer, "{}", line.as_ref())?;
+            }
+            return writer.flush();
+        }
+        if self.drawn > 0 {
+            write!(writer, "\x1b[{}A", self.drawn)?;
+        }
+        for line in lines {
+            write!(writer, "\r\x1b[2K{}\n", line.as_ref())?;
+        }
+        // Clear lines left over from a taller previous block, then return below the new one.
+        let leftover = self.drawn.saturating_sub(lines.len());
+        for _ in 0..leftover {
+            writer.write_all(b"\r\x1b[2K\n")?;
+        }
+        if leftover > 0 {
+            write!(writer, "\x1b[{}A", leftover)?;
+        }
+        self.drawn = lines.len();
+        writer.flush()
+    }
+}
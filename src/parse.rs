@@ -0,0 +1,377 @@
+//! Parsing durations typed by people.
+//!
+//! `parse_duration` accepts the forms the crate prints: `h:m:s` and compact
+//! units such as `1h 5m 3s`. `parse_duration_lenient` also takes messier input
+//! like `1.5h`, `90 min`, `2 hours and 10 minutes` or `1:30`. Both also take
+//! ISO 8601 durations such as `PT1H30M`, as found in calendar and API payloads.
+//! Both report what went wrong and where through `ParseError`, so front-ends
+//! can point at the offending part of the input.
+
+use core::{fmt, time::Duration};
+
+/// What went wrong while parsing a duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// The input is empty or only whitespace.
+    Empty,
+    /// A number was expected, e.g. before a unit.
+    ExpectedNumber,
+    /// A number is malformed, such as `1.` or a fraction in strict mode.
+    InvalidNumber,
+    /// A number isn't followed by a unit.
+    MissingUnit,
+    /// The unit isn't one of the recognised spellings.
+    UnknownUnit,
+    /// A unit appears twice or after a smaller one, in strict mode.
+    UnitOrder,
+    /// Minutes or seconds of a clock time are 60 or more.
+    OutOfRange,
+    /// A clock time has the wrong number of `:`-separated fields.
+    ClockFields,
+    /// The duration doesn't fit in a `Duration`.
+    Overflow,
+}
+
+/// Why a duration couldn't be parsed, and the byte offset in the input where the problem
+/// starts.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::parse::{ParseErrorKind, parse_duration_lenient};
+///
+/// let error = parse_duration_lenient("5 fortnights").unwrap_err();
+/// assert_eq!(error.kind, ParseErrorKind::UnknownUnit);
+/// assert_eq!(error.position, 2);
+/// assert_eq!(error.to_string(), "unknown unit at byte 2");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+    /// The byte offset in the input where the problem starts.
+    pub position: usize,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, position: usize) -> ParseError {
+        ParseError { kind, position }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.kind {
+            ParseErrorKind::Empty => return f.write_str("empty duration"),
+            ParseErrorKind::ExpectedNumber => "expected a number",
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::MissingUnit => "missing unit",
+            ParseErrorKind::UnknownUnit => "unknown unit",
+            ParseErrorKind::UnitOrder => "unit repeated or out of order",
+            ParseErrorKind::OutOfRange => "value must be below 60",
+            ParseErrorKind::ClockFields => "wrong number of clock fields",
+            ParseErrorKind::Overflow => "duration too long",
+        };
+        write!(f, "{} at byte {}", description, self.position)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Parses `h:m:s` or compact units in descending order such as `1h30m`, `90s` or
+/// `1h 5m 3s`, the forms the crate itself prints, and ISO 8601 durations.
+///
+/// ISO 8601 durations take weeks and days before the `T`, and hours, minutes and
+/// seconds after it: `P1W`, `P2DT3H`, `PT1H30M`, `PT0.5S`. Years and months are rejected
+/// as `UnknownUnit`, since their length varies.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::parse::{ParseErrorKind, parse_duration};
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration("1:02:03"), Ok(Duration::from_secs(3723)));
+/// assert_eq!(parse_duration("1h 5m 3s"), Ok(Duration::from_secs(3903)));
+/// assert_eq!(parse_duration("5m1h").unwrap_err().kind, ParseErrorKind::UnitOrder);
+/// assert_eq!(parse_duration("1.5h").unwrap_err().kind, ParseErrorKind::InvalidNumber);
+///
+/// assert_eq!(parse_duration("PT1H30M"), Ok(Duration::from_secs(5400)));
+/// assert_eq!(parse_duration("P2DT3H"), Ok(Duration::from_secs(2 * 86_400 + 3 * 3600)));
+/// assert_eq!(parse_duration("P1M").unwrap_err().kind, ParseErrorKind::UnknownUnit);
+/// ```
+pub fn parse_duration(text: &str) -> Result<Duration, ParseError> {
+    parse(text, false)
+}
+
+/// Parses durations the way people type them:
+///
+/// * fractions with `.` or `,`: `1.5h`, `2,5 min`
+/// * spelled-out and abbreviated units in any case: `90 min`, `2 Hours`, `45 secs`
+/// * several terms in any order, optionally joined by `and`: `1 hour and 30 minutes`
+/// * `h:m` as well as `h:m:s` clock times: `1:30`
+/// * a bare number as minutes, as on a kitchen timer: `5`
+/// * ISO 8601 durations, as with `parse_duration`: `PT1H30M`
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::parse::{ParseErrorKind, parse_duration_lenient};
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_duration_lenient("1.5h"), Ok(Duration::from_secs(5400)));
+/// assert_eq!(parse_duration_lenient("90 min"), Ok(Duration::from_secs(5400)));
+/// assert_eq!(parse_duration_lenient("1:30"), Ok(Duration::from_secs(5400)));
+/// assert_eq!(parse_duration_lenient("1 Hour and 30 MINUTES"), Ok(Duration::from_secs(5400)));
+/// assert_eq!(parse_duration_lenient(" 5 "), Ok(Duration::from_secs(300)));
+/// assert_eq!(parse_duration_lenient("1:75").unwrap_err().kind, ParseErrorKind::OutOfRange);
+/// ```
+pub fn parse_duration_lenient(text: &str) -> Result<Duration, ParseError> {
+    parse(text, true)
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+fn parse(text: &str, lenient: bool) -> Result<Duration, ParseError> {
+    let start = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::Empty, 0));
+    }
+    if trimmed.starts_with(['P', 'p']) {
+        return parse_iso8601(text, start);
+    }
+    if trimmed.contains(':') {
+        return parse_clock(trimmed, start, lenient);
+    }
+
+    let bytes = text.as_bytes();
+    let mut position = start;
+    let mut total: u128 = 0;
+    // In strict mode units must strictly descend; this is the last unit's size.
+    let mut previous_unit = u64::MAX;
+    let mut terms = 0;
+    while position < trimmed.len() + start {
+        if lenient && terms > 0 && word_at(text, position, "and") {
+            position = skip_whitespace(bytes, position + 3);
+            continue;
+        }
+
+        let (amount, after_number) = parse_number(text, position, lenient)?;
+        let unit_start = if lenient {
+            skip_whitespace(bytes, after_number)
+        } else {
+            after_number
+        };
+        let unit_end = unit_start
+            + text[unit_start..]
+                .bytes()
+                .take_while(u8::is_ascii_alphabetic)
+                .count();
+        let unit_secs = if unit_start == unit_end {
+            if lenient && terms == 0 && text[unit_end..].trim().is_empty() {
+                60
+            } else {
+                return Err(ParseError::new(ParseErrorKind::MissingUnit, unit_start));
+            }
+        } else {
+            unit_seconds(&text[unit_start..unit_end], lenient)
+                .ok_or(ParseError::new(ParseErrorKind::UnknownUnit, unit_start))?
+        };
+        if !lenient {
+            if unit_secs >= previous_unit {
+                return Err(ParseError::new(ParseErrorKind::UnitOrder, unit_start));
+            }
+            previous_unit = unit_secs;
+        }
+
+        total = amount
+            .checked_mul(u128::from(unit_secs))
+            .and_then(|nanos| total.checked_add(nanos))
+            .ok_or(ParseError::new(ParseErrorKind::Overflow, position))?;
+        terms += 1;
+        position = skip_whitespace(bytes, unit_end);
+        if lenient && bytes.get(position) == Some(&b',') {
+            position = skip_whitespace(bytes, position + 1);
+        }
+    }
+    to_duration(total, start)
+}
+
+/// Parses `h:m:s`, or in lenient mode also `h:m`, starting at byte `offset` of the input.
+fn parse_clock(text: &str, offset: usize, lenient: bool) -> Result<Duration, ParseError> {
+    let fields = text.split(':').count();
+    if fields != 3 && !(lenient && fields == 2) {
+        return Err(ParseError::new(ParseErrorKind::ClockFields, offset));
+    }
+    // Hours:minutes in the short form, hours:minutes:seconds otherwise.
+    let scales: &[u64] = if fields == 2 {
+        &[3600, 60]
+    } else {
+        &[3600, 60, 1]
+    };
+
+    let mut total: u128 = 0;
+    let mut position = offset;
+    for (index, (field, scale)) in text.split(':').zip(scales).enumerate() {
+        let trimmed = field.trim();
+        let field_start = position + (field.len() - field.trim_start().len());
+        if trimmed.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::ExpectedNumber, field_start));
+        }
+        if !trimmed.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseError::new(ParseErrorKind::InvalidNumber, field_start));
+        }
+        let value: u64 = trimmed
+            .parse()
+            .map_err(|_| ParseError::new(ParseErrorKind::Overflow, field_start))?;
+        if index > 0 && value >= 60 {
+            return Err(ParseError::new(ParseErrorKind::OutOfRange, field_start));
+        }
+        total = u128::from(value)
+            .checked_mul(u128::from(*scale) * NANOS_PER_SEC)
+            .and_then(|nanos| total.checked_add(nanos))
+            .ok_or(ParseError::new(ParseErrorKind::Overflow, field_start))?;
+        position += field.len() + 1;
+    }
+    to_duration(total, offset)
+}
+
+/// Parses an ISO 8601 duration whose `P` is at byte `offset`. Designators are accepted in
+/// either case, and fractions on any component.
+fn parse_iso8601(text: &str, offset: usize) -> Result<Duration, ParseError> {
+    let bytes = text.as_bytes();
+    let end = text.trim_end().len();
+    let mut position = offset + 1;
+    let mut in_time = false;
+    // Components must strictly descend; this is the last one's size.
+    let mut previous_unit = u64::MAX;
+    let mut total: u128 = 0;
+    let mut terms = 0;
+    while position < end {
+        if !in_time && bytes[position].eq_ignore_ascii_case(&b'T') {
+            in_time = true;
+            terms = 0;
+            position += 1;
+            continue;
+        }
+        let (amount, after_number) = parse_number(text, position, true)?;
+        if after_number >= end {
+            return Err(ParseError::new(ParseErrorKind::MissingUnit, after_number));
+        }
+        let unit_secs = match (in_time, bytes[after_number].to_ascii_uppercase()) {
+            (false, b'W') => 7 * 86_400,
+            (false, b'D') => 86_400,
+            (true, b'H') => 3600,
+            (true, b'M') => 60,
+            (true, b'S') => 1,
+            _ => return Err(ParseError::new(ParseErrorKind::UnknownUnit, after_number)),
+        };
+        if unit_secs >= previous_unit {
+            return Err(ParseError::new(ParseErrorKind::UnitOrder, after_number));
+        }
+        previous_unit = unit_secs;
+        total = amount
+            .checked_mul(u128::from(unit_secs))
+            .and_then(|nanos| total.checked_add(nanos))
+            .ok_or(ParseError::new(ParseErrorKind::Overflow, position))?;
+        terms += 1;
+        position = after_number + 1;
+    }
+    // `P` and `T` each need at least one component after them.
+    if terms == 0 {
+        return Err(ParseError::new(ParseErrorKind::ExpectedNumber, end));
+    }
+    to_duration(total, offset)
+}
+
+/// Parses the number at `position` as nanoseconds per unit, returning it with the offset
+/// just past it. Fractions are only accepted in lenient mode.
+fn parse_number(text: &str, position: usize, lenient: bool) -> Result<(u128, usize), ParseError> {
+    let bytes = text.as_bytes();
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+
+    let whole_len = digits(position);
+    if whole_len == 0 {
+        return Err(ParseError::new(ParseErrorKind::ExpectedNumber, position));
+    }
+    let whole: u128 = text[position..position + whole_len]
+        .parse()
+        .map_err(|_| ParseError::new(ParseErrorKind::Overflow, position))?;
+    let mut end = position + whole_len;
+    let mut nanos = whole
+        .checked_mul(NANOS_PER_SEC)
+        .ok_or(ParseError::new(ParseErrorKind::Overflow, position))?;
+
+    if let Some(b'.' | b',') = bytes.get(end) {
+        let fraction_len = digits(end + 1);
+        // A comma not followed by digits separates terms rather than starting a fraction.
+        if bytes[end] == b',' && fraction_len == 0 {
+            return Ok((nanos, end));
+        }
+        if !lenient || fraction_len == 0 {
+            return Err(ParseError::new(ParseErrorKind::InvalidNumber, position));
+        }
+        let mut scale = NANOS_PER_SEC / 10;
+        // Digits past nanosecond precision are dropped.
+        for digit in &bytes[end + 1..end + 1 + fraction_len] {
+            nanos += u128::from(digit - b'0') * scale;
+            scale /= 10;
+        }
+        end += 1 + fraction_len;
+    }
+    Ok((nanos, end))
+}
+
+/// The length in seconds of the unit spelled `unit`.
+fn unit_seconds(unit: &str, lenient: bool) -> Option<u64> {
+    if !lenient {
+        return match unit {
+            "h" => Some(3600),
+            "m" => Some(60),
+            "s" => Some(1),
+            _ => None,
+        };
+    }
+    const UNITS: [(&[&str], u64); 3] = [
+        (&["h", "hr", "hrs", "hour", "hours"], 3600),
+        (&["m", "min", "mins", "minute", "minutes"], 60),
+        (&["s", "sec", "secs", "second", "seconds"], 1),
+    ];
+    UNITS.iter().find_map(|(spellings, seconds)| {
+        spellings
+            .iter()
+            .any(|spelling| spelling.eq_ignore_ascii_case(unit))
+            .then_some(*seconds)
+    })
+}
+
+/// Returns `true` if the whole word `word` starts at `position`, in any case.
+fn word_at(text: &str, position: usize, word: &str) -> bool {
+    let end = position + word.len();
+    text.get(position..end)
+        .is_some_and(|found| found.eq_ignore_ascii_case(word))
+        && text
+            .as_bytes()
+            .get(end)
+            .is_none_or(|byte| !byte.is_ascii_alphanumeric())
+}
+
+fn skip_whitespace(bytes: &[u8], position: usize) -> usize {
+    position
+        + bytes[position..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_whitespace())
+            .count()
+}
+
+fn to_duration(nanos: u128, position: usize) -> Result<Duration, ParseError> {
+    let seconds = u64::try_from(nanos / NANOS_PER_SEC)
+        .map_err(|_| ParseError::new(ParseErrorKind::Overflow, position))?;
+    Ok(Duration::new(seconds, (nanos % NANOS_PER_SEC) as u32))
+}
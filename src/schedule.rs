@@ -0,0 +1,619 @@
+//! Recurring timers: every N minutes, daily at a time of day, or on weekdays only.
+//!
+//! Times of day are evaluated at a fixed UTC offset (UTC by default). The crate
+//! has no time zone database, so daylight saving changes have to be handled by
+//! updating the offset.
+//!
+//! `AlarmImport` creates many labelled schedules at once from a CSV file, and
+//! `Holidays` lets a schedule stay quiet on days off.
+
+use crate::calendar::{split_property, unfold};
+use crate::clock::{days_from_civil, days_in_month};
+use crate::interval::CancelHandle;
+use crate::parse::parse_duration_lenient;
+use crate::time_source::unix_seconds;
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt, fs, io, mem,
+    path::Path,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// How often a `Schedule` fires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cadence {
+    /// Every `period`, aligned to multiples of the period since the epoch in local
+    /// time (so every 15 minutes fires at :00, :15, :30 and :45).
+    Every(Duration),
+    /// Every day at `hour`:`minute`.
+    Daily {
+        /// The hour of day, 0-23.
+        hour: u8,
+        /// The minute of the hour, 0-59.
+        minute: u8,
+    },
+    /// Monday to Friday at `hour`:`minute`.
+    Weekdays {
+        /// The hour of day, 0-23.
+        hour: u8,
+        /// The minute of the hour, 0-59.
+        minute: u8,
+    },
+}
+
+/// A recurring cadence plus the UTC offset its times of day are expressed in.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::schedule::Schedule;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// // 1970-01-01 was a Thursday; the next weekday 09:00 after Friday noon is Monday.
+/// let friday_noon = UNIX_EPOCH + Duration::from_secs(86_400 + 12 * 3600);
+/// let schedule = Schedule::weekdays(9, 0).unwrap();
+/// let next = schedule.next_occurrence(friday_noon).unwrap();
+/// assert_eq!(next, UNIX_EPOCH + Duration::from_secs(4 * 86_400 + 9 * 3600));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    cadence: Cadence,
+    utc_offset: i32,
+}
+
+impl Schedule {
+    /// Creates a `Schedule` firing every `period`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Schedule)` if `period` is at least one second.
+    /// * `Err("Period need to be 1 or more seconds.")` otherwise.
+    pub fn every(period: Duration) -> Result<Schedule, &'static str> {
+        if period.as_secs() == 0 {
+            return Err("Period need to be 1 or more seconds.");
+        }
+        Ok(Schedule::new(Cadence::Every(period)))
+    }
+
+    /// Creates a `Schedule` firing every day at `hour`:`minute`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Schedule)` for a valid time of day.
+    /// * `Err("Invalid time of day.")` if `hour` is over 23 or `minute` over 59.
+    pub fn daily(hour: u8, minute: u8) -> Result<Schedule, &'static str> {
+        check_time_of_day(hour, minute)?;
+        Ok(Schedule::new(Cadence::Daily { hour, minute }))
+    }
+
+    /// Creates a `Schedule` firing Monday to Friday at `hour`:`minute`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Schedule)` for a valid time of day.
+    /// * `Err("Invalid time of day.")` if `hour` is over 23 or `minute` over 59.
+    pub fn weekdays(hour: u8, minute: u8) -> Result<Schedule, &'static str> {
+        check_time_of_day(hour, minute)?;
+        Ok(Schedule::new(Cadence::Weekdays { hour, minute }))
+    }
+
+    fn new(cadence: Cadence) -> Schedule {
+        Schedule {
+            cadence,
+            utc_offset: 0,
+        }
+    }
+
+    /// Evaluates times of day at `seconds` east of UTC (e.g. `3600` for UTC+1).
+    pub fn utc_offset(mut self, seconds: i32) -> Schedule {
+        self.utc_offset = seconds;
+        self
+    }
+
+    /// The cadence this schedule fires on.
+    pub fn cadence(&self) -> Cadence {
+        self.cadence
+    }
+
+    /// Returns the first occurrence strictly after `after`.
+    ///
+    /// Occurrences fall on whole seconds. Returns `None` if the result isn't
+    /// representable as a `SystemTime`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::schedule::Schedule;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let schedule = Schedule::every(Duration::from_secs(15 * 60)).unwrap();
+    /// let after = UNIX_EPOCH + Duration::from_secs(10 * 60);
+    /// assert_eq!(
+    ///     schedule.next_occurrence(after),
+    ///     Some(UNIX_EPOCH + Duration::from_secs(15 * 60))
+    /// );
+    ///
+    /// // 08:30 in UTC+2 is 06:30 UTC.
+    /// let schedule = Schedule::daily(8, 30).unwrap().utc_offset(2 * 3600);
+    /// assert_eq!(
+    ///     schedule.next_occurrence(UNIX_EPOCH),
+    ///     Some(UNIX_EPOCH + Duration::from_secs(6 * 3600 + 30 * 60))
+    /// );
+    /// ```
+    pub fn next_occurrence(&self, after: SystemTime) -> Option<SystemTime> {
+        let offset = i64::from(self.utc_offset);
+        let local = unix_seconds(after).checked_add(offset)?;
+
+        let next_local = match self.cadence {
+            Cadence::Every(period) => {
+                let period = i64::try_from(period.as_secs()).ok()?;
+                (local.div_euclid(period) + 1).checked_mul(period)?
+            }
+            Cadence::Daily { hour, minute } => next_time_of_day(local, hour, minute, |_| true)?,
+            Cadence::Weekdays { hour, minute } => {
+                next_time_of_day(local, hour, minute, |day| weekday(day) < 5)?
+            }
+        };
+
+        from_unix_seconds(next_local.checked_sub(offset)?)
+    }
+
+    /// Runs `operation` at every occurrence until the returned handle is cancelled.
+    ///
+    /// Each occurrence is computed from the wall clock when the previous one fires,
+    /// so clock adjustments are picked up at the next occurrence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::schedule::Schedule;
+    ///
+    /// let handle = Schedule::weekdays(9, 0)
+    ///     .unwrap()
+    ///     .start(|| println!("Stand-up time!"));
+    /// handle.cancel();
+    /// ```
+    pub fn start<F>(self, mut operation: F) -> CancelHandle
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.run(move |_| operation())
+    }
+
+    /// Whether `occurrence` falls on one of `holidays`, taking the date in this
+    /// schedule's UTC offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::schedule::{Holidays, Schedule};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let holidays = Holidays::from_list("1970-01-02").unwrap();
+    /// let schedule = Schedule::weekdays(9, 0).unwrap();
+    /// let thursday = schedule.next_occurrence(UNIX_EPOCH).unwrap();
+    /// let friday = schedule.next_occurrence(thursday).unwrap();
+    /// assert!(!schedule.is_holiday(thursday, &holidays));
+    /// assert!(schedule.is_holiday(friday, &holidays));
+    ///
+    /// // 23:30 UTC on Thursday is already Friday in UTC+2.
+    /// let late = UNIX_EPOCH + Duration::from_secs(23 * 3600 + 30 * 60);
+    /// assert!(schedule.utc_offset(2 * 3600).is_holiday(late, &holidays));
+    /// ```
+    pub fn is_holiday(&self, occurrence: SystemTime, holidays: &Holidays) -> bool {
+        let local = unix_seconds(occurrence).saturating_add(i64::from(self.utc_offset));
+        holidays.days.contains(&local.div_euclid(SECS_PER_DAY))
+    }
+
+    /// Like `start`, but occurrences falling on `holidays` call `on_skip` with the
+    /// occurrence instead of running `operation`, e.g. to note that a work-morning
+    /// alarm stayed quiet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::schedule::{Holidays, Schedule};
+    ///
+    /// let holidays = Holidays::from_list("2025-12-25\n2025-12-26").unwrap();
+    /// let handle = Schedule::weekdays(7, 0).unwrap().start_skipping_holidays(
+    ///     holidays,
+    ///     || println!("Wake up!"),
+    ///     |skipped| println!("No alarm at {:?}: it's a holiday", skipped),
+    /// );
+    /// handle.cancel();
+    /// ```
+    pub fn start_skipping_holidays<F, S>(
+        self,
+        holidays: Holidays,
+        mut operation: F,
+        mut on_skip: S,
+    ) -> CancelHandle
+    where
+        F: FnMut() + Send + 'static,
+        S: FnMut(SystemTime) + Send + 'static,
+    {
+        self.run(move |occurrence| {
+            if self.is_holiday(occurrence, &holidays) {
+                on_skip(occurrence);
+            } else {
+                operation();
+            }
+        })
+    }
+
+    /// Calls `on_occurrence` with every occurrence as it comes, until the returned handle
+    /// is cancelled.
+    fn run<F>(self, mut on_occurrence: F) -> CancelHandle
+    where
+        F: FnMut(SystemTime) + Send + 'static,
+    {
+        let handle = CancelHandle::new();
+        let thread_handle = handle.clone();
+
+        thread::spawn(move || {
+            loop {
+                let now = SystemTime::now();
+                let Some(next) = self.next_occurrence(now) else {
+                    break;
+                };
+                let wait = next.duration_since(now).unwrap_or(Duration::ZERO);
+                if thread_handle.wait_until(Instant::now() + wait) {
+                    break;
+                }
+                on_occurrence(next);
+            }
+        });
+
+        handle
+    }
+}
+
+/// A labelled schedule created by an `AlarmImport`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    /// What the alarm is for.
+    pub label: String,
+    /// When it goes off, in UTC until `Schedule::utc_offset` is applied.
+    pub schedule: Schedule,
+}
+
+/// A CSV row that an `AlarmImport` skipped, and why.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RowError {
+    /// The 1-based line number of the row.
+    pub line: usize,
+    /// What is wrong with it.
+    pub message: &'static str,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for RowError {}
+
+/// Alarms read from CSV rows of `time,label,recurrence`.
+///
+/// `time` is `HH:MM`. `recurrence` is `daily` (the default when left empty),
+/// `weekdays`, or `every` and a duration such as `every 50 min` with the time left
+/// empty. Labels containing commas are quoted. An optional header row starting with
+/// `time` and blank lines are skipped. A row that can't be read is reported in `errors`
+/// without stopping the import.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::schedule::{AlarmImport, Schedule};
+/// use std::time::Duration;
+///
+/// let import = AlarmImport::from_csv(
+///     "time,label,recurrence\n\
+///      07:30,Wake up,daily\n\
+///      09:00,\"Stand-up, team\",weekdays\n\
+///      25:00,Broken,daily\n\
+///      ,Stretch,every 50 min\n",
+/// );
+/// assert_eq!(import.alarms.len(), 3);
+/// assert_eq!(import.alarms[1].label, "Stand-up, team");
+/// assert_eq!(import.alarms[1].schedule, Schedule::weekdays(9, 0).unwrap());
+/// assert_eq!(
+///     import.alarms[2].schedule,
+///     Schedule::every(Duration::from_secs(50 * 60)).unwrap()
+/// );
+/// assert_eq!(import.errors[0].to_string(), "line 4: Invalid time of day.");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AlarmImport {
+    /// The alarms of every valid row, in file order.
+    pub alarms: Vec<Alarm>,
+    /// The rows that were skipped.
+    pub errors: Vec<RowError>,
+}
+
+impl AlarmImport {
+    /// Reads alarms from CSV text, one row per line.
+    pub fn from_csv(csv: &str) -> AlarmImport {
+        let mut import = AlarmImport::default();
+        let mut first = true;
+        for (index, line) in csv.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = csv_fields(line);
+            let header = first
+                && row
+                    .as_ref()
+                    .is_ok_and(|fields| fields[0].trim().eq_ignore_ascii_case("time"));
+            first = false;
+            if header {
+                continue;
+            }
+            match row.and_then(|fields| parse_alarm(&fields)) {
+                Ok(alarm) => import.alarms.push(alarm),
+                Err(message) => import.errors.push(RowError {
+                    line: index + 1,
+                    message,
+                }),
+            }
+        }
+        import
+    }
+
+    /// Reads alarms from a CSV file like `from_csv`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AlarmImport)` if the file was read, even if some rows were skipped.
+    /// * `Err(io::Error)` if it couldn't be read.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<AlarmImport> {
+        Ok(AlarmImport::from_csv(&fs::read_to_string(path)?))
+    }
+}
+
+/// Builds the alarm of one row's `time,label,recurrence` fields.
+fn parse_alarm(fields: &[String]) -> Result<Alarm, &'static str> {
+    let (time, label, recurrence) = match fields {
+        [time, label] => (time.trim(), label, ""),
+        [time, label, recurrence] => (time.trim(), label, recurrence.trim()),
+        _ => return Err("Expected time, label and recurrence columns."),
+    };
+    let schedule = if let Some(period) = recurrence
+        .get(..5)
+        .filter(|word| word.eq_ignore_ascii_case("every"))
+        .map(|_| &recurrence[5..])
+    {
+        if !time.is_empty() {
+            return Err("Repeating alarms take no time of day.");
+        }
+        let period = parse_duration_lenient(period).map_err(|_| "Invalid recurrence period.")?;
+        Schedule::every(period)?
+    } else {
+        let (hour, minute) = time
+            .split_once(':')
+            .and_then(|(hour, minute)| Some((hour.parse().ok()?, minute.parse().ok()?)))
+            .ok_or("Invalid time of day.")?;
+        match recurrence.to_ascii_lowercase().as_str() {
+            "" | "daily" => Schedule::daily(hour, minute)?,
+            "weekdays" => Schedule::weekdays(hour, minute)?,
+            _ => return Err("Unknown recurrence."),
+        }
+    };
+    Ok(Alarm {
+        label: label.trim().to_string(),
+        schedule,
+    })
+}
+
+/// Splits one CSV line into its fields, unquoting quoted ones.
+fn csv_fields(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            (',', false) => fields.push(mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted field.");
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Dates on which a schedule started with `Schedule::start_skipping_holidays` doesn't
+/// fire, read from a list of `YYYY-MM-DD` dates or from the all-day events of an
+/// iCalendar (`.ics`) file.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::schedule::Holidays;
+///
+/// let holidays = Holidays::from_list("# Office closed\n2024-12-25\n2024-12-26\n").unwrap();
+/// assert!(holidays.contains(2024, 12, 25));
+/// assert!(!holidays.contains(2024, 12, 24));
+/// assert_eq!(Holidays::from_list("2024-02-30"), Err("Invalid holiday date."));
+///
+/// let holidays = Holidays::from_ics(
+///     "BEGIN:VCALENDAR\r\n\
+///      BEGIN:VEVENT\r\nSUMMARY:New Year\r\nDTSTART;VALUE=DATE:20250101\r\nEND:VEVENT\r\n\
+///      END:VCALENDAR\r\n",
+/// )
+/// .unwrap();
+/// assert!(holidays.contains(2025, 1, 1));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Holidays {
+    /// Days since 1970-01-01.
+    days: BTreeSet<i64>,
+}
+
+impl Holidays {
+    /// Creates an empty set of holidays.
+    pub fn new() -> Holidays {
+        Holidays::default()
+    }
+
+    /// Adds the date `year`-`month`-`day`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the date exists.
+    /// * `Err("Invalid holiday date.")` otherwise.
+    pub fn insert(&mut self, year: i64, month: u8, day: u8) -> Result<(), &'static str> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Err("Invalid holiday date.");
+        }
+        self.days.insert(days_from_civil(year, month, day));
+        Ok(())
+    }
+
+    /// Whether `year`-`month`-`day` is a holiday.
+    pub fn contains(&self, year: i64, month: u8, day: u8) -> bool {
+        self.days.contains(&days_from_civil(year, month, day))
+    }
+
+    /// Parses one `YYYY-MM-DD` date per line. Blank lines and lines starting with `#`
+    /// are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Holidays)` with every listed date.
+    /// * `Err("Invalid holiday date.")` if a line isn't a valid date.
+    pub fn from_list(list: &str) -> Result<Holidays, &'static str> {
+        let mut holidays = Holidays::new();
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (year, month, day) = split_date(line, "-").ok_or("Invalid holiday date.")?;
+            holidays.insert(year, month, day)?;
+        }
+        Ok(holidays)
+    }
+
+    /// Takes the start date of every event in an iCalendar document, as written and
+    /// regardless of its time zone.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Holidays)` with the date of each event.
+    /// * `Err("Invalid calendar data.")` if the document isn't a `VCALENDAR` or an event's
+    ///   start date can't be understood.
+    pub fn from_ics(ics: &str) -> Result<Holidays, &'static str> {
+        const INVALID: &str = "Invalid calendar data.";
+        let lines = unfold(ics);
+        if !lines
+            .iter()
+            .any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+        {
+            return Err(INVALID);
+        }
+
+        let mut holidays = Holidays::new();
+        let mut in_event = false;
+        for line in &lines {
+            let Some((name, _, value)) = split_property(line) else {
+                continue;
+            };
+            match name.to_ascii_uppercase().as_str() {
+                "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => in_event = true,
+                "END" if value.eq_ignore_ascii_case("VEVENT") => in_event = false,
+                "DTSTART" if in_event => {
+                    let date = value.get(..8).ok_or(INVALID)?;
+                    let (year, month, day) = split_date(date, "").ok_or(INVALID)?;
+                    holidays.insert(year, month, day).map_err(|_| INVALID)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(holidays)
+    }
+
+    /// Reads holidays from a file: an iCalendar document if it contains a `VCALENDAR`,
+    /// a list of dates otherwise.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Holidays)` if the file was read and parsed.
+    /// * `Err(io::Error)` if it couldn't be read, or with `InvalidData` if it isn't valid.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Holidays> {
+        let text = fs::read_to_string(path)?;
+        let holidays = if text.to_ascii_uppercase().contains("BEGIN:VCALENDAR") {
+            Holidays::from_ics(&text)
+        } else {
+            Holidays::from_list(&text)
+        };
+        holidays.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Splits `YYYY{separator}MM{separator}DD` into its numbers.
+fn split_date(date: &str, separator: &str) -> Option<(i64, u8, u8)> {
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let (year, rest) = date.split_at_checked(4)?;
+    let rest = rest.strip_prefix(separator)?;
+    let (month, rest) = rest.split_at_checked(2)?;
+    let day = rest.strip_prefix(separator)?;
+    if !(digits(year) && digits(month) && day.len() == 2 && digits(day)) {
+        return None;
+    }
+    Some((year.parse().ok()?, month.parse().ok()?, day.parse().ok()?))
+}
+
+fn check_time_of_day(hour: u8, minute: u8) -> Result<(), &'static str> {
+    if hour > 23 || minute > 59 {
+        return Err("Invalid time of day.");
+    }
+    Ok(())
+}
+
+/// The first `hour`:`minute` after `local` on a day accepted by `allow_day`.
+fn next_time_of_day(
+    local: i64,
+    hour: u8,
+    minute: u8,
+    allow_day: impl Fn(i64) -> bool,
+) -> Option<i64> {
+    let time_of_day = i64::from(hour) * 3600 + i64::from(minute) * 60;
+    let mut day = local.div_euclid(SECS_PER_DAY);
+    if day * SECS_PER_DAY + time_of_day <= local {
+        day += 1;
+    }
+    // Any run of seven days contains an allowed day for the cadences above.
+    (day..day + 7)
+        .find(|&day| allow_day(day))
+        .and_then(|day| day.checked_mul(SECS_PER_DAY)?.checked_add(time_of_day))
+}
+
+/// The day of the week for a count of days since the epoch, Monday being 0.
+fn weekday(day: i64) -> i64 {
+    // 1970-01-01 was a Thursday.
+    (day + 3).rem_euclid(7)
+}
+
+fn from_unix_seconds(seconds: i64) -> Option<SystemTime> {
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds.unsigned_abs()))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
@@ -0,0 +1,132 @@
+//! Output adapters for hardware clock displays.
+//!
+//! Everything here works on fixed-size arrays without allocating, so hardware
+//! projects can drive seven-segment modules or HD44780 character LCDs
+//! straight from a timer or stopwatch reading.
+
+use crate::format::FixedWidth;
+
+/// Seven-segment bitmasks for the digits 0-9, segment `a` in bit 0 through `g` in bit 6.
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b011_1111, // 0
+    0b000_0110, // 1
+    0b101_1011, // 2
+    0b100_1111, // 3
+    0b110_0110, // 4
+    0b110_1101, // 5
+    0b111_1101, // 6
+    0b000_0111, // 7
+    0b111_1111, // 8
+    0b110_1111, // 9
+];
+
+/// The segment bit for the decimal point, often wired as the clock colon.
+pub const DECIMAL_POINT: u8 = 0b1000_0000;
+
+/// Returns the seven-segment bitmask for `digit`, or `None` if it isn't 0-9.
+///
+/// Segments are in the conventional `a`-`g` order from bit 0 to bit 6; bit 7
+/// (`DECIMAL_POINT`) is left clear.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::segment::digit_segments;
+///
+/// assert_eq!(digit_segments(8), Some(0b0111_1111));
+/// assert_eq!(digit_segments(10), None);
+/// ```
+pub const fn digit_segments(digit: u8) -> Option<u8> {
+    if digit < 10 {
+        Some(DIGIT_SEGMENTS[digit as usize])
+    } else {
+        None
+    }
+}
+
+/// Converts `total_seconds` into six seven-segment bitmasks for an `HH MM SS` display.
+///
+/// The decimal point of the hours and minutes ones digits is lit so it can act
+/// as the separator on modules that lack a dedicated colon.
+///
+/// # Returns
+///
+/// * `Some(masks)` with the hour tens digit first.
+/// * `None` if the time is 100 hours or more and doesn't fit two hour digits.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::segment::{DECIMAL_POINT, digit_segments, hms_segments};
+///
+/// let masks = hms_segments(3723).unwrap(); // 01:02:03
+/// assert_eq!(masks[0], digit_segments(0).unwrap());
+/// assert_eq!(masks[1], digit_segments(1).unwrap() | DECIMAL_POINT);
+/// assert_eq!(masks[5], digit_segments(3).unwrap());
+/// ```
+pub fn hms_segments(total_seconds: u64) -> Option<[u8; 6]> {
+    let hours = total_seconds / 3600;
+    if hours > 99 {
+        return None;
+    }
+    let fields = [hours, (total_seconds % 3600) / 60, total_seconds % 60];
+
+    let mut masks = [0; 6];
+    for (index, field) in fields.iter().enumerate() {
+        masks[index * 2] = DIGIT_SEGMENTS[(field / 10) as usize];
+        masks[index * 2 + 1] = DIGIT_SEGMENTS[(field % 10) as usize];
+        if index < 2 {
+            masks[index * 2 + 1] |= DECIMAL_POINT;
+        }
+    }
+    Some(masks)
+}
+
+/// A single write to an HD44780-compatible controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hd44780Write {
+    /// A byte written with RS low (instruction register).
+    Command(u8),
+    /// A byte written with RS high (data register).
+    Data(u8),
+}
+
+/// The number of writes in a sequence returned by `hd44780_hms`.
+pub const HD44780_HMS_LEN: usize = 1 + FixedWidth::<2>::LEN;
+
+/// Builds the HD44780 write sequence that shows `total_seconds` as `HH:MM:SS`.
+///
+/// The sequence starts with a "set DDRAM address" command for `address`
+/// (`0x00` is the start of the first line and `0x40` of the second on most
+/// modules), followed by the eight characters.
+///
+/// # Returns
+///
+/// * `Some(writes)` ready to be clocked out in order.
+/// * `None` if `address` is outside the 7-bit DDRAM range or the time is 100
+///   hours or more.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::segment::{Hd44780Write, hd44780_hms};
+///
+/// let writes = hd44780_hms(59, 0x40).unwrap();
+/// assert_eq!(writes[0], Hd44780Write::Command(0xC0));
+/// assert_eq!(writes[1], Hd44780Write::Data(b'0'));
+/// assert_eq!(writes[8], Hd44780Write::Data(b'9'));
+/// ```
+pub fn hd44780_hms(total_seconds: u64, address: u8) -> Option<[Hd44780Write; HD44780_HMS_LEN]> {
+    if address > 0x7F {
+        return None;
+    }
+
+    let mut text = [0u8; FixedWidth::<2>::LEN];
+    FixedWidth::<2>::format_into(total_seconds, &mut text).ok()?;
+
+    let mut writes = [Hd44780Write::Command(0x80 | address); HD44780_HMS_LEN];
+    for (write, byte) in writes[1..].iter_mut().zip(text) {
+        *write = Hd44780Write::Data(byte);
+    }
+    Some(writes)
+}
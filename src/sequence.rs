@@ -0,0 +1,372 @@
+//! Several countdowns run back-to-back, such as the steps of a recipe or the
+//! sections of a talk.
+//!
+//! Each step is a full `TimerStruct`, so it keeps its own checkpoints,
+//! announcements and finish actions. A `SequenceController` skips ahead, goes
+//! back and reports progress through the whole sequence while it runs.
+
+use crate::timer::{TimerController, TimerOutcome, TimerStruct};
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// A callback invoked with the index and label of each step as it starts.
+pub type OnStep = Arc<Mutex<Box<dyn FnMut(usize, &str) + Send>>>;
+
+/// How a sequence run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceOutcome {
+    /// Every step ran or was skipped.
+    Finished,
+    /// The run was cancelled through its `SequenceController`.
+    Cancelled {
+        /// The index of the step that was running.
+        step: usize,
+    },
+}
+
+/// Where a sequence run stands, from `SequenceController::progress`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SequenceProgress {
+    /// The index of the current step, equal to `steps` once the sequence is done.
+    pub step: usize,
+    /// The number of steps.
+    pub steps: usize,
+    /// The time counted down so far. Skipped steps count in full.
+    pub elapsed: Duration,
+    /// The combined duration of every step.
+    pub total: Duration,
+}
+
+impl SequenceProgress {
+    /// The fraction of the total time counted down, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total.is_zero() {
+            1.0
+        } else {
+            self.elapsed.as_secs_f64() / self.total.as_secs_f64()
+        }
+    }
+}
+
+/// Labelled timers run one after another.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::{
+///     sequence::{SequenceOutcome, TimerSequence},
+///     time_source::VirtualClock,
+///     timer::TimerBuilder,
+/// };
+/// use std::{
+///     io::sink,
+///     sync::{Arc, Mutex},
+/// };
+///
+/// let step = |seconds| {
+///     TimerBuilder::new(0, 0, seconds)
+///         .clock(VirtualClock::new())
+///         .build()
+///         .unwrap()
+/// };
+/// let started = Arc::new(Mutex::new(Vec::new()));
+/// let log = started.clone();
+/// let recipe = TimerSequence::new()
+///     .then("Sear", step(90))
+///     .then("Rest", step(300))
+///     .on_step(move |_, label| log.lock().unwrap().push(label.to_string()));
+///
+/// assert_eq!(recipe.start(&mut sink()).unwrap(), SequenceOutcome::Finished);
+/// assert_eq!(*started.lock().unwrap(), ["Sear", "Rest"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct TimerSequence {
+    steps: Vec<(String, TimerStruct)>,
+    on_step: Option<OnStep>,
+}
+
+impl fmt::Debug for TimerSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerSequence")
+            .field("steps", &self.steps)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TimerSequence {
+    /// Creates an empty sequence.
+    pub fn new() -> TimerSequence {
+        TimerSequence::default()
+    }
+
+    /// Appends `timer` as a step labelled `label`.
+    pub fn then(mut self, label: impl Into<String>, timer: TimerStruct) -> TimerSequence {
+        self.steps.push((label.into(), timer));
+        self
+    }
+
+    /// Calls `callback` with the index and label of each step as it starts, including
+    /// steps restarted with `SequenceController::previous`.
+    pub fn on_step<F>(mut self, callback: F) -> TimerSequence
+    where
+        F: FnMut(usize, &str) + Send + 'static,
+    {
+        self.on_step = Some(Arc::new(Mutex::new(Box::new(callback))));
+        self
+    }
+
+    /// The number of steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the sequence has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The combined duration of every step.
+    pub fn total(&self) -> Duration {
+        self.steps.iter().map(|(_, timer)| timer.total()).sum()
+    }
+
+    /// Runs every step in order, rendering each to `writer`, and blocks until the last
+    /// one finishes.
+    ///
+    /// On the interactive terminal each step's label is printed on its own line before
+    /// its countdown; other output modes only carry the countdowns.
+    pub fn start<W: Write>(&self, writer: &mut W) -> io::Result<SequenceOutcome> {
+        self.run(writer, &self.controller())
+    }
+
+    /// Runs the sequence on a background thread, returning a controller to skip, go back,
+    /// cancel and query it from any thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::{
+    ///     sequence::{SequenceOutcome, TimerSequence},
+    ///     timer::{TimerStruct, TimerTrait},
+    /// };
+    /// use std::{io::sink, time::Duration};
+    ///
+    /// let talk = TimerSequence::new()
+    ///     .then("Intro", TimerStruct::new(0, 2, 0).unwrap())
+    ///     .then("Demo", TimerStruct::new(0, 10, 0).unwrap())
+    ///     .then("Questions", TimerStruct::new(0, 5, 0).unwrap());
+    /// let (handle, controller) = talk.spawn(sink());
+    ///
+    /// controller.skip();
+    /// let progress = controller.progress();
+    /// assert_eq!(progress.step, 1);
+    /// assert!(progress.elapsed >= Duration::from_secs(120));
+    /// assert_eq!(progress.total, Duration::from_secs(17 * 60));
+    ///
+    /// controller.cancel();
+    /// assert_eq!(handle.join().unwrap().unwrap(), SequenceOutcome::Cancelled { step: 1 });
+    /// ```
+    pub fn spawn<W: Write + Send + 'static>(
+        &self,
+        mut writer: W,
+    ) -> (JoinHandle<io::Result<SequenceOutcome>>, SequenceController) {
+        let controller = self.controller();
+        let sequence = self.clone();
+        let thread_controller = controller.clone();
+        let handle = thread::spawn(move || sequence.run(&mut writer, &thread_controller));
+        (handle, controller)
+    }
+
+    fn controller(&self) -> SequenceController {
+        SequenceController {
+            state: Arc::new(Mutex::new(SequenceState {
+                steps: self
+                    .steps
+                    .iter()
+                    .map(|(label, timer)| (label.clone(), timer.total()))
+                    .collect(),
+                index: 0,
+                current: None,
+                jumped: false,
+                paused: false,
+                cancelled: false,
+            })),
+        }
+    }
+
+    fn run<W: Write>(
+        &self,
+        writer: &mut W,
+        controller: &SequenceController,
+    ) -> io::Result<SequenceOutcome> {
+        loop {
+            let (index, timer_controller) = match controller.begin_step() {
+                Ok(step) => step,
+                Err(outcome) => return Ok(outcome),
+            };
+            let (label, timer) = &self.steps[index];
+            if let Some(on_step) = &self.on_step {
+                (on_step.lock().unwrap())(index, label);
+            }
+            if timer.output.is_tty() {
+                writeln!(writer, "{}", label)?;
+            }
+
+            let outcome = timer.run(writer, &timer_controller)?;
+            controller.end_step(outcome);
+        }
+    }
+}
+
+/// Shared state behind a `SequenceController`.
+#[derive(Debug)]
+struct SequenceState {
+    /// The label and duration of every step.
+    steps: Vec<(String, Duration)>,
+    index: usize,
+    /// The running step's controller, absent between steps.
+    current: Option<TimerController>,
+    /// Set when `skip` or `previous` already moved `index` past the running step.
+    jumped: bool,
+    paused: bool,
+    cancelled: bool,
+}
+
+impl SequenceState {
+    /// Moves to step `index`, stopping the step that is running.
+    fn jump(&mut self, index: usize) {
+        if self.cancelled || self.index >= self.steps.len() {
+            return;
+        }
+        self.index = index;
+        if let Some(current) = self.current.take() {
+            current.cancel();
+            self.jumped = true;
+        }
+    }
+}
+
+/// Skips, goes back, pauses, cancels and queries a sequence started with
+/// `TimerSequence::spawn`.
+///
+/// Clones control the same run, so the controller can be handed to other threads.
+#[derive(Clone, Debug)]
+pub struct SequenceController {
+    state: Arc<Mutex<SequenceState>>,
+}
+
+impl SequenceController {
+    /// Ends the current step early and moves on to the next one, finishing the sequence
+    /// after the last step. The skipped step's finish actions don't run.
+    pub fn skip(&self) {
+        let mut state = self.state.lock().unwrap();
+        let next = state.index + 1;
+        state.jump(next);
+    }
+
+    /// Goes back to the previous step and runs it again from the start. On the first step,
+    /// restarts it.
+    pub fn previous(&self) {
+        let mut state = self.state.lock().unwrap();
+        let previous = state.index.saturating_sub(1);
+        state.jump(previous);
+    }
+
+    /// Pauses the current step. Steps started while paused, e.g. after a `skip`, start
+    /// paused too.
+    pub fn pause(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = true;
+        if let Some(current) = &state.current {
+            current.pause();
+        }
+    }
+
+    /// Resumes a paused sequence.
+    pub fn resume(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = false;
+        if let Some(current) = &state.current {
+            current.resume();
+        }
+    }
+
+    /// Cancels the run. The current step stops at once and no further steps start.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.cancelled = true;
+        if let Some(current) = &state.current {
+            current.cancel();
+        }
+    }
+
+    /// Returns `true` while the sequence is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// The label of the current step, `None` once the sequence is done.
+    pub fn current_label(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.steps.get(state.index).map(|(label, _)| label.clone())
+    }
+
+    /// Where the run stands across the whole sequence.
+    pub fn progress(&self) -> SequenceProgress {
+        let state = self.state.lock().unwrap();
+        let index = state.index.min(state.steps.len());
+        let done: Duration = state.steps[..index]
+            .iter()
+            .map(|(_, duration)| *duration)
+            .sum();
+        let current = match (&state.current, state.steps.get(index)) {
+            (Some(controller), Some((_, duration))) => {
+                duration.saturating_sub(controller.remaining())
+            }
+            _ => Duration::ZERO,
+        };
+        SequenceProgress {
+            step: index,
+            steps: state.steps.len(),
+            elapsed: done + current,
+            total: state.steps.iter().map(|(_, duration)| *duration).sum(),
+        }
+    }
+
+    /// Starts the step at the current index, or returns how the run ended.
+    fn begin_step(&self) -> Result<(usize, TimerController), SequenceOutcome> {
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled {
+            return Err(SequenceOutcome::Cancelled { step: state.index });
+        }
+        let Some((_, duration)) = state.steps.get(state.index) else {
+            return Err(SequenceOutcome::Finished);
+        };
+        let controller =
+            TimerController::new(u32::try_from(duration.as_secs()).unwrap_or(u32::MAX));
+        if state.paused {
+            controller.pause();
+        }
+        state.current = Some(controller.clone());
+        Ok((state.index, controller))
+    }
+
+    /// Moves on once the running step ended, unless `skip` or `previous` already did.
+    fn end_step(&self, outcome: TimerOutcome) {
+        let mut state = self.state.lock().unwrap();
+        state.current = None;
+        if state.cancelled {
+            return;
+        }
+        if state.jumped {
+            state.jumped = false;
+        } else if outcome == TimerOutcome::Finished {
+            state.index += 1;
+        }
+    }
+}
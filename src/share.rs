@@ -0,0 +1,161 @@
+//! Compact, URL-safe codes for sharing a countdown across devices.
+//!
+//! A code carries the countdown's end as a wall-clock time and its label, so
+//! every device that opens it counts down to the same moment whatever its
+//! clock was doing when the code was made. Codes use the base64url alphabet
+//! without padding and fit in a query string or fragment as they are.
+
+use alloc::{string::String, vec::Vec};
+use core::time::Duration;
+
+/// The first byte of every code, bumped if the layout ever changes.
+const VERSION: u8 = 1;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A countdown to a fixed moment, as shared between devices.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::share::SharedCountdown;
+/// use std::time::Duration;
+///
+/// let launch = SharedCountdown::new(Duration::from_secs(1_900_000_000), "Launch 🚀");
+/// let code = launch.encode();
+/// assert!(code.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+///
+/// assert_eq!(SharedCountdown::decode(&code), Ok(launch));
+/// assert!(SharedCountdown::decode("not a code!").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SharedCountdown {
+    /// When the countdown ends, as time since the Unix epoch. Whole seconds are kept.
+    pub end: Duration,
+    /// What the countdown is for.
+    pub label: String,
+}
+
+impl SharedCountdown {
+    /// Creates a countdown ending `end` after the Unix epoch, truncated to whole seconds.
+    pub fn new(end: Duration, label: impl Into<String>) -> SharedCountdown {
+        SharedCountdown {
+            end: Duration::from_secs(end.as_secs()),
+            label: label.into(),
+        }
+    }
+
+    /// Creates a countdown ending at the wall-clock time `end`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SharedCountdown)` for times after the Unix epoch.
+    /// * `Err("End time is before the Unix epoch.")` otherwise.
+    #[cfg(feature = "std")]
+    pub fn at(
+        end: std::time::SystemTime,
+        label: impl Into<String>,
+    ) -> Result<SharedCountdown, &'static str> {
+        let end = end
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|_| "End time is before the Unix epoch.")?;
+        Ok(SharedCountdown::new(end, label))
+    }
+
+    /// The time left at `now`, measured since the Unix epoch like `end`. Zero once over.
+    pub fn remaining(&self, now: Duration) -> Duration {
+        self.end.saturating_sub(now)
+    }
+
+    /// Encodes the countdown as a URL-safe code.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(11 + self.label.len());
+        bytes.push(VERSION);
+        // LEB128, so dates in this century take five bytes rather than eight.
+        let mut end = self.end.as_secs();
+        loop {
+            let byte = (end & 0x7f) as u8;
+            end >>= 7;
+            if end == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+        bytes.extend_from_slice(self.label.as_bytes());
+        encode_base64url(&bytes)
+    }
+
+    /// Decodes a code made by `encode`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SharedCountdown)` for a valid code.
+    /// * `Err(&'static str)` describing why `code` isn't one.
+    pub fn decode(code: &str) -> Result<SharedCountdown, &'static str> {
+        let bytes = decode_base64url(code.trim())?;
+        let (&version, mut rest) = bytes.split_first().ok_or("Share code is empty.")?;
+        if version != VERSION {
+            return Err("Unsupported share code version.");
+        }
+
+        let mut end: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let (&byte, tail) = rest.split_first().ok_or("Share code is truncated.")?;
+            rest = tail;
+            if shift > 63 || (shift == 63 && byte & 0x7e != 0) {
+                return Err("Share code end time is out of range.");
+            }
+            end |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        let label = core::str::from_utf8(rest).map_err(|_| "Share code label isn't UTF-8.")?;
+        Ok(SharedCountdown::new(Duration::from_secs(end), label))
+    }
+}
+
+fn encode_base64url(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | u32::from(byte) << (16 - 8 * index)
+            });
+        // Three bytes make four characters; a shorter final chunk makes one fewer per
+        // missing byte.
+        for index in 0..=chunk.len() {
+            let sextet = (group >> (18 - 6 * index)) & 0x3f;
+            code.push(char::from(ALPHABET[sextet as usize]));
+        }
+    }
+    code
+}
+
+fn decode_base64url(code: &str) -> Result<Vec<u8>, &'static str> {
+    const INVALID: &str = "Share code contains invalid characters.";
+    if code.len() % 4 == 1 {
+        return Err("Share code is truncated.");
+    }
+    let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+    for chunk in code.as_bytes().chunks(4) {
+        let mut group = 0u32;
+        for (index, &character) in chunk.iter().enumerate() {
+            let sextet = ALPHABET
+                .iter()
+                .position(|&letter| letter == character)
+                .ok_or(INVALID)?;
+            group |= (sextet as u32) << (18 - 6 * index);
+        }
+        for index in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * index)) as u8);
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,99 @@
+//! Audible alerts, enabled with the `sound` feature.
+//!
+//! The default chime is the terminal bell. Audio files are handed to the
+//! platform's stock player (`paplay`/`aplay` on Linux and the BSDs, `afplay`
+//! on macOS, `System.Media.SoundPlayer` on Windows), which covers WAV
+//! everywhere and OGG wherever the player supports it.
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// The programs audio files are played with on this platform, in the order they're tried.
+#[cfg(target_os = "macos")]
+pub(crate) const PLAYERS: &[&str] = &["afplay"];
+#[cfg(target_os = "windows")]
+pub(crate) const PLAYERS: &[&str] = &["powershell"];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) const PLAYERS: &[&str] = &["paplay", "aplay"];
+
+/// The sound played when a countdown reaches zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Chime {
+    /// Rings the terminal bell by writing `BEL` to the timer's writer.
+    #[default]
+    Bell,
+    /// Plays an audio file through the platform's audio player.
+    File(PathBuf),
+}
+
+impl Chime {
+    /// Plays the chime.
+    ///
+    /// `Chime::Bell` is written to `writer`. `Chime::File` starts the player in
+    /// the background and returns as soon as it has been spawned.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the bell was written or the player was started.
+    /// * `Err(io::Error)` if writing failed or no player could be started.
+    pub fn play<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Chime::Bell => {
+                write!(writer, "\x07")?;
+                writer.flush()
+            }
+            Chime::File(path) => {
+                let mut last_error =
+                    io::Error::new(io::ErrorKind::NotFound, "no audio player found");
+                for mut command in player_commands(path) {
+                    match command
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .spawn()
+                    {
+                        Ok(mut child) => {
+                            // Reap the player once it's done so it doesn't linger as a zombie.
+                            thread::spawn(move || child.wait());
+                            return Ok(());
+                        }
+                        Err(error) => last_error = error,
+                    }
+                }
+                Err(last_error)
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn player_commands(path: &Path) -> Vec<Command> {
+    let mut afplay = Command::new(PLAYERS[0]);
+    afplay.arg(path);
+    vec![afplay]
+}
+
+#[cfg(target_os = "windows")]
+fn player_commands(path: &Path) -> Vec<Command> {
+    let script = format!(
+        "(New-Object System.Media.SoundPlayer '{}').PlaySync()",
+        path.display().to_string().replace('\'', "''")
+    );
+    let mut powershell = Command::new(PLAYERS[0]);
+    powershell.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    vec![powershell]
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn player_commands(path: &Path) -> Vec<Command> {
+    // PulseAudio's player understands OGG as well; ALSA's is the WAV-only fallback.
+    let mut paplay = Command::new(PLAYERS[0]);
+    paplay.arg(path);
+    let mut aplay = Command::new(PLAYERS[1]);
+    aplay.arg("-q").arg(path);
+    vec![paplay, aplay]
+}
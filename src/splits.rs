@@ -0,0 +1,364 @@
+//! Speedrun split definitions in the LiveSplit (`.lss`) format.
+//!
+//! A `SplitFile` holds the named segments of a run together with the
+//! personal best (PB) split times and best segment ("gold") times. Live
+//! splits are compared against it with an `Attempt`, which reports for each
+//! segment whether the runner is ahead of or behind the PB and whether the
+//! segment was a new gold.
+//!
+//! Only the parts of the format needed for that comparison are read and
+//! written: the game and category names, segment names, the
+//! `Personal Best` real time and the best segment real time.
+
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, time::Duration};
+
+/// A single named segment of a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// The segment name.
+    pub name: String,
+    /// The cumulative personal best time at the end of this segment.
+    pub personal_best: Option<Duration>,
+    /// The fastest time ever achieved for this segment on its own.
+    pub best_segment: Option<Duration>,
+}
+
+/// A set of split definitions, as stored in a LiveSplit `.lss` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitFile {
+    /// The game name.
+    pub game: String,
+    /// The category name (e.g. `Any%`).
+    pub category: String,
+    /// The segments of the run, in order.
+    pub segments: Vec<Segment>,
+}
+
+impl SplitFile {
+    /// Parses the contents of a LiveSplit `.lss` file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SplitFile)` with every `<Segment>` found in the document.
+    /// * `Err(&'static str)` if the document has no segments or a time can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::splits::SplitFile;
+    /// use std::time::Duration;
+    ///
+    /// let lss = r#"<Run><GameName>Celeste</GameName><CategoryName>Any%</CategoryName>
+    ///   <Segments><Segment><Name>Prologue</Name>
+    ///     <SplitTimes><SplitTime name="Personal Best"><RealTime>00:01:05.5000000</RealTime></SplitTime></SplitTimes>
+    ///     <BestSegmentTime><RealTime>00:01:02</RealTime></BestSegmentTime>
+    ///   </Segment></Segments></Run>"#;
+    ///
+    /// let splits = SplitFile::from_lss(lss).unwrap();
+    /// assert_eq!(splits.segments[0].name, "Prologue");
+    /// assert_eq!(splits.segments[0].personal_best, Some(Duration::from_millis(65_500)));
+    /// ```
+    pub fn from_lss(document: &str) -> Result<SplitFile, &'static str> {
+        let mut segments = Vec::new();
+        for (_, block) in elements(document, "Segment") {
+            let personal_best = elements(block, "SplitTime")
+                .find(|(attributes, _)| attributes.contains("name=\"Personal Best\""))
+                .and_then(|(_, split_time)| element(split_time, "RealTime"))
+                .map(parse_time)
+                .transpose()?;
+            let best_segment = element(block, "BestSegmentTime")
+                .and_then(|best| element(best, "RealTime"))
+                .map(parse_time)
+                .transpose()?;
+
+            segments.push(Segment {
+                name: unescape(element(block, "Name").unwrap_or_default()),
+                personal_best,
+                best_segment,
+            });
+        }
+
+        if segments.is_empty() {
+            return Err("Split file contains no segments.");
+        }
+
+        Ok(SplitFile {
+            game: unescape(element(document, "GameName").unwrap_or_default()),
+            category: unescape(element(document, "CategoryName").unwrap_or_default()),
+            segments,
+        })
+    }
+
+    /// Serializes the split definitions as a LiveSplit `.lss` document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::splits::{Segment, SplitFile};
+    /// use std::time::Duration;
+    ///
+    /// let splits = SplitFile {
+    ///     game: "Tetris & Co".into(),
+    ///     category: "40 Lines".into(),
+    ///     segments: vec![Segment {
+    ///         name: "Lines 1-10".into(),
+    ///         personal_best: Some(Duration::from_millis(12_340)),
+    ///         best_segment: None,
+    ///     }],
+    /// };
+    /// assert_eq!(SplitFile::from_lss(&splits.to_lss()).unwrap(), splits);
+    /// ```
+    pub fn to_lss(&self) -> String {
+        let mut document = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        document.push_str("<Run version=\"1.7.0\">\n");
+        document.push_str(&format!("  <GameName>{}</GameName>\n", escape(&self.game)));
+        document.push_str(&format!(
+            "  <CategoryName>{}</CategoryName>\n",
+            escape(&self.category)
+        ));
+        document.push_str("  <Segments>\n");
+        for segment in &self.segments {
+            document.push_str("    <Segment>\n");
+            document.push_str(&format!("      <Name>{}</Name>\n", escape(&segment.name)));
+            document.push_str("      <SplitTimes>\n");
+            match segment.personal_best {
+                Some(time) => document.push_str(&format!(
+                    "        <SplitTime name=\"Personal Best\">\n          <RealTime>{}</RealTime>\n        </SplitTime>\n",
+                    format_time(time)
+                )),
+                None => document.push_str("        <SplitTime name=\"Personal Best\" />\n"),
+            }
+            document.push_str("      </SplitTimes>\n");
+            match segment.best_segment {
+                Some(time) => document.push_str(&format!(
+                    "      <BestSegmentTime>\n        <RealTime>{}</RealTime>\n      </BestSegmentTime>\n",
+                    format_time(time)
+                )),
+                None => document.push_str("      <BestSegmentTime />\n"),
+            }
+            document.push_str("    </Segment>\n");
+        }
+        document.push_str("  </Segments>\n</Run>\n");
+        document
+    }
+}
+
+/// How a live segment compares with the split definitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitStatus {
+    /// The segment beat the best segment time.
+    Gold,
+    /// The cumulative time is ahead of (or equal to) the personal best.
+    Ahead,
+    /// The cumulative time is behind the personal best.
+    Behind,
+    /// There is nothing to compare the segment with.
+    NoComparison,
+}
+
+/// The result of comparing one live split with the split definitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitComparison {
+    /// The indicator to render for the segment. `Gold` takes precedence over ahead/behind.
+    pub status: SplitStatus,
+    /// Live cumulative time minus personal best cumulative time in milliseconds;
+    /// negative when ahead.
+    pub delta_millis: Option<i64>,
+}
+
+impl fmt::Display for SplitComparison {
+    /// Renders the delta as `-0:12.3`/`+0:05.0`, marking golds with a trailing `*`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.delta_millis {
+            Some(delta) => {
+                let sign = if delta < 0 { '-' } else { '+' };
+                let millis = delta.unsigned_abs();
+                write!(
+                    f,
+                    "{}{}:{:02}.{}",
+                    sign,
+                    millis / 60_000,
+                    (millis / 1000) % 60,
+                    (millis % 1000) / 100
+                )?;
+            }
+            None => write!(f, "-")?,
+        }
+        if self.status == SplitStatus::Gold {
+            write!(f, "*")?;
+        }
+        Ok(())
+    }
+}
+
+/// A live run being compared segment by segment against a `SplitFile`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::splits::{Attempt, Segment, SplitFile, SplitStatus};
+/// use std::time::Duration;
+///
+/// let splits = SplitFile {
+///     segments: vec![Segment {
+///         name: "Level 1".into(),
+///         personal_best: Some(Duration::from_secs(90)),
+///         best_segment: Some(Duration::from_secs(85)),
+///     }],
+///     ..SplitFile::default()
+/// };
+///
+/// let mut attempt = Attempt::new(&splits);
+/// let comparison = attempt.split(Duration::from_secs(78)).unwrap();
+/// assert_eq!(comparison.status, SplitStatus::Gold);
+/// assert_eq!(comparison.to_string(), "-0:12.0*");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Attempt<'a> {
+    splits: &'a SplitFile,
+    live_splits: Vec<Duration>,
+}
+
+impl<'a> Attempt<'a> {
+    /// Starts a new attempt against `splits`.
+    pub fn new(splits: &'a SplitFile) -> Attempt<'a> {
+        Attempt {
+            splits,
+            live_splits: Vec::new(),
+        }
+    }
+
+    /// Records the end of the current segment at cumulative time `elapsed`.
+    ///
+    /// Returns `None` once every segment has been split.
+    pub fn split(&mut self, elapsed: Duration) -> Option<SplitComparison> {
+        let segment = self.splits.segments.get(self.live_splits.len())?;
+        let previous = self.live_splits.last().copied().unwrap_or_default();
+        self.live_splits.push(elapsed);
+
+        let segment_time = elapsed.saturating_sub(previous);
+        let delta_millis = segment
+            .personal_best
+            .map(|personal_best| elapsed.as_millis() as i64 - personal_best.as_millis() as i64);
+        let gold = segment.best_segment.is_some_and(|best| segment_time < best);
+
+        let status = match delta_millis {
+            _ if gold => SplitStatus::Gold,
+            Some(delta) if delta <= 0 => SplitStatus::Ahead,
+            Some(_) => SplitStatus::Behind,
+            None => SplitStatus::NoComparison,
+        };
+
+        Some(SplitComparison {
+            status,
+            delta_millis,
+        })
+    }
+
+    /// The cumulative live split times recorded so far.
+    pub fn live_splits(&self) -> &[Duration] {
+        &self.live_splits
+    }
+
+    /// Returns `true` once every segment has been split.
+    pub fn is_finished(&self) -> bool {
+        self.live_splits.len() == self.splits.segments.len()
+    }
+}
+
+/// Iterates over every `<name ...>body</name>` element in `document`, yielding
+/// `(attributes, body)`. Self-closing elements yield an empty body.
+fn elements<'a>(document: &'a str, name: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut rest = document;
+    core::iter::from_fn(move || {
+        loop {
+            let start = rest.find(&open)?;
+            let after_name = &rest[start + open.len()..];
+            // Skip elements whose name merely starts with `name` (e.g. `SplitTimes`).
+            if !after_name.starts_with(['>', ' ', '/', '\t', '\n', '\r']) {
+                rest = after_name;
+                continue;
+            }
+            let tag_end = after_name.find('>')?;
+            let attributes = &after_name[..tag_end];
+            if let Some(attributes) = attributes.strip_suffix('/') {
+                rest = &after_name[tag_end + 1..];
+                return Some((attributes, ""));
+            }
+            let body = &after_name[tag_end + 1..];
+            let end = body.find(&close)?;
+            rest = &body[end + close.len()..];
+            return Some((attributes, &body[..end]));
+        }
+    })
+}
+
+/// Returns the body of the first `<name>...</name>` element in `document`.
+fn element<'a>(document: &'a str, name: &'a str) -> Option<&'a str> {
+    elements(document, name).next().map(|(_, body)| body)
+}
+
+/// Parses a LiveSplit time such as `01:23:45.6780000` or `1.02:03:04`.
+fn parse_time(text: &str) -> Result<Duration, &'static str> {
+    const INVALID: &str = "Invalid split time.";
+
+    let text = text.trim();
+    let (days, clock) = match text.split_once('.') {
+        // A leading `d.` before the first colon is a day count.
+        Some((days, clock)) if !days.contains(':') => {
+            (days.parse::<u64>().map_err(|_| INVALID)?, clock)
+        }
+        _ => (0, text),
+    };
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+
+    let mut parts = clock.split(':');
+    let hours: u64 = parts.next().ok_or(INVALID)?.parse().map_err(|_| INVALID)?;
+    let minutes: u64 = parts.next().ok_or(INVALID)?.parse().map_err(|_| INVALID)?;
+    let seconds: u64 = parts.next().ok_or(INVALID)?.parse().map_err(|_| INVALID)?;
+    if parts.next().is_some() || minutes >= 60 || seconds >= 60 {
+        return Err(INVALID);
+    }
+
+    let mut nanos = 0u32;
+    for (position, digit) in fraction.chars().take(9).enumerate() {
+        let digit = digit.to_digit(10).ok_or(INVALID)?;
+        nanos += digit * 10u32.pow(8 - position as u32);
+    }
+
+    Ok(Duration::new(
+        days * 86_400 + hours * 3600 + minutes * 60 + seconds,
+        nanos,
+    ))
+}
+
+/// Formats a time the way LiveSplit writes it: `HH:MM:SS.fffffff`.
+fn format_time(time: Duration) -> String {
+    let total = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        total / 3600,
+        (total % 3600) / 60,
+        total % 60,
+        time.subsec_nanos() / 100
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,290 @@
+//! Running a command under a time limit, with a countdown while it runs.
+//!
+//! When the limit expires the whole process tree is asked to terminate, then
+//! killed after a grace period. On Unix the command runs in its own process
+//! group which receives `SIGTERM` and later `SIGKILL`; on Windows `taskkill /T`
+//! is used.
+
+use crate::history::Session;
+use crate::interval::{CancelHandle, Interval};
+use crate::output::{OutputMode, Tick};
+use std::{
+    io::{self, Write},
+    process::{Child, Command, ExitStatus},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the child is polled for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How a supervised command ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The command exited on its own within the limit.
+    Exited(ExitStatus),
+    /// The limit expired and the command was terminated; holds its final status.
+    TimedOut(ExitStatus),
+}
+
+impl RunOutcome {
+    /// The command's exit status, however it ended.
+    pub fn status(&self) -> ExitStatus {
+        match *self {
+            RunOutcome::Exited(status) | RunOutcome::TimedOut(status) => status,
+        }
+    }
+}
+
+/// Runs commands with a time limit, showing the remaining time while they run.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(unix)]
+/// # {
+/// use clock_timer::supervise::{RunOutcome, Supervisor};
+/// use std::{io::sink, process::Command, time::Duration};
+///
+/// let outcome = Supervisor::new(Duration::from_millis(200))
+///     .grace(Duration::from_millis(100))
+///     .run(Command::new("sleep").arg("10"), &mut sink())
+///     .unwrap();
+/// assert!(matches!(outcome, RunOutcome::TimedOut(_)));
+///
+/// let outcome = Supervisor::new(Duration::from_secs(10))
+///     .run(&mut Command::new("true"), &mut sink())
+///     .unwrap();
+/// assert!(matches!(outcome, RunOutcome::Exited(status) if status.success()));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Supervisor {
+    limit: Duration,
+    grace: Duration,
+    output: OutputMode,
+}
+
+impl Supervisor {
+    /// Creates a new `Supervisor` allowing commands to run for `limit`.
+    pub fn new(limit: Duration) -> Supervisor {
+        Supervisor {
+            limit,
+            grace: Duration::from_secs(5),
+            output: OutputMode::default(),
+        }
+    }
+
+    /// Sets how long a command may take to exit after being asked to terminate before it
+    /// is killed. Defaults to 5 seconds.
+    pub fn grace(mut self, grace: Duration) -> Supervisor {
+        self.grace = grace;
+        self
+    }
+
+    /// Sets how the countdown is written. Defaults to `OutputMode::Auto`.
+    pub fn output(mut self, output: OutputMode) -> Supervisor {
+        self.output = output;
+        self
+    }
+
+    /// Spawns `command`, renders the countdown to `writer` and waits for the command to end.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RunOutcome)` once the command has exited, on its own or after being terminated.
+    /// * `Err(io::Error)` if the command couldn't be spawned or signalled, or writing failed.
+    pub fn run<W: Write>(&self, command: &mut Command, writer: &mut W) -> io::Result<RunOutcome> {
+        let mut child = spawn_in_new_group(command)?;
+        let started = Instant::now();
+        let total_secs = self.limit.as_secs();
+        let mut rendered = None;
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                self.end_line(writer)?;
+                return Ok(RunOutcome::Exited(status));
+            }
+
+            let elapsed = started.elapsed();
+            if elapsed >= self.limit {
+                self.output.write_tick(
+                    writer,
+                    &Tick {
+                        remaining_secs: 0,
+                        total_secs,
+                        overtime_secs: 0,
+                        last: true,
+                    },
+                )?;
+                let status = self.terminate(&mut child)?;
+                return Ok(RunOutcome::TimedOut(status));
+            }
+
+            // Round up so the display reaches 0 exactly when the limit expires.
+            let remaining_secs = (self.limit - elapsed).as_secs_f64().ceil() as u64;
+            if rendered != Some(remaining_secs) {
+                self.output.write_tick(
+                    writer,
+                    &Tick {
+                        remaining_secs,
+                        total_secs,
+                        overtime_secs: 0,
+                        last: false,
+                    },
+                )?;
+                rendered = Some(remaining_secs);
+            }
+
+            thread::sleep(POLL_INTERVAL.min(self.limit - elapsed));
+        }
+    }
+
+    /// Asks the child's process tree to terminate, then kills it once the grace period is over.
+    fn terminate(&self, child: &mut Child) -> io::Result<ExitStatus> {
+        signal_tree(child, false)?;
+        let deadline = Instant::now() + self.grace;
+        while Instant::now() < deadline {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        signal_tree(child, true)?;
+        child.wait()
+    }
+
+    fn end_line<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.output.is_tty() {
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `command` with a countdown of `limit` on `writer`, terminating it when time runs out.
+///
+/// This is shorthand for `Supervisor::new(limit).run(command, writer)`.
+pub fn run_with_limit<W: Write>(
+    command: &mut Command,
+    limit: Duration,
+    writer: &mut W,
+) -> io::Result<RunOutcome> {
+    Supervisor::new(limit).run(command, writer)
+}
+
+/// Runs `command` to completion and measures how long it took.
+///
+/// The returned `Session` is labelled with the command line (see `command_label`), ready to
+/// be recorded in a `History` whose `statistics_for` tracks the mean and best times.
+///
+/// # Examples
+///
+/// ```no_run
+/// use clock_timer::{history::History, supervise::time_command};
+/// use std::process::Command;
+///
+/// let mut history = History::new();
+/// let (status, session) = time_command(Command::new("cargo").arg("build")).unwrap();
+/// let label = session.label.clone();
+/// history.record(session);
+///
+/// let stats = history.statistics_for(&label);
+/// println!("{} in {:?} (best {:?})", status, stats.total, stats.best);
+/// ```
+pub fn time_command(command: &mut Command) -> io::Result<(ExitStatus, Session)> {
+    let started = SystemTime::now();
+    let clock = Instant::now();
+    let status = command.status()?;
+    let session = Session::new(command_label(command), started, clock.elapsed());
+    Ok((status, session))
+}
+
+/// Runs `command` on every tick of `interval`, passing each exit status to `on_exit`,
+/// like `watch` but on the interval's drift-free schedule.
+///
+/// The command inherits the caller's standard streams. A run that overruns the
+/// period delays the next one rather than overlapping it. Cancel the returned handle
+/// to stop; with `Interval::max_runs` it is cancelled after the last run.
+///
+/// # Examples
+///
+/// ```no_run
+/// use clock_timer::{interval::{Interval, Jitter}, supervise::run_every};
+/// use std::{process::Command, time::Duration};
+///
+/// let interval = Interval::new(Duration::from_secs(5))
+///     .jitter(Jitter::Percent(0.1))
+///     .max_runs(10);
+/// let handle = run_every(interval, Command::new("./check.sh"), |status| {
+///     if !status.is_ok_and(|status| status.success()) {
+///         eprintln!("check failed");
+///     }
+/// });
+/// handle.wait();
+/// ```
+pub fn run_every<F>(interval: Interval, mut command: Command, mut on_exit: F) -> CancelHandle
+where
+    F: FnMut(io::Result<ExitStatus>) + Send + 'static,
+{
+    interval.start(move || on_exit(command.status()))
+}
+
+/// The program and arguments of `command` joined by spaces, e.g. `cargo build`.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::supervise::command_label;
+/// use std::process::Command;
+///
+/// assert_eq!(command_label(Command::new("cargo").args(["build", "--release"])), "cargo build --release");
+/// ```
+pub fn command_label(command: &Command) -> String {
+    let mut label = command.get_program().to_string_lossy().into_owned();
+    for arg in command.get_args() {
+        label.push(' ');
+        label.push_str(&arg.to_string_lossy());
+    }
+    label
+}
+
+#[cfg(unix)]
+fn spawn_in_new_group(command: &mut Command) -> io::Result<Child> {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0).spawn()
+}
+
+#[cfg(not(unix))]
+fn spawn_in_new_group(command: &mut Command) -> io::Result<Child> {
+    command.spawn()
+}
+
+/// Signals the child's process group: `SIGTERM`, or `SIGKILL` when `kill` is set.
+#[cfg(unix)]
+fn signal_tree(child: &Child, kill: bool) -> io::Result<()> {
+    let signal = if kill { libc::SIGKILL } else { libc::SIGTERM };
+    let group = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+    // SAFETY: `kill` has no memory-safety preconditions; the child leads its own group.
+    if unsafe { libc::kill(-group, signal) } == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    // The group is already gone if every process in it has exited.
+    if error.raw_os_error() == Some(libc::ESRCH) {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+/// Ends the child's process tree with `taskkill`, forcefully when `kill` is set.
+#[cfg(not(unix))]
+fn signal_tree(child: &Child, kill: bool) -> io::Result<()> {
+    let mut taskkill = Command::new("taskkill");
+    taskkill.arg("/PID").arg(child.id().to_string()).arg("/T");
+    if kill {
+        taskkill.arg("/F");
+    }
+    taskkill.status().map(|_| ())
+}
@@ -0,0 +1,204 @@
+//! Terminal compatibility for in-place rendering.
+//!
+//! Overwriting a line and redrawing several lines rely on ANSI escape
+//! sequences. Unix terminals understand them out of the box; Windows consoles
+//! only do once virtual terminal (VT) processing is switched on, which
+//! `ansi_supported` does on first use. Consoles that can't enable it (legacy
+//! conhost) fall back to padding with spaces instead of clearing.
+
+use std::{
+    io::{self, Write},
+    sync::OnceLock,
+};
+
+/// Trailing spaces written instead of "clear to end of line" without ANSI support. An
+/// `h:m:s` display shrinks by at most a couple of characters between ticks.
+const FALLBACK_PADDING: usize = 3;
+
+/// Returns `true` if ANSI escape sequences can be written to the terminal.
+///
+/// On Windows the first call enables VT processing on the standard output console; it
+/// returns `false` if that isn't possible. Elsewhere it is always `true`.
+pub fn ansi_supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(enable_virtual_terminal)
+}
+
+/// Writes `text` over the current line and returns the cursor to its start, so the next
+/// write replaces it.
+///
+/// Anything left over from a longer previous line is cleared.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::terminal::write_in_place;
+///
+/// let mut line = Vec::new();
+/// write_in_place(&mut line, "0:0:10").unwrap();
+/// write_in_place(&mut line, "0:0:9").unwrap();
+/// # #[cfg(unix)]
+/// assert_eq!(line, b"0:0:10\x1b[K\r0:0:9\x1b[K\r");
+/// ```
+pub fn write_in_place<W: Write + ?Sized>(writer: &mut W, text: &str) -> io::Result<()> {
+    if ansi_supported() {
+        write!(writer, "{}\x1b[K\r", text)
+    } else {
+        write!(writer, "{}{:2$}\r", text, "", FALLBACK_PADDING)
+    }
+}
+
+/// Wraps `text` in the ANSI colour `code`, an SGR parameter such as `31` for red, or
+/// returns it unchanged without ANSI support.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::terminal::paint;
+///
+/// # #[cfg(unix)]
+/// assert_eq!(paint("0:0:59", "31"), "\x1b[31m0:0:59\x1b[0m");
+/// ```
+pub fn paint(text: &str, code: &str) -> String {
+    if ansi_supported() {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Hides the cursor so it doesn't flicker over a display being redrawn.
+///
+/// Pair it with `show_cursor` before exiting; a hidden cursor outlives the process.
+/// Does nothing without ANSI support.
+pub fn hide_cursor<W: Write + ?Sized>(writer: &mut W) -> io::Result<()> {
+    if ansi_supported() {
+        writer.write_all(b"\x1b[?25l")?;
+    }
+    Ok(())
+}
+
+/// Shows the cursor again after `hide_cursor`. Does nothing without ANSI support.
+pub fn show_cursor<W: Write + ?Sized>(writer: &mut W) -> io::Result<()> {
+    if ansi_supported() {
+        writer.write_all(b"\x1b[?25h")?;
+    }
+    Ok(())
+}
+
+/// The reading direction of a display pairing labels with times, such as a
+/// `StopwatchGroup` or a `WorldClock`.
+///
+/// Most terminals lay text out left to right whatever the script, so a Hebrew or
+/// Arabic label followed by a time reads back to front. `Rtl` puts the label on the
+/// right, aligned to the right edge, and the time on its left.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// The label on the left, then the time.
+    #[default]
+    Ltr,
+    /// The time on the left, then the label aligned to the right edge.
+    Rtl,
+}
+
+impl Direction {
+    /// The direction of the first letter of `text`, `Ltr` if it has none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock_timer::terminal::Direction;
+    ///
+    /// assert_eq!(Direction::of("שיעור 3"), Direction::Rtl);
+    /// assert_eq!(Direction::of("3. درس"), Direction::Rtl);
+    /// assert_eq!(Direction::of("Build"), Direction::Ltr);
+    /// ```
+    pub fn of(text: &str) -> Direction {
+        match text.chars().find(|c| c.is_alphabetic()) {
+            Some(c) if is_rtl(c) => Direction::Rtl,
+            _ => Direction::Ltr,
+        }
+    }
+}
+
+/// Returns `true` for letters of right-to-left scripts: Hebrew, Arabic, Syriac, Thaana,
+/// N'Ko and their presentation forms.
+fn is_rtl(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}'
+    )
+}
+
+/// Wraps `text` in Unicode first-strong isolates when it contains right-to-left letters,
+/// so terminals that do apply the bidi algorithm keep it from reordering the surrounding
+/// digits and separators. Other text is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::terminal::isolate;
+///
+/// assert_eq!(isolate("Tea"), "Tea");
+/// assert_eq!(isolate("شاي"), "\u{2068}شاي\u{2069}");
+/// ```
+pub fn isolate(text: &str) -> String {
+    if text.chars().any(is_rtl) {
+        format!("\u{2068}{}\u{2069}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Lays out `label` and `value` on one line in `direction`, padding the label to `width`
+/// characters so a block of lines stays aligned. The label is bidi-isolated.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::terminal::{Direction, label_line};
+///
+/// assert_eq!(label_line("Tea", 5, "0:2:59", Direction::Ltr), "Tea    0:2:59");
+/// assert_eq!(
+///     label_line("תה", 5, "0:2:59", Direction::Rtl),
+///     "0:2:59     \u{2068}תה\u{2069}"
+/// );
+/// ```
+pub fn label_line(label: &str, width: usize, value: &str, direction: Direction) -> String {
+    let padding = " ".repeat(width.saturating_sub(label.chars().count()));
+    let label = isolate(label);
+    match direction {
+        Direction::Ltr => format!("{}{}  {}", label, padding, value),
+        Direction::Rtl => format!("{}  {}{}", value, padding, label),
+    }
+}
+
+#[cfg(windows)]
+fn enable_virtual_terminal() -> bool {
+    use std::{ffi::c_void, os::windows::io::AsRawHandle};
+
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+    }
+
+    let console = io::stdout().as_raw_handle();
+    let mut mode = 0;
+    // SAFETY: `console` is the process's standard output handle, which stays open, and
+    // `mode` is a valid pointer for the duration of the call.
+    unsafe {
+        if GetConsoleMode(console, &mut mode) == 0 {
+            return false;
+        }
+        mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+            || SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
+
+#[cfg(not(windows))]
+fn enable_virtual_terminal() -> bool {
+    true
+}
@@ -0,0 +1,274 @@
+//! Pluggable clocks for the timer and stopwatch loops.
+//!
+//! By default the loops read the monotonic clock and block the thread between
+//! ticks. Handing them a `Clock` instead lets tests and simulations decide how
+//! time passes: `VirtualClock` advances instantly whenever a loop sleeps, so a
+//! five minute countdown runs in microseconds and always produces the same
+//! ticks. `CalibratedClock` corrects for how late the platform wakes sleeping
+//! threads, measured once per process by `calibration`.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, OnceLock},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// A source of monotonic time that can also wait.
+///
+/// Readings are durations from an arbitrary fixed origin, the same convention
+/// the `engine` module uses.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// The current reading.
+    fn now(&self) -> Duration;
+
+    /// Waits until `duration` has passed on this clock.
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real clock: `Instant` readings and `thread::sleep`.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl SystemClock {
+    /// Creates a clock reading zero now.
+    pub fn new() -> SystemClock {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when slept on or advanced, for deterministic tests.
+///
+/// Clones share the same time, so a test can keep one to inspect or advance
+/// while a timer runs on another.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::time_source::{Clock, VirtualClock};
+/// use std::time::Duration;
+///
+/// let clock = VirtualClock::new();
+/// clock.sleep(Duration::from_secs(3600)); // returns immediately
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(clock.now(), Duration::from_secs(3601));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct VirtualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl VirtualClock {
+    /// Creates a clock reading zero.
+    pub fn new() -> VirtualClock {
+        VirtualClock::default()
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+/// How many sleeps `calibrate` measures.
+const CALIBRATION_SAMPLES: u32 = 10;
+
+/// The sleep `calibrate` asks for each time, short enough to expose the scheduler's
+/// granularity.
+const CALIBRATION_SLEEP: Duration = Duration::from_millis(1);
+
+/// The mean oversleep above which `calibrate` switches to `SleepStrategy::Hybrid`.
+const HYBRID_THRESHOLD: Duration = Duration::from_micros(500);
+
+/// The longest a hybrid sleep spins, however coarse the platform's sleeps are.
+const MAX_SPIN: Duration = Duration::from_millis(20);
+
+/// How `CalibratedClock` waits, chosen by `calibrate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SleepStrategy {
+    /// `thread::sleep` alone, for platforms that wake sleeping threads promptly.
+    Plain,
+    /// `thread::sleep` until `spin` before the deadline, then yields in a loop until it
+    /// passes. Trades some CPU time for accuracy on platforms with coarse sleeps.
+    Hybrid {
+        /// How long before the deadline to stop sleeping.
+        spin: Duration,
+    },
+}
+
+/// How accurately this platform sleeps, from `calibrate` or `calibration`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    /// The number of sleeps measured.
+    pub samples: u32,
+    /// The mean time a sleep overran what was asked for.
+    pub mean_oversleep: Duration,
+    /// The longest overrun.
+    pub max_oversleep: Duration,
+    /// The strategy `CalibratedClock` uses as a result.
+    pub strategy: SleepStrategy,
+}
+
+/// Measures the platform's oversleep now, blocking for a few milliseconds.
+///
+/// Most callers want `calibration`, which measures once and caches the result.
+///
+/// # Returns
+///
+/// * A `Calibration` choosing `SleepStrategy::Hybrid` when sleeps overrun by more than
+///   half a millisecond on average, spinning for the longest overrun seen.
+pub fn calibrate() -> Calibration {
+    let mut total = Duration::ZERO;
+    let mut max_oversleep = Duration::ZERO;
+    for _ in 0..CALIBRATION_SAMPLES {
+        let started = Instant::now();
+        thread::sleep(CALIBRATION_SLEEP);
+        let oversleep = started.elapsed().saturating_sub(CALIBRATION_SLEEP);
+        total += oversleep;
+        max_oversleep = max_oversleep.max(oversleep);
+    }
+    let mean_oversleep = total / CALIBRATION_SAMPLES;
+    let strategy = if mean_oversleep > HYBRID_THRESHOLD {
+        SleepStrategy::Hybrid {
+            spin: max_oversleep.min(MAX_SPIN),
+        }
+    } else {
+        SleepStrategy::Plain
+    };
+    Calibration {
+        samples: CALIBRATION_SAMPLES,
+        mean_oversleep,
+        max_oversleep,
+        strategy,
+    }
+}
+
+/// The platform's sleep accuracy, measured by `calibrate` on the first call and cached
+/// for the rest of the process. Useful in diagnostics and bug reports.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::time_source::{SleepStrategy, calibration};
+///
+/// let measured = calibration();
+/// assert!(measured.max_oversleep >= measured.mean_oversleep);
+/// if let SleepStrategy::Hybrid { spin } = measured.strategy {
+///     println!("spinning for the last {:?} of each sleep", spin);
+/// }
+/// assert_eq!(calibration(), measured); // measured only once
+/// ```
+pub fn calibration() -> Calibration {
+    static CALIBRATION: OnceLock<Calibration> = OnceLock::new();
+    *CALIBRATION.get_or_init(calibrate)
+}
+
+/// The real clock, corrected for the platform's oversleep as measured by `calibration`.
+///
+/// The first one created runs the calibration, blocking for a few milliseconds.
+///
+/// # Examples
+///
+/// ```
+/// use clock_timer::{
+///     time_source::CalibratedClock,
+///     timer::{TimerBuilder, TimerTrait},
+/// };
+/// use std::io::sink;
+///
+/// let timer = TimerBuilder::new(0, 0, 1)
+///     .clock(CalibratedClock::new())
+///     .build()
+///     .unwrap();
+/// timer.start_timer(&mut sink()).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CalibratedClock {
+    clock: SystemClock,
+    strategy: SleepStrategy,
+}
+
+impl Default for CalibratedClock {
+    fn default() -> CalibratedClock {
+        CalibratedClock::new()
+    }
+}
+
+impl CalibratedClock {
+    /// Creates a clock reading zero now, calibrating first if nothing has yet.
+    pub fn new() -> CalibratedClock {
+        let strategy = calibration().strategy;
+        CalibratedClock {
+            clock: SystemClock::new(),
+            strategy,
+        }
+    }
+
+    /// The strategy this clock sleeps with.
+    pub fn strategy(&self) -> SleepStrategy {
+        self.strategy
+    }
+}
+
+impl Clock for CalibratedClock {
+    fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        match self.strategy {
+            SleepStrategy::Plain => thread::sleep(duration),
+            SleepStrategy::Hybrid { spin } => {
+                let deadline = Instant::now() + duration;
+                thread::sleep(duration.saturating_sub(spin));
+                while Instant::now() < deadline {
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+}
+
+/// Whole seconds from the Unix epoch to `time`, negative for earlier times and saturating
+/// outside the range of `i64`.
+pub(crate) fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => i64::try_from(since.as_secs()).unwrap_or(i64::MAX),
+        Err(before) => {
+            let before = before.duration();
+            let seconds = i64::try_from(before.as_secs()).unwrap_or(i64::MAX);
+            // Round towards the past so a fractional second before the epoch counts as -1.
+            -seconds - i64::from(before.subsec_nanos() > 0)
+        }
+    }
+}
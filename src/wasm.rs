@@ -1,7 +1,48 @@
-use crate::timer::{TimerStruct, TimerTrait};
+use crate::{
+    engine::{Easing, StopwatchEngine, format_dhms, format_hms},
+    share::SharedCountdown,
+    timer::{TimerStruct, TimerTrait},
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
 
 use wasm_bindgen::prelude::*;
 
+// Timer functions of the JavaScript global scope. Unlike `web_sys::window()` these
+// exist in browsers, Web Workers, Node and Deno alike. The returned handles are
+// numbers in browsers but objects in Node, so they are kept as `JsValue`. Scheduling
+// and the clock are bound with `catch` so a stripped-down runtime (SSR sandboxes,
+// test environments) surfaces an error instead of aborting the instance.
+#[wasm_bindgen]
+extern "C" {
+    // Fails rather than aborting the instance where the global scope has no `setTimeout`
+    #[wasm_bindgen(catch, js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: &JsValue);
+
+    #[wasm_bindgen(catch, js_name = setInterval)]
+    fn set_interval(handler: &js_sys::Function, timeout: i32) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(handle: &JsValue);
+
+    // Only exists on the main thread of a browser
+    #[wasm_bindgen(catch, js_name = requestAnimationFrame)]
+    fn request_animation_frame(callback: &js_sys::Function) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = cancelAnimationFrame)]
+    fn cancel_animation_frame(handle: &JsValue);
+
+    // Monotonic, sub-millisecond clock that keeps advancing in throttled background tabs
+    #[wasm_bindgen(catch, js_namespace = performance, js_name = now)]
+    fn performance_now() -> Result<f64, JsValue>;
+}
+
 #[cfg(feature = "console_error_panic_hook")]
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -10,10 +51,108 @@ pub fn set_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// Listeners registered with `subscribe` and the snapshot they were last handed.
+///
+/// The snapshot is cached until the next change, so `snapshot()` returns the same object
+/// for as long as nothing changed, which is what `useSyncExternalStore` expects.
+#[derive(Default)]
+struct Subscribers {
+    next_id: Cell<u32>,
+    listeners: RefCell<Vec<(u32, js_sys::Function)>>,
+    snapshot: RefCell<Option<JsValue>>,
+}
+
+impl Subscribers {
+    /// Registers `callback` and returns a function that unregisters it again
+    fn subscribe(self: &Rc<Self>, callback: js_sys::Function) -> js_sys::Function {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+        self.listeners.borrow_mut().push((id, callback));
+
+        // A weak reference keeps a forgotten unsubscribe function from keeping the clock alive
+        let subscribers = Rc::downgrade(self);
+        Closure::<dyn FnMut()>::new(move || {
+            if let Some(subscribers) = subscribers.upgrade() {
+                subscribers
+                    .listeners
+                    .borrow_mut()
+                    .retain(|(existing, _)| *existing != id);
+            }
+        })
+        .into_js_value()
+        .unchecked_into()
+    }
+
+    /// Returns the cached snapshot, building it with `build` after a change
+    fn snapshot(&self, build: impl FnOnce() -> JsValue) -> JsValue {
+        self.snapshot.borrow_mut().get_or_insert_with(build).clone()
+    }
+
+    /// Replaces the cached snapshot with `build()` and hands it to every listener
+    fn notify(&self, build: impl FnOnce() -> JsValue) {
+        let snapshot = build();
+        *self.snapshot.borrow_mut() = Some(snapshot.clone());
+        // Listeners may subscribe or unsubscribe, so none run while the list is borrowed
+        let listeners: Vec<js_sys::Function> = self
+            .listeners
+            .borrow()
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect();
+        for listener in listeners {
+            let _ = listener.call1(&JsValue::NULL, &snapshot);
+        }
+    }
+}
+
+/// Wraps `cause` in an `Error` whose message starts with `context`
+fn js_error(context: &str, cause: &JsValue) -> JsValue {
+    let detail = cause
+        .dyn_ref::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .or_else(|| cause.as_string())
+        .unwrap_or_else(|| format!("{:?}", cause));
+    js_sys::Error::new(&format!("{}: {}", context, detail)).into()
+}
+
+/// Builds a frozen object from `entries`, so snapshots can't be mutated by their readers
+fn frozen_object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = js_sys::Object::new();
+    for (key, value) in entries {
+        let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), value);
+    }
+    js_sys::Object::freeze(&object).into()
+}
+
 // Create a wrapper for TimerStruct that can be used in JavaScript
 #[wasm_bindgen]
 pub struct Timer {
     inner: TimerStruct,
+    run: Rc<TimerRun>,
+    easing: Easing,
+}
+
+/// State shared between a `Timer` and its running timeout chain
+struct TimerRun {
+    duration: u32,
+    // Driven by `advance()` instead of `setTimeout`
+    headless: bool,
+    // Milliseconds passed to `advance()` since the last tick
+    carry_ms: Cell<f64>,
+    // The `performance.now()` reading the running countdown ends at
+    deadline_ms: Cell<f64>,
+    remaining: Cell<u32>,
+    on_tick: RefCell<Option<js_sys::Function>>,
+    // Present while a countdown started by `start()` is in progress
+    pending: RefCell<Option<PendingRun>>,
+    subscribers: Rc<Subscribers>,
+}
+
+/// The pending timeout of a running countdown and the functions settling its Promise
+struct PendingRun {
+    timeout_id: Option<JsValue>,
+    resolve: js_sys::Function,
+    reject: js_sys::Function,
 }
 
 #[wasm_bindgen]
@@ -21,13 +160,50 @@ impl Timer {
     /// Creates a new Timer instance
     #[wasm_bindgen(constructor)]
     pub fn new(hours: u32, minutes: u32, seconds: u32) -> Result<Timer, JsValue> {
-        #[cfg(feature = "console_error_panic_hook")]
-        set_panic_hook();
+        Timer::create(hours, minutes, seconds, false)
+    }
 
-        match TimerStruct::new(hours, minutes, seconds) {
-            Ok(timer) => Ok(Timer { inner: timer }),
-            Err(e) => Err(JsValue::from_str(e)),
+    /// Creates a Timer lasting days, hours, minutes and seconds, for countdowns spanning days.
+    ///
+    /// The days are added to the `hours` component; `formatted` values read like `3d 04:12:09`
+    #[wasm_bindgen(js_name = fromDays)]
+    pub fn from_days(days: u32, hours: u32, minutes: u32, seconds: u32) -> Result<Timer, JsValue> {
+        let hours = days
+            .checked_mul(24)
+            .and_then(|day_hours| day_hours.checked_add(hours))
+            .ok_or_else(|| JsValue::from_str("Duration doesn't fit in u32 seconds."))?;
+        Timer::create(hours, minutes, seconds, false)
+    }
+
+    /// Creates a Timer that never schedules its own ticks.
+    ///
+    /// Time only passes when the host calls `advance(ms)`, e.g. from a game loop or a
+    /// unit test, so countdowns are deterministic and need no fake timers.
+    pub fn headless(hours: u32, minutes: u32, seconds: u32) -> Result<Timer, JsValue> {
+        Timer::create(hours, minutes, seconds, true)
+    }
+
+    /// Moves a headless timer forward by `ms` milliseconds, running every tick that falls
+    /// within them.
+    ///
+    /// Fractions of a second carry over to the next call. Throws if the timer wasn't
+    /// created with `Timer.headless()`.
+    pub fn advance(&self, ms: f64) -> Result<(), JsValue> {
+        if !self.run.headless {
+            return Err(JsValue::from_str(
+                "advance() needs a timer created with Timer.headless()",
+            ));
         }
+        let mut carry_ms = self.run.carry_ms.get() + ms.max(0.0);
+        while carry_ms >= 1000.0 && self.is_running() {
+            carry_ms -= 1000.0;
+            self.run.remaining.set(self.run.remaining.get() - 1);
+            tick_timer(&self.run);
+        }
+        self.run
+            .carry_ms
+            .set(if self.is_running() { carry_ms } else { 0.0 });
+        Ok(())
     }
 
     /// Gets the total duration in seconds
@@ -54,80 +230,326 @@ impl Timer {
         self.inner.seconds
     }
 
-    /// Starts the timer and returns a Promise that resolves when the timer completes
+    /// Gets the remaining time in seconds, updated every second while the timer runs
+    #[wasm_bindgen(getter)]
+    pub fn remaining(&self) -> u32 {
+        self.run.remaining.get()
+    }
+
+    /// Checks if a countdown is currently in progress
+    #[wasm_bindgen(getter)]
+    pub fn is_running(&self) -> bool {
+        self.run.pending.borrow().is_some()
+    }
+
+    /// Gets the share of the countdown remaining for progress bars and rings, from 1 down
+    /// to 0, mapped through the easing set with `set_easing`
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        let total = Duration::from_secs(self.run.duration.into());
+        let remaining = Duration::from_secs(self.run.remaining.get().into());
+        self.easing.apply(remaining, total).as_secs_f64() / total.as_secs_f64()
+    }
+
+    /// Sets how `progress` follows the remaining time: `"linear"` (the default),
+    /// `"quadratic"`, `"cubic"` or `"exponential"`.
+    ///
+    /// The non-linear curves drain slowly at first and quickly at the end, so the last
+    /// stretch takes up more of the bar. Throws for any other name.
+    pub fn set_easing(&mut self, easing: &str) -> Result<(), JsValue> {
+        self.easing = match easing {
+            "linear" => Easing::Linear,
+            "quadratic" => Easing::Quadratic,
+            "cubic" => Easing::Cubic,
+            "exponential" => Easing::Exponential,
+            _ => return Err(JsValue::from_str(&format!("Unknown easing: {}", easing))),
+        };
+        Ok(())
+    }
+
+    /// Sets a callback invoked every second with `(remainingSeconds, formatted)`.
+    ///
+    /// While a tick callback is set, ticks are no longer logged to the console.
+    pub fn set_on_tick(&mut self, callback: js_sys::Function) {
+        *self.run.on_tick.borrow_mut() = Some(callback);
+    }
+
+    /// Registers `callback` to be called with a new `snapshot()` whenever the timer's state
+    /// changes: every tick, and when it starts, finishes or is cancelled.
+    ///
+    /// Returns a function that unsubscribes `callback`. Together with `snapshot()` this
+    /// plugs directly into React's `useSyncExternalStore`:
+    ///
+    /// ```js
+    /// const state = useSyncExternalStore(
+    ///   (onChange) => timer.subscribe(onChange),
+    ///   () => timer.snapshot(),
+    /// );
+    /// ```
+    pub fn subscribe(&self, callback: js_sys::Function) -> js_sys::Function {
+        self.run.subscribers.subscribe(callback)
+    }
+
+    /// Gets a frozen `{ remaining, duration, running, formatted }` object describing the
+    /// timer.
+    ///
+    /// The same object is returned until the state changes.
+    pub fn snapshot(&self) -> JsValue {
+        self.run.subscribers.snapshot(|| timer_snapshot(&self.run))
+    }
+
+    /// Starts the timer and returns a Promise that resolves when the timer completes.
+    ///
+    /// The Promise rejects with `"Timer cancelled"` if `cancel()` is called first, or
+    /// with an `Error` if the next tick can't be scheduled. Starting a timer that is
+    /// already running cancels the previous countdown.
     pub fn start(&self) -> js_sys::Promise {
-        let duration = self.inner.duration;
-
-        // Create a Promise that will resolve when the timer completes
-        let promise = js_sys::Promise::new(&mut |resolve, _reject| {
-            let window = web_sys::window().expect("should have a window in this context");
-
-            // Clone necessary values for the closure
-            let resolve_fn = resolve.clone();
-
-            // Create a recursive setTimeout function to handle the countdown
-            fn create_timeout(
-                window: &web_sys::Window,
-                remaining: u32,
-                callback: &js_sys::Function,
-                resolve_fn: &js_sys::Function,
-            ) {
-                if remaining == 0 {
-                    // Timer completed, resolve the promise with the final time
-                    let _ = resolve_fn.call0(&JsValue::NULL);
-                    return;
+        js_sys::Promise::new(&mut |resolve, reject| self.begin(resolve, reject))
+    }
+
+    /// Starts the timer without a Promise, for callback-style consumers.
+    ///
+    /// `on_tick` replaces any callback set with `set_on_tick` and is invoked every second
+    /// with `(remainingSeconds, formatted)`. `on_finish` is invoked once with `null` when
+    /// the timer completes, or with the reason it didn't: `"Timer cancelled"` or an
+    /// `Error` if the next tick can't be scheduled.
+    pub fn start_with_callback(&mut self, on_tick: js_sys::Function, on_finish: js_sys::Function) {
+        self.set_on_tick(on_tick);
+        let resolve = on_finish.bind1(&JsValue::NULL, &JsValue::NULL);
+        self.begin(resolve, on_finish);
+    }
+
+    /// Cancels a running countdown, clearing its pending timeout and rejecting its Promise.
+    ///
+    /// Returns `false` if the timer wasn't running.
+    pub fn cancel(&self) -> bool {
+        let pending = self.run.pending.borrow_mut().take();
+        match pending {
+            Some(pending) => {
+                if let Some(timeout_id) = pending.timeout_id {
+                    clear_timeout(&timeout_id);
                 }
+                self.run.subscribers.notify(|| timer_snapshot(&self.run));
+                let _ = pending
+                    .reject
+                    .call1(&JsValue::NULL, &JsValue::from_str("Timer cancelled"));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Timer {
+    /// Builds a timer that ticks on its own, or through `advance()` when `headless`
+    fn create(hours: u32, minutes: u32, seconds: u32, headless: bool) -> Result<Timer, JsValue> {
+        #[cfg(feature = "console_error_panic_hook")]
+        set_panic_hook();
 
-                // Calculate display components
-                let hours = remaining / 3600;
-                let minutes = (remaining % 3600) / 60;
-                let seconds = remaining % 60;
+        match TimerStruct::new(hours, minutes, seconds) {
+            Ok(timer) => Ok(Timer {
+                run: Rc::new(TimerRun {
+                    duration: timer.duration,
+                    headless,
+                    carry_ms: Cell::new(0.0),
+                    deadline_ms: Cell::new(0.0),
+                    remaining: Cell::new(timer.duration),
+                    on_tick: RefCell::new(None),
+                    pending: RefCell::new(None),
+                    subscribers: Rc::default(),
+                }),
+                inner: timer,
+                easing: Easing::Linear,
+            }),
+            Err(e) => Err(JsValue::from_str(e)),
+        }
+    }
 
-                // Log current time to console
-                web_sys::console::log_1(&JsValue::from_str(&format!(
-                    "Timer: {}:{}:{}",
-                    hours, minutes, seconds
-                )));
+    /// Cancels any running countdown and starts a new one settled through `resolve` and
+    /// `reject`
+    fn begin(&self, resolve: js_sys::Function, reject: js_sys::Function) {
+        self.cancel();
+        self.run.remaining.set(self.inner.duration);
+        self.run.carry_ms.set(0.0);
+        self.run
+            .deadline_ms
+            .set(now_ms() + f64::from(self.inner.duration) * 1000.0);
+        *self.run.pending.borrow_mut() = Some(PendingRun {
+            timeout_id: None,
+            resolve,
+            reject,
+        });
+        tick_timer(&self.run);
+    }
+}
+
+/// Describes a timer's state for `Timer::snapshot`
+fn timer_snapshot(run: &TimerRun) -> JsValue {
+    let remaining = run.remaining.get();
+    frozen_object(&[
+        ("remaining", remaining.into()),
+        ("duration", run.duration.into()),
+        ("running", run.pending.borrow().is_some().into()),
+        ("formatted", format_dhms(remaining.into()).into()),
+    ])
+}
 
-                // Create closure for the next timeout
-                let window_clone = window.clone();
-                let callback_clone = callback.clone();
-                let resolve_clone = resolve_fn.clone();
-                let next_remaining = remaining - 1;
-
-                let next_callback = Closure::once_into_js(move || {
-                    create_timeout(
-                        &window_clone,
-                        next_remaining,
-                        &callback_clone,
-                        &resolve_clone,
-                    );
-                });
-
-                // Set timeout for 1 second
-                let _ = window
-                    .set_timeout_with_callback_and_timeout_and_arguments_0(
-                        next_callback.as_ref().unchecked_ref(),
-                        1000,
-                    )
-                    .expect("failed to set timeout");
+/// Reports the current remaining time and schedules the next second of a countdown
+fn tick_timer(run: &Rc<TimerRun>) {
+    let remaining = run.remaining.get();
+    let formatted = format_dhms(remaining.into());
+
+    // A finished countdown is no longer running by the time subscribers see it
+    let finished = if remaining == 0 {
+        run.pending.borrow_mut().take()
+    } else {
+        None
+    };
+    run.subscribers.notify(|| timer_snapshot(run));
+
+    match run.on_tick.borrow().as_ref() {
+        Some(callback) => {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(remaining),
+                &JsValue::from_str(&formatted),
+            );
+        }
+        None => {
+            if remaining > 0 {
+                // Log current time to console
+                web_sys::console::log_1(&JsValue::from_str(&format!("Timer: {}", formatted)));
             }
+        }
+    }
 
-            // Start the timeout chain
-            let callback = js_sys::Function::new_no_args("");
-            create_timeout(&window, duration, &callback, &resolve_fn);
-        });
+    if remaining == 0 {
+        // Timer completed, resolve the promise
+        if let Some(pending) = finished {
+            let _ = pending.resolve.call0(&JsValue::NULL);
+        }
+        return;
+    }
+
+    // The tick callback may have cancelled the countdown
+    if run.pending.borrow().is_none() {
+        return;
+    }
+
+    // Headless timers wait for the host to call `advance()`
+    if run.headless {
+        return;
+    }
 
-        promise
+    // The remaining time is read off the clock, so a timeout the browser delays (e.g. in a
+    // background tab) skips the seconds it missed instead of stretching the countdown
+    let next_run = run.clone();
+    let next_callback = Closure::once_into_js(move || {
+        // A timeout firing a hair early still moves the countdown on by a second
+        let remaining =
+            remaining_secs(next_run.deadline_ms.get(), now_ms()).min(next_run.remaining.get() - 1);
+        next_run.remaining.set(remaining);
+        tick_timer(&next_run);
+    });
+
+    // Wake up once the next whole second has passed
+    let next_second = run.deadline_ms.get() - f64::from(remaining - 1) * 1000.0;
+    let delay = (next_second - now_ms()).ceil().max(0.0) as i32;
+    match set_timeout(next_callback.unchecked_ref(), delay) {
+        Ok(timeout_id) => {
+            if let Some(pending) = run.pending.borrow_mut().as_mut() {
+                pending.timeout_id = Some(timeout_id);
+            }
+        }
+        Err(cause) => {
+            let pending = run.pending.borrow_mut().take();
+            run.subscribers.notify(|| timer_snapshot(run));
+            if let Some(pending) = pending {
+                let error = js_error("Couldn't schedule the next timer tick", &cause);
+                let _ = pending.reject.call1(&JsValue::NULL, &error);
+            }
+        }
     }
 }
 
 // Create a wrapper for StopwatchStruct that can be used in JavaScript
 #[wasm_bindgen]
 pub struct Stopwatch {
-    current_time: u32,
-    running: bool,
-    interval_id: Option<i32>,
+    // Shared with the interval closure, which reads the elapsed time on every tick
+    engine: Rc<Cell<StopwatchEngine>>,
+    interval_id: Option<JsValue>,
+    // Created on the first start and reused by every later one
+    interval_callback: Option<Closure<dyn FnMut()>>,
+    // Shared with the interval closure so callbacks can be swapped while running
+    on_tick: Rc<RefCell<Option<js_sys::Function>>>,
+    on_stop: Option<js_sys::Function>,
+    subscribers: Rc<Subscribers>,
+    // The clock moved by `advance()`, present for headless stopwatches
+    manual_now: Option<Cell<Duration>>,
+    count_frames: bool,
+    frames: Rc<FrameCounter>,
+}
+
+/// Counts animation frames through a `requestAnimationFrame` chain while a stopwatch runs
+#[derive(Default)]
+struct FrameCounter {
+    frames: Cell<u32>,
+    // Present while a frame is requested
+    request_id: RefCell<Option<JsValue>>,
+    // Created on first use and reused for every frame
+    callback: RefCell<Option<Closure<dyn FnMut()>>>,
+}
+
+impl FrameCounter {
+    /// Starts counting from the next frame
+    fn start(self: &Rc<Self>) -> Result<(), JsValue> {
+        if self.request_id.borrow().is_some() {
+            return Ok(());
+        }
+        if self.callback.borrow().is_none() {
+            // A weak reference keeps the closure from keeping its own counter alive
+            let counter = Rc::downgrade(self);
+            *self.callback.borrow_mut() = Some(Closure::new(move || {
+                if let Some(counter) = counter.upgrade() {
+                    counter.frames.set(counter.frames.get() + 1);
+                    *counter.request_id.borrow_mut() = None;
+                    let _ = counter.request();
+                }
+            }));
+        }
+        self.request()
+    }
+
+    /// Requests the next frame
+    fn request(&self) -> Result<(), JsValue> {
+        let callback = self.callback.borrow();
+        if let Some(callback) = callback.as_ref() {
+            let request_id = request_animation_frame(callback.as_ref().unchecked_ref())?;
+            *self.request_id.borrow_mut() = Some(request_id);
+        }
+        Ok(())
+    }
+
+    /// Stops counting, keeping the frames counted so far
+    fn stop(&self) {
+        if let Some(request_id) = self.request_id.borrow_mut().take() {
+            cancel_animation_frame(&request_id);
+        }
+    }
+}
+
+/// Reads `performance.now()` as the monotonic clock driving the shared stopwatch engine.
+///
+/// Elapsed time is derived from clock readings rather than from counting interval firings,
+/// which browsers delay arbitrarily in background tabs.
+fn now() -> Duration {
+    Duration::from_secs_f64(now_ms() / 1000.0)
+}
+
+/// Milliseconds from `performance.now()`, falling back to `Date.now()` in runtimes
+/// without the Performance API
+fn now_ms() -> f64 {
+    performance_now().unwrap_or_else(|_| js_sys::Date::now())
 }
 
 #[wasm_bindgen]
@@ -139,90 +561,599 @@ impl Stopwatch {
         set_panic_hook();
 
         Stopwatch {
-            current_time: 0,
-            running: false,
+            engine: Rc::new(Cell::new(StopwatchEngine::new())),
             interval_id: None,
+            interval_callback: None,
+            on_tick: Rc::new(RefCell::new(None)),
+            on_stop: None,
+            subscribers: Rc::default(),
+            manual_now: None,
+            count_frames: false,
+            frames: Rc::default(),
+        }
+    }
+
+    /// Creates a Stopwatch that never schedules its own ticks.
+    ///
+    /// Time only passes when the host calls `advance(ms)`, e.g. from a game loop or a
+    /// unit test, so readings are deterministic and need no fake timers.
+    pub fn headless() -> Stopwatch {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.manual_now = Some(Cell::new(Duration::ZERO));
+        stopwatch
+    }
+
+    /// Moves a headless stopwatch's clock forward by `ms` milliseconds, invoking the tick
+    /// callback once for every whole second a running stopwatch passes.
+    ///
+    /// Throws if the stopwatch wasn't created with `Stopwatch.headless()`.
+    pub fn advance(&self, ms: f64) -> Result<(), JsValue> {
+        let Some(manual_now) = &self.manual_now else {
+            return Err(JsValue::from_str(
+                "advance() needs a stopwatch created with Stopwatch.headless()",
+            ));
+        };
+        let before = self.elapsed_secs();
+        let step = Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).unwrap_or(Duration::MAX);
+        manual_now.set(manual_now.get().saturating_add(step));
+
+        let after = self.elapsed_secs();
+        if after != before {
+            for elapsed in before + 1..=after {
+                report_stopwatch_tick(&self.on_tick, elapsed);
+            }
+            let (engine, now) = (self.engine.get(), self.now());
+            self.subscribers.notify(|| stopwatch_snapshot(engine, now));
+        }
+        Ok(())
+    }
+
+    /// Also counts the animation frames rendered while the stopwatch runs, for timing
+    /// animations and screen recordings in frames as well as wall time.
+    ///
+    /// Frames are counted with `requestAnimationFrame`, so `start()` throws where it isn't
+    /// available, e.g. in Web Workers. Headless stopwatches don't count frames.
+    pub fn set_count_frames(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.count_frames = enabled;
+        if !enabled {
+            self.frames.stop();
+        } else if self.is_running() && self.manual_now.is_none() {
+            self.start_frames()?;
+        }
+        Ok(())
+    }
+
+    /// Gets the number of animation frames counted while running, see `set_count_frames`
+    #[wasm_bindgen(getter)]
+    pub fn frames(&self) -> u32 {
+        self.frames.frames.get()
+    }
+
+    /// Gets the average frames per second over the elapsed time, 0 before any time passed
+    #[wasm_bindgen(getter)]
+    pub fn frame_rate(&self) -> f64 {
+        let elapsed = self.engine.get().elapsed(self.now()).as_secs_f64();
+        if elapsed > 0.0 {
+            f64::from(self.frames()) / elapsed
+        } else {
+            0.0
         }
     }
 
     /// Gets the current elapsed time in seconds
     #[wasm_bindgen(getter)]
     pub fn current_time(&self) -> u32 {
-        self.current_time
+        self.elapsed_secs()
+    }
+
+    /// Gets the current elapsed time in milliseconds, with sub-millisecond precision
+    #[wasm_bindgen(getter)]
+    pub fn current_time_ms(&self) -> f64 {
+        self.engine.get().elapsed(self.now()).as_secs_f64() * 1000.0
     }
 
     /// Checks if the stopwatch is currently running
     #[wasm_bindgen(getter)]
     pub fn is_running(&self) -> bool {
-        self.running
+        self.engine.get().is_running()
+    }
+
+    /// Sets a callback invoked every second with `(elapsedSeconds, formatted)`.
+    ///
+    /// While a tick callback is set, ticks are no longer logged to the console.
+    pub fn set_on_tick(&mut self, callback: js_sys::Function) {
+        *self.on_tick.borrow_mut() = Some(callback);
+    }
+
+    /// Sets a callback invoked by `stop()` with `(elapsedSeconds, formatted)`.
+    pub fn set_on_stop(&mut self, callback: js_sys::Function) {
+        self.on_stop = Some(callback);
+    }
+
+    /// Registers `callback` to be called with a new `snapshot()` whenever the stopwatch's
+    /// state changes: every tick, and when it starts, stops, pauses or is reset.
+    ///
+    /// Returns a function that unsubscribes `callback`, as `useSyncExternalStore` expects.
+    pub fn subscribe(&self, callback: js_sys::Function) -> js_sys::Function {
+        self.subscribers.subscribe(callback)
+    }
+
+    /// Gets a frozen `{ elapsed, running, formatted }` object describing the stopwatch as
+    /// of its last change.
+    ///
+    /// The same object is returned until the state changes, so `elapsed` advances once a
+    /// tick rather than on every read.
+    pub fn snapshot(&self) -> JsValue {
+        self.subscribers
+            .snapshot(|| stopwatch_snapshot(self.engine.get(), self.now()))
     }
 
-    /// Starts the stopwatch
+    /// Starts the stopwatch.
+    ///
+    /// Throws an `Error`, leaving the stopwatch stopped, if the runtime has no
+    /// `setInterval`.
     pub fn start(&mut self) -> Result<(), JsValue> {
-        if self.running {
+        if self.is_running() {
             return Ok(());
         }
 
-        self.running = true;
-        let window = web_sys::window().expect("should have a window in this context");
+        if self.manual_now.is_none() {
+            if self.count_frames {
+                self.start_frames()?;
+            }
+            if let Err(error) = self.start_interval() {
+                self.frames.stop();
+                return Err(error);
+            }
+        }
+
+        let now = self.now();
+        let mut engine = self.engine.get();
+        engine.start(now);
+        self.engine.set(engine);
+        self.subscribers.notify(|| stopwatch_snapshot(engine, now));
+
+        Ok(())
+    }
+
+    /// Stops the stopwatch and returns the elapsed time
+    pub fn stop(&mut self) -> u32 {
+        if !self.is_running() {
+            return self.elapsed_secs();
+        }
+
+        self.halt();
+
+        let elapsed = self.elapsed_secs();
+        if let Some(callback) = &self.on_stop {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(elapsed),
+                &JsValue::from_str(&format_hms(elapsed.into())),
+            );
+        }
 
-        // Create closure for the interval
-        let closure = {
-            let mut time = self.current_time;
+        elapsed
+    }
 
-            Closure::wrap(Box::new(move || {
-                time += 1;
+    /// Pauses the stopwatch, keeping the elapsed time without invoking the stop callback
+    pub fn pause(&mut self) {
+        if self.is_running() {
+            self.halt();
+        }
+    }
 
-                // Calculate display components
-                let hours = time / 3600;
-                let minutes = (time % 3600) / 60;
-                let seconds = time % 60;
+    /// Resumes a paused stopwatch
+    pub fn resume(&mut self) -> Result<(), JsValue> {
+        self.start()
+    }
 
-                // Log current time to console
-                web_sys::console::log_1(&JsValue::from_str(&format!(
-                    "Stopwatch: {}:{}:{}",
-                    hours, minutes, seconds
-                )));
-            }) as Box<dyn FnMut()>)
+    /// Serializes the stopwatch state to JSON, e.g. to keep it in `localStorage`.
+    ///
+    /// A running stopwatch stores the wall-clock time its current run started, so
+    /// `from_json` can account for the time that passed in between.
+    pub fn to_json(&self) -> String {
+        let engine = self.engine.get();
+        let started_at = match engine.started_at() {
+            Some(started_at) => {
+                let run_ms = self.now().saturating_sub(started_at).as_secs_f64() * 1000.0;
+                (js_sys::Date::now() - run_ms).to_string()
+            }
+            None => String::from("null"),
         };
+        format!(
+            "{{\"accumulated_ms\":{},\"started_at\":{}}}",
+            engine.accumulated().as_secs_f64() * 1000.0,
+            started_at
+        )
+    }
+
+    /// Restores a stopwatch saved with `to_json`.
+    ///
+    /// A stopwatch that was running when saved keeps running, including the time that
+    /// passed since it was saved. Callbacks aren't persisted and need to be set again.
+    /// Throws for state that isn't valid JSON or holds non-finite or out-of-range times.
+    pub fn from_json(json: &str) -> Result<Stopwatch, JsValue> {
+        let state = js_sys::JSON::parse(json)?;
+        let accumulated_ms = js_sys::Reflect::get(&state, &JsValue::from_str("accumulated_ms"))?
+            .as_f64()
+            .ok_or_else(|| JsValue::from_str("Invalid stopwatch state: missing accumulated_ms"))?;
+        if !accumulated_ms.is_finite() {
+            return Err(JsValue::from_str(
+                "Invalid stopwatch state: accumulated_ms isn't finite",
+            ));
+        }
+        let started_at = js_sys::Reflect::get(&state, &JsValue::from_str("started_at"))?.as_f64();
+        if started_at.is_some_and(|started_at| !started_at.is_finite()) {
+            return Err(JsValue::from_str(
+                "Invalid stopwatch state: started_at isn't finite",
+            ));
+        }
+
+        // Time that passed while the page was closed counts towards a running stopwatch
+        let since_start = started_at.map_or(0.0, |started_at| {
+            (js_sys::Date::now() - started_at).max(0.0)
+        });
+        let elapsed = Duration::try_from_secs_f64((accumulated_ms.max(0.0) + since_start) / 1000.0)
+            .map_err(|_| JsValue::from_str("Invalid stopwatch state: elapsed time too long"))?;
+
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.engine.set(StopwatchEngine::from_elapsed(elapsed));
+        if started_at.is_some() {
+            stopwatch.start()?;
+        }
+
+        Ok(stopwatch)
+    }
+
+    /// Resets the stopwatch to zero
+    pub fn reset(&mut self) {
+        self.stop();
+        self.engine.set(StopwatchEngine::new());
+        self.frames.frames.set(0);
+        let now = self.now();
+        self.subscribers
+            .notify(|| stopwatch_snapshot(StopwatchEngine::new(), now));
+    }
+}
+
+impl Stopwatch {
+    fn elapsed_secs(&self) -> u32 {
+        self.engine.get().elapsed(self.now()).as_secs() as u32
+    }
+
+    /// Reads the manual clock of a headless stopwatch, `performance.now()` otherwise
+    fn now(&self) -> Duration {
+        self.manual_now.as_ref().map_or_else(now, Cell::get)
+    }
+
+    /// Starts the frame counter
+    fn start_frames(&self) -> Result<(), JsValue> {
+        self.frames
+            .start()
+            .map_err(|cause| js_error("Couldn't count animation frames", &cause))
+    }
+
+    /// Installs the interval reporting a tick every second
+    fn start_interval(&mut self) -> Result<(), JsValue> {
+        let closure = self.interval_callback.get_or_insert_with(|| {
+            let engine = self.engine.clone();
+            let on_tick = self.on_tick.clone();
+            let subscribers = self.subscribers.clone();
+
+            Closure::new(move || {
+                let now = now();
+                let elapsed = engine.get().elapsed(now).as_secs() as u32;
+                subscribers.notify(|| stopwatch_snapshot(engine.get(), now));
+                report_stopwatch_tick(&on_tick, elapsed);
+            })
+        });
 
         // Set interval for 1 second
-        let interval_id = window
-            .set_interval_with_callback_and_timeout_and_arguments_0(
-                closure.as_ref().unchecked_ref(),
-                1000,
-            )
-            .expect("failed to set interval");
+        let interval_id = set_interval(closure.as_ref().unchecked_ref(), 1000)
+            .map_err(|cause| js_error("Couldn't start the stopwatch", &cause))?;
 
         // Store the interval ID so we can clear it later
         self.interval_id = Some(interval_id);
 
-        // Forget the closure so it remains valid for the interval
-        closure.forget();
-
         Ok(())
     }
 
-    /// Stops the stopwatch and returns the elapsed time
-    pub fn stop(&mut self) -> u32 {
-        if !self.running {
-            return self.current_time;
+    /// Halts the running interval and folds the current run into the accumulated time
+    fn halt(&mut self) {
+        let now = self.now();
+        let mut engine = self.engine.get();
+        engine.stop(now);
+        self.engine.set(engine);
+
+        // Clear the interval if it exists
+        if let Some(interval_id) = self.interval_id.take() {
+            clear_interval(&interval_id);
+        }
+        self.frames.stop();
+        self.subscribers.notify(|| stopwatch_snapshot(engine, now));
+    }
+}
+
+impl Drop for Stopwatch {
+    // The interval and frame callbacks are dropped with the stopwatch, so they must not fire
+    fn drop(&mut self) {
+        if let Some(interval_id) = self.interval_id.take() {
+            clear_interval(&interval_id);
         }
+        self.frames.stop();
+    }
+}
 
-        self.running = false;
+/// Hands `elapsed` to the tick callback, or logs it to the console without one
+fn report_stopwatch_tick(on_tick: &RefCell<Option<js_sys::Function>>, elapsed: u32) {
+    let formatted = format_hms(elapsed.into());
+    match on_tick.borrow().as_ref() {
+        Some(callback) => {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(elapsed),
+                &JsValue::from_str(&formatted),
+            );
+        }
+        None => {
+            // Log current time to console
+            web_sys::console::log_1(&JsValue::from_str(&format!("Stopwatch: {}", formatted)));
+        }
+    }
+}
 
-        // Clear the interval if it exists
+/// Describes a stopwatch's state at `now` for `Stopwatch::snapshot`
+fn stopwatch_snapshot(engine: StopwatchEngine, now: Duration) -> JsValue {
+    let elapsed = engine.elapsed(now).as_secs();
+    frozen_object(&[
+        ("elapsed", (elapsed as u32).into()),
+        ("running", engine.is_running().into()),
+        ("formatted", format_hms(elapsed).into()),
+    ])
+}
+
+/// Many named countdowns sharing one interval and one aggregated tick event.
+///
+/// Each timer counts towards a deadline on the monotonic clock, so every tick
+/// reports the true remaining time even when the browser delays the interval.
+#[wasm_bindgen]
+pub struct TimerPool {
+    state: Rc<RefCell<PoolState>>,
+    // Runs the shared interval; created on first use and reused whenever the pool restarts
+    interval_callback: RefCell<Option<Closure<dyn FnMut()>>>,
+}
+
+/// State shared between a `TimerPool` and its interval closure
+#[derive(Default)]
+struct PoolState {
+    // Name and deadline (a `performance.now()` reading) of each running timer, in creation order
+    timers: Vec<(String, f64)>,
+    // Present while at least one timer is running
+    interval_id: Option<JsValue>,
+    on_tick: Option<js_sys::Function>,
+    on_finish: Option<js_sys::Function>,
+}
+
+impl Default for TimerPool {
+    fn default() -> TimerPool {
+        TimerPool::new()
+    }
+}
+
+#[wasm_bindgen]
+impl TimerPool {
+    /// Creates an empty pool
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TimerPool {
+        #[cfg(feature = "console_error_panic_hook")]
+        set_panic_hook();
+
+        TimerPool {
+            state: Rc::new(RefCell::new(PoolState::default())),
+            interval_callback: RefCell::new(None),
+        }
+    }
+
+    /// Starts a countdown named `name`, replacing any running timer of the same name.
+    ///
+    /// Throws an `Error` if the runtime has no `setInterval`.
+    pub fn create(
+        &self,
+        name: &str,
+        hours: u32,
+        minutes: u32,
+        seconds: u32,
+    ) -> Result<(), JsValue> {
+        let timer = TimerStruct::new(hours, minutes, seconds).map_err(JsValue::from_str)?;
+        let deadline = now_ms() + f64::from(timer.duration) * 1000.0;
+
+        let start_interval = {
+            let mut state = self.state.borrow_mut();
+            state.timers.retain(|(existing, _)| existing != name);
+            state.timers.push((name.to_string(), deadline));
+            state.interval_id.is_none()
+        };
+        if start_interval {
+            let mut callback = self.interval_callback.borrow_mut();
+            let closure = callback.get_or_insert_with(|| {
+                let state = self.state.clone();
+                Closure::new(move || tick_pool(&state))
+            });
+            let interval_id = match set_interval(closure.as_ref().unchecked_ref(), 1000) {
+                Ok(interval_id) => interval_id,
+                Err(cause) => {
+                    self.state
+                        .borrow_mut()
+                        .timers
+                        .retain(|(existing, _)| existing != name);
+                    return Err(js_error("Couldn't start the timer pool", &cause));
+                }
+            };
+            self.state.borrow_mut().interval_id = Some(interval_id);
+        }
+        Ok(())
+    }
+
+    /// Cancels the timer named `name` without firing the finish callback.
+    ///
+    /// Returns `false` if no such timer was running.
+    pub fn cancel(&self, name: &str) -> bool {
+        let mut state = self.state.borrow_mut();
+        let before = state.timers.len();
+        state.timers.retain(|(existing, _)| existing != name);
+        let cancelled = state.timers.len() != before;
+        state.stop_if_idle();
+        cancelled
+    }
+
+    /// Cancels every timer in the pool
+    pub fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+        state.timers.clear();
+        state.stop_if_idle();
+    }
+
+    /// The names of the running timers, in creation order
+    pub fn list(&self) -> js_sys::Array {
+        self.state
+            .borrow()
+            .timers
+            .iter()
+            .map(|(name, _)| JsValue::from_str(name))
+            .collect()
+    }
+
+    /// The remaining seconds of the timer named `name`, or `undefined` if it isn't running
+    pub fn remaining(&self, name: &str) -> Option<u32> {
+        let now = now_ms();
+        self.state
+            .borrow()
+            .timers
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, deadline)| remaining_secs(*deadline, now))
+    }
+
+    /// Gets the number of running timers
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.state.borrow().timers.len()
+    }
+
+    /// Sets a callback invoked every second with an array of
+    /// `{ name, remaining, formatted }` objects, one per running timer.
+    pub fn set_on_tick(&mut self, callback: js_sys::Function) {
+        self.state.borrow_mut().on_tick = Some(callback);
+    }
+
+    /// Sets a callback invoked with `(name)` when a timer reaches zero.
+    pub fn set_on_finish(&mut self, callback: js_sys::Function) {
+        self.state.borrow_mut().on_finish = Some(callback);
+    }
+}
+
+impl Drop for TimerPool {
+    // The interval callback is dropped with the pool, so the interval must not fire again
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.timers.clear();
+        state.stop_if_idle();
+    }
+}
+
+impl PoolState {
+    /// Clears the shared interval once no timers are left
+    fn stop_if_idle(&mut self) {
+        if !self.timers.is_empty() {
+            return;
+        }
         if let Some(interval_id) = self.interval_id.take() {
-            let window = web_sys::window().expect("should have a window in this context");
-            window.clear_interval_with_handle(interval_id);
+            clear_interval(&interval_id);
         }
+    }
+}
 
-        self.current_time
+/// Whole seconds left until `deadline`, rounded up so a timer shows 0 only once it's done
+fn remaining_secs(deadline: f64, now: f64) -> u32 {
+    ((deadline - now) / 1000.0).ceil().max(0.0) as u32
+}
+
+/// Reports every running timer in one tick event, then removes and announces finished ones
+fn tick_pool(state: &Rc<RefCell<PoolState>>) {
+    let now = now_ms();
+    // Callbacks may call back into the pool, so none run while the state is borrowed
+    let (ticks, finished, on_tick, on_finish) = {
+        let mut state = state.borrow_mut();
+        let ticks: Vec<(String, u32)> = state
+            .timers
+            .iter()
+            .map(|(name, deadline)| (name.clone(), remaining_secs(*deadline, now)))
+            .collect();
+        state
+            .timers
+            .retain(|(_, deadline)| remaining_secs(*deadline, now) > 0);
+        state.stop_if_idle();
+        let finished: Vec<String> = ticks
+            .iter()
+            .filter(|(_, remaining)| *remaining == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        (
+            ticks,
+            finished,
+            state.on_tick.clone(),
+            state.on_finish.clone(),
+        )
+    };
+
+    let event = js_sys::Array::new();
+    for (name, remaining) in &ticks {
+        let entry = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&entry, &"name".into(), &JsValue::from_str(name));
+        let _ = js_sys::Reflect::set(&entry, &"remaining".into(), &JsValue::from(*remaining));
+        let _ = js_sys::Reflect::set(
+            &entry,
+            &"formatted".into(),
+            &JsValue::from_str(&format_dhms((*remaining).into())),
+        );
+        event.push(&entry);
+    }
+    match on_tick {
+        Some(callback) => {
+            let _ = callback.call1(&JsValue::NULL, &event);
+        }
+        None => {
+            for (name, remaining) in &ticks {
+                web_sys::console::log_1(&JsValue::from_str(&format!(
+                    "{}: {}",
+                    name,
+                    format_dhms((*remaining).into())
+                )));
+            }
+        }
     }
 
-    /// Resets the stopwatch to zero
-    pub fn reset(&mut self) {
-        self.stop();
-        self.current_time = 0;
+    if let Some(callback) = on_finish {
+        for name in finished {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&name));
+        }
     }
 }
+
+/// Encodes a countdown ending `end_ms` milliseconds after the Unix epoch, as from
+/// `Date.now()`, and its label as a URL-safe share code
+#[wasm_bindgen(js_name = encodeShareCode)]
+pub fn encode_share_code(end_ms: f64, label: &str) -> String {
+    SharedCountdown::new(Duration::from_millis(end_ms.max(0.0) as u64), label).encode()
+}
+
+/// Decodes a share code into a frozen `{ endMs, label }` object, throwing on an invalid code
+#[wasm_bindgen(js_name = decodeShareCode)]
+pub fn decode_share_code(code: &str) -> Result<JsValue, JsValue> {
+    let shared = SharedCountdown::decode(code).map_err(JsValue::from_str)?;
+    Ok(frozen_object(&[
+        ("endMs", (shared.end.as_millis() as f64).into()),
+        ("label", shared.label.into()),
+    ]))
+}
@@ -0,0 +1,139 @@
+//! Tests of the JavaScript bindings, run inside a dedicated Web Worker where there is
+//! no `window`:
+//!
+//! ```bash
+//! wasm-pack test --headless --chrome
+//! ```
+
+#![cfg(target_arch = "wasm32")]
+
+use clock_timer::wasm::{Stopwatch, Timer, TimerPool, decode_share_code, encode_share_code};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_dedicated_worker);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+}
+
+/// Resolves after `ms` milliseconds
+async fn sleep(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        set_timeout(&resolve, ms);
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+/// Blocks the worker for `ms` milliseconds, so no timeout can fire in between
+fn busy_wait(ms: f64) {
+    let until = js_sys::Date::now() + ms;
+    while js_sys::Date::now() < until {}
+}
+
+#[wasm_bindgen_test]
+async fn timer_resolves_when_finished() {
+    let mut timer = Timer::new(0, 0, 1).unwrap();
+    timer.set_on_tick(js_sys::Function::new_no_args(""));
+    JsFuture::from(timer.start()).await.unwrap();
+    assert_eq!(timer.remaining(), 0);
+    assert!(!timer.is_running());
+}
+
+#[wasm_bindgen_test]
+async fn timer_rejects_when_cancelled() {
+    let timer = Timer::new(0, 0, 5).unwrap();
+    let finished = JsFuture::from(timer.start());
+    assert!(timer.cancel());
+    let reason = finished.await.unwrap_err();
+    assert_eq!(reason.as_string().as_deref(), Some("Timer cancelled"));
+}
+
+#[wasm_bindgen_test]
+async fn timer_catches_up_after_a_delayed_tick() {
+    let mut timer = Timer::new(0, 0, 5).unwrap();
+    timer.set_on_tick(js_sys::Function::new_no_args(""));
+    let _ = timer.start();
+    // Holding the worker stands in for a throttled background tab
+    busy_wait(2500.0);
+    sleep(100).await;
+    // 2.6 of 5 seconds have passed: one late tick skips straight to 3 seconds left
+    assert_eq!(timer.remaining(), 3);
+    timer.cancel();
+}
+
+#[wasm_bindgen_test]
+fn headless_timer_advances_on_demand() {
+    let timer = Timer::headless(0, 0, 3).unwrap();
+    let _ = timer.start();
+    timer.advance(1500.0).unwrap();
+    assert_eq!(timer.remaining(), 2);
+    timer.advance(1500.0).unwrap();
+    assert_eq!(timer.remaining(), 0);
+    assert!(Timer::new(0, 0, 1).unwrap().advance(1000.0).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn stopwatch_restarts_and_counts() {
+    let mut stopwatch = Stopwatch::new();
+    stopwatch.set_on_tick(js_sys::Function::new_no_args(""));
+    for _ in 0..3 {
+        stopwatch.start().unwrap();
+        sleep(50).await;
+        stopwatch.pause();
+    }
+    assert!(stopwatch.current_time_ms() >= 150.0);
+    stopwatch.resume().unwrap();
+    sleep(1100).await;
+    assert!(stopwatch.stop() >= 1);
+}
+
+#[wasm_bindgen_test]
+async fn dropped_stopwatch_stops_ticking() {
+    let ticks = js_sys::Array::new();
+    let mut stopwatch = Stopwatch::new();
+    stopwatch
+        .set_on_tick(js_sys::Function::new_with_args("elapsed", "this.push(elapsed)").bind(&ticks));
+    stopwatch.start().unwrap();
+    drop(stopwatch);
+    sleep(1100).await;
+    assert_eq!(ticks.length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn stopwatch_rejects_invalid_saved_state() {
+    let saved = Stopwatch::from_json(r#"{"accumulated_ms":1500,"started_at":null}"#).unwrap();
+    assert_eq!(saved.current_time(), 1);
+    for json in [
+        r#"{"accumulated_ms":1e400,"started_at":null}"#,
+        r#"{"accumulated_ms":0,"started_at":-1e400}"#,
+        r#"{"accumulated_ms":1e300,"started_at":null}"#,
+        r#"{"started_at":null}"#,
+    ] {
+        assert!(Stopwatch::from_json(json).is_err(), "{json}");
+    }
+}
+
+#[wasm_bindgen_test]
+async fn timer_pool_restarts_after_going_idle() {
+    let mut pool = TimerPool::new();
+    pool.set_on_tick(js_sys::Function::new_no_args(""));
+    for _ in 0..2 {
+        pool.create("tea", 0, 0, 1).unwrap();
+        assert_eq!(pool.size(), 1);
+        sleep(2100).await;
+        assert_eq!(pool.size(), 0);
+    }
+}
+
+#[wasm_bindgen_test]
+fn share_codes_round_trip() {
+    let code = encode_share_code(1_700_000_000_000.0, "launch");
+    let decoded = decode_share_code(&code).unwrap();
+    let label = js_sys::Reflect::get(&decoded, &"label".into()).unwrap();
+    assert_eq!(label.as_string().as_deref(), Some("launch"));
+    assert!(decode_share_code("not a code").is_err());
+}